@@ -1,11 +1,8 @@
+use std::any::TypeId;
+use std::collections::HashSet;
 use log::LevelFilter;
-use crate::{Context, DefaultDriver, Driver, Plugin};
-
-/// Function signature for system callbacks
-///
-/// Systems are functions that run every frame or once at startup.
-/// They receive mutable access to the engine context.
-pub type SystemFn = Box<dyn FnMut(&mut Context)>;
+use crate::schedule::Stage;
+use crate::{Context, DefaultDriver, Driver, Plugin, SystemAccess};
 
 /// Main application struct that manages the game loop and plugin lifecycle
 ///
@@ -30,11 +27,13 @@ pub struct App {
     /// Shared engine context (time, events, globals)
     context: Context,
     /// Systems that run once at startup
-    startup_systems: Vec<SystemFn>,
+    startup_systems: Stage,
     /// Systems that run every frame
-    systems: Vec<SystemFn>,
+    systems: Stage,
     /// System that run at the end of every frame
-    sync_systems: Vec<SystemFn>,
+    sync_systems: Stage,
+    /// Resource types that may only be touched from the main thread
+    main_thread_types: HashSet<TypeId>,
     /// Registered plugins
     plugins: Vec<Box<dyn Plugin>>,
     /// Custom game loop driver (defaults to DefaultDriver)
@@ -61,9 +60,10 @@ impl App {
 
         Self {
             context: Context::new(),
-            startup_systems: Vec::new(),
-            systems: Vec::new(),
-            sync_systems: Vec::new(),
+            startup_systems: Stage::default(),
+            systems: Stage::default(),
+            sync_systems: Stage::default(),
+            main_thread_types: HashSet::new(),
             plugins: Vec::new(),
             driver: Some(Box::new(DefaultDriver)),
         }
@@ -115,7 +115,7 @@ impl App {
     where
         F: FnMut(&mut Context) + 'static,
     {
-        self.startup_systems.push(Box::new(system));
+        self.startup_systems.add_system(Box::new(system));
         self
     }
 
@@ -127,7 +127,36 @@ impl App {
     where
         F: FnMut(&mut Context) + 'static,
     {
-        self.systems.push(Box::new(system));
+        self.systems.add_system(Box::new(system));
+        self
+    }
+
+    /// Adds a system that runs every frame and may run in parallel
+    ///
+    /// The system declares which component and resource types it reads and
+    /// writes. Systems whose accesses don't conflict are executed at the same
+    /// time on worker threads; conflicting ones keep their registration order.
+    /// The `World` is one resource, so declare it too (see `SystemAccess`);
+    /// borrowing an undeclared global panics.
+    ///
+    /// # Returns
+    /// Self reference for method chaining
+    ///
+    /// # Example
+    /// ```ignore
+    /// Kon::new()
+    ///     .add_parallel_system(SystemAccess::new().read::<World>().read::<Health>(), |ctx| {
+    ///         ctx.world_ref().select::<(Health,)>().each(|_, (hp,)| {
+    ///             println!("HP: {}", hp.0);
+    ///         });
+    ///     });
+    /// ```
+    pub fn add_parallel_system<F>(&mut self, access: SystemAccess, system: F) -> &mut Self
+    where
+        F: FnMut(&Context) + Send + 'static,
+    {
+        self.systems
+            .add_parallel_system(std::any::type_name::<F>(), Box::new(system), access);
         self
     }
 
@@ -139,7 +168,20 @@ impl App {
     where
         F: FnMut(&mut Context) + 'static,
     {
-        self.sync_systems.push(Box::new(system));
+        self.sync_systems.add_system(Box::new(system));
+        self
+    }
+
+    /// Pins a resource type to the main thread
+    ///
+    /// Parallel systems that declare access to this type always run on the
+    /// main thread. Used for resources wrapping thread-bound handles such as
+    /// the window.
+    ///
+    /// # Returns
+    /// Self reference for method chaining
+    pub fn pin_to_main_thread<R: 'static>(&mut self) -> &mut Self {
+        self.main_thread_types.insert(TypeId::of::<R>());
         self
     }
 
@@ -182,29 +224,25 @@ impl App {
         log::debug!("Registered {} active system(s)", self.systems.len());
 
         log::debug!("Executed {} startup system(s)", self.startup_systems.len());
-        for system in &mut self.startup_systems {
-            system(&mut self.context);
-        }
+        self.startup_systems
+            .run(&mut self.context, &self.main_thread_types);
     }
 
     /// Executes a single frame update
     ///
     /// Called automatically by the driver each frame. This method:
     /// 1. Updates time tracking
-    /// 2. Runs all registered systems
-    /// 3. Clears frame events
+    /// 2. Runs all registered systems (parallel ones in conflict-free batches)
+    /// 3. Runs sync systems
+    /// 4. Clears frame events
     ///
     /// Should not be called manually unless implementing a custom driver.
     pub fn tick(&mut self) {
         self.context.time.update();
 
-        for system in &mut self.systems {
-            system(&mut self.context);
-        }
-
-        for sync_system in &mut self.sync_systems {
-            sync_system(&mut self.context);
-        }
+        self.systems.run(&mut self.context, &self.main_thread_types);
+        self.sync_systems
+            .run(&mut self.context, &self.main_thread_types);
 
         self.context.events.clear_all();
     }
@@ -1,8 +1,92 @@
 use crate::events::AppQuit;
+use crate::schedule::{check_declared, running_system};
 use crate::{Event, Events, Time};
 use std::any::{Any, TypeId};
-use std::cell::{RefCell, RefMut};
 use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard, TryLockError};
+
+type GlobalBox = Box<dyn Any + Send + Sync>;
+
+/// Exclusive guard to a global resource
+///
+/// Returned by `Globals::get()` and `Context::global()`. Dereferences to the
+/// resource and releases the lock when dropped.
+pub struct GlobalMut<'a, G: ?Sized> {
+    _guard: RwLockWriteGuard<'a, GlobalBox>,
+    value: *mut G,
+}
+
+impl<'a, G: ?Sized> GlobalMut<'a, G> {
+    /// Narrows the guard to a part of the resource
+    ///
+    /// The lock stays held until the returned guard is dropped.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let difficulty = GlobalMut::map(ctx.global::<GameConfig>().unwrap(), |c| &mut c.difficulty);
+    /// ```
+    pub fn map<U: ?Sized>(this: Self, f: impl FnOnce(&mut G) -> &mut U) -> GlobalMut<'a, U> {
+        // SAFETY: `value` points into the boxed resource owned by the guard,
+        // which stays locked (and at the same heap address) for 'a.
+        let value = f(unsafe { &mut *this.value }) as *mut U;
+        GlobalMut {
+            _guard: this._guard,
+            value,
+        }
+    }
+}
+
+impl<G: ?Sized> Deref for GlobalMut<'_, G> {
+    type Target = G;
+
+    fn deref(&self) -> &G {
+        unsafe { &*self.value }
+    }
+}
+
+impl<G: ?Sized> DerefMut for GlobalMut<'_, G> {
+    fn deref_mut(&mut self) -> &mut G {
+        unsafe { &mut *self.value }
+    }
+}
+
+/// Shared guard to a global resource
+///
+/// Returned by `Globals::get_ref()` and `Context::global_ref()`. Several
+/// shared guards to the same resource can be held at once, which lets
+/// parallel systems read it concurrently.
+pub struct GlobalRef<'a, G: ?Sized> {
+    _guard: RwLockReadGuard<'a, GlobalBox>,
+    value: *const G,
+    _marker: PhantomData<&'a G>,
+}
+
+impl<'a, G: ?Sized> GlobalRef<'a, G> {
+    /// Narrows the guard to a part of the resource
+    ///
+    /// The lock stays held until the returned guard is dropped.
+    pub fn map<U: ?Sized>(this: Self, f: impl FnOnce(&G) -> &U) -> GlobalRef<'a, U> {
+        // SAFETY: `value` points into the boxed resource owned by the guard,
+        // which stays locked (and at the same heap address) for 'a.
+        let value = f(unsafe { &*this.value }) as *const U;
+        GlobalRef {
+            _guard: this._guard,
+            value,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<G: ?Sized> Deref for GlobalRef<'_, G> {
+    type Target = G;
+
+    fn deref(&self) -> &G {
+        unsafe { &*self.value }
+    }
+}
 
 /// Type-erased storage for engine-wide resources
 ///
@@ -27,10 +111,18 @@ use std::collections::HashMap;
 /// let mut config = ctx.global::<GameConfig>().unwrap();
 /// config.difficulty = 3;
 /// println!("Difficulty: {}", config.difficulty); // 3
+///
+/// # Borrowing
+/// Each resource sits behind a read-write lock. Borrowing a resource that is
+/// already borrowed panics (like a `RefCell`), also inside a parallel batch:
+/// systems of a batch never wait for each other, so a resource two of them
+/// use must be declared in their `SystemAccess`.
 #[derive(Default)]
 pub struct Globals {
     /// TypeId -> boxed resource mapping
-    data: HashMap<TypeId, RefCell<Box<dyn Any + Send + Sync>>>,
+    data: HashMap<TypeId, RwLock<GlobalBox>>,
+    /// Set while the executor runs systems on worker threads
+    shared: AtomicBool,
 }
 
 impl Globals {
@@ -43,17 +135,57 @@ impl Globals {
     /// If a resource of this type already exists, it will be replaced.
     pub fn register<G: Any + Send + Sync + 'static>(&mut self, global: G) {
         self.data
-            .insert(TypeId::of::<G>(), RefCell::new(Box::new(global)));
+            .insert(TypeId::of::<G>(), RwLock::new(Box::new(global)));
     }
 
     /// Gets a reference to a global resource
     ///
     /// Returns `None` if type not registered
-    pub fn get<G: Any + Send + Sync + 'static>(&self) -> Option<RefMut<'_, G>> {
-        self.data.get(&TypeId::of::<G>()).map(|cell| {
-            RefMut::map(cell.borrow_mut(), |boxed| {
-                boxed.downcast_mut::<G>().expect("Resource type mismatch")
-            })
+    ///
+    /// # Panics
+    /// Panics if the resource is already borrowed, or if a parallel system
+    /// borrows it without declaring `write::<G>()`
+    #[track_caller]
+    pub fn get<G: Any + Send + Sync + 'static>(&self) -> Option<GlobalMut<'_, G>> {
+        check_declared::<G>(true);
+        self.data.get(&TypeId::of::<G>()).map(|lock| {
+            let mut guard = match lock.try_write() {
+                Ok(guard) => guard,
+                Err(TryLockError::Poisoned(err)) => err.into_inner(),
+                Err(TryLockError::WouldBlock) => self.already_borrowed::<G>("borrowed"),
+            };
+
+            let value = guard.downcast_mut::<G>().expect("Resource type mismatch") as *mut G;
+            GlobalMut {
+                _guard: guard,
+                value,
+            }
+        })
+    }
+
+    /// Gets a shared reference to a global resource
+    ///
+    /// Returns `None` if type not registered
+    ///
+    /// # Panics
+    /// Panics if the resource is mutably borrowed, or if a parallel system
+    /// borrows it without declaring `read::<G>()` or `write::<G>()`
+    #[track_caller]
+    pub fn get_ref<G: Any + Send + Sync + 'static>(&self) -> Option<GlobalRef<'_, G>> {
+        check_declared::<G>(false);
+        self.data.get(&TypeId::of::<G>()).map(|lock| {
+            let guard = match lock.try_read() {
+                Ok(guard) => guard,
+                Err(TryLockError::Poisoned(err)) => err.into_inner(),
+                Err(TryLockError::WouldBlock) => self.already_borrowed::<G>("mutably borrowed"),
+            };
+
+            let value = guard.downcast_ref::<G>().expect("Resource type mismatch") as *const G;
+            GlobalRef {
+                _guard: guard,
+                value,
+                _marker: PhantomData,
+            }
         })
     }
 
@@ -68,12 +200,42 @@ impl Globals {
     pub fn remove<G: Any + Send + Sync + 'static>(&mut self) -> Option<G> {
         self.data
             .remove(&TypeId::of::<G>())
-            .and_then(|cell| {
-                let boxed = cell.into_inner();
+            .and_then(|lock| {
+                let boxed = lock.into_inner().unwrap_or_else(PoisonError::into_inner);
                 boxed.downcast::<G>().ok()
             })
             .map(|boxed| *boxed)
     }
+
+    /// Returns true while systems are running on worker threads
+    pub(crate) fn is_shared(&self) -> bool {
+        self.shared.load(Ordering::Acquire)
+    }
+
+    /// Panics for a resource that can't be borrowed
+    ///
+    /// Inside a parallel batch the borrow is held by the same system
+    /// (re-entrant access) or by a system running alongside it that didn't
+    /// declare the access, so the message names the system.
+    #[track_caller]
+    fn already_borrowed<G: 'static>(&self, state: &str) -> ! {
+        let name = std::any::type_name::<G>();
+        if self.is_shared() {
+            panic!(
+                "Global '{}' is already {} while running parallel system '{}'. \
+                 Declare the access in its SystemAccess so conflicting systems don't run at the same time",
+                name,
+                state,
+                running_system().unwrap_or("<unknown>")
+            );
+        }
+        panic!("Global '{}' is already {}", name, state);
+    }
+
+    /// Switches between exclusive (main thread) and shared (parallel batch) borrowing
+    pub(crate) fn set_shared(&self, shared: bool) {
+        self.shared.store(shared, Ordering::Release);
+    }
 }
 
 /// Main context passed to all systems
@@ -147,10 +309,19 @@ impl Context {
     }
 
     /// Gets a reference to a global resource
-    pub fn global<G: Any + Send + Sync + 'static>(&self) -> Option<RefMut<'_, G>> {
+    #[track_caller]
+    pub fn global<G: Any + Send + Sync + 'static>(&self) -> Option<GlobalMut<'_, G>> {
         self.globals.get()
     }
 
+    /// Gets a shared reference to a global resource
+    ///
+    /// Prefer this in parallel systems that only read the resource.
+    #[track_caller]
+    pub fn global_ref<G: Any + Send + Sync + 'static>(&self) -> Option<GlobalRef<'_, G>> {
+        self.globals.get_ref()
+    }
+
     /// Convenience method for reading events
     ///
     /// # Example
@@ -8,16 +8,18 @@ mod driver;
 mod event;
 pub mod events;
 mod plugin;
+mod schedule;
 mod time;
 
 pub use app::{App, Kon};
-pub use context::{Context, Globals};
+pub use context::{Context, GlobalMut, GlobalRef, Globals};
 pub use event::{Event, Events};
 pub use plugin::Plugin;
+pub use schedule::{ParallelSystemFn, SystemAccess, SystemFn};
 pub use time::Time;
 pub use driver::{DefaultDriver, Driver};
 
 pub mod prelude {
-    pub use crate::{App, Context, Event, Events, Kon, Plugin, Time, Driver};
+    pub use crate::{App, Context, Event, Events, Kon, Plugin, SystemAccess, Time, Driver};
     pub use crate::events::*;
 }
@@ -0,0 +1,535 @@
+//! System scheduling and the parallel executor
+//!
+//! Systems added with `add_system()` take `&mut Context` and run one after another.
+//! Systems added with `add_parallel_system()` declare the data they touch through
+//! `SystemAccess`, which lets the executor run non-conflicting systems at the same time.
+//!
+//! # World access
+//! Globals are locked as a whole, and the ECS `World` is a single global. Component
+//! declarations order systems but don't split the World lock: systems calling
+//! `ctx.world()` must also declare `write::<World>()` and then never run at the same
+//! time. Read-only systems declaring `read::<World>()` and using `ctx.world_ref()` do
+//! run in parallel, as do systems working on other resources.
+//!
+//! A parallel system borrowing a global it didn't declare panics on the borrow,
+//! every time it runs, whether or not another system holds the lock.
+
+use crate::Context;
+use std::any::TypeId;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Name and declared access of a running parallel system
+type Running = (&'static str, Arc<SystemAccess>);
+
+thread_local! {
+    /// Parallel system running on this thread, for borrow checks and panics
+    static RUNNING_SYSTEM: RefCell<Option<Running>> = const { RefCell::new(None) };
+}
+
+/// Returns the name of the parallel system running on the current thread
+pub(crate) fn running_system() -> Option<&'static str> {
+    RUNNING_SYSTEM.with(|running| running.borrow().as_ref().map(|(name, _)| *name))
+}
+
+/// Panics if the parallel system running on this thread didn't declare `G`
+///
+/// A write borrow needs `write::<G>()`, a read borrow either declaration.
+#[track_caller]
+pub(crate) fn check_declared<G: 'static>(write: bool) {
+    let type_id = TypeId::of::<G>();
+    let undeclared = RUNNING_SYSTEM.with(|running| match &*running.borrow() {
+        Some((name, access))
+            if !access.writes.contains(&type_id) && (write || !access.reads.contains(&type_id)) =>
+        {
+            Some(*name)
+        }
+        _ => None,
+    });
+
+    if let Some(name) = undeclared {
+        panic!(
+            "Parallel system '{}' borrowed global '{}' without declaring it. \
+             Add `{}::<{}>()` to its SystemAccess",
+            name,
+            std::any::type_name::<G>(),
+            if write { "write" } else { "read" },
+            std::any::type_name::<G>()
+        );
+    }
+}
+
+/// Marks a parallel system as running on the current thread until dropped
+struct RunningSystem(Option<Running>);
+
+impl RunningSystem {
+    fn enter(name: &'static str, access: Arc<SystemAccess>) -> Self {
+        Self(RUNNING_SYSTEM.with(|running| running.replace(Some((name, access)))))
+    }
+}
+
+impl Drop for RunningSystem {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        RUNNING_SYSTEM.with(|running| running.replace(previous));
+    }
+}
+
+/// Leaves shared borrowing mode when dropped, also while unwinding
+struct SharedMode<'a>(&'a Context);
+
+impl<'a> SharedMode<'a> {
+    fn enter(ctx: &'a Context) -> Self {
+        ctx.globals.set_shared(true);
+        Self(ctx)
+    }
+}
+
+impl Drop for SharedMode<'_> {
+    fn drop(&mut self) {
+        self.0.globals.set_shared(false);
+    }
+}
+
+/// Function signature for system callbacks
+///
+/// Systems are functions that run every frame or once at startup.
+/// They receive mutable access to the engine context.
+pub type SystemFn = Box<dyn FnMut(&mut Context)>;
+
+/// Function signature for parallel system callbacks
+///
+/// Parallel systems receive shared access to the context so several of them
+/// can run on worker threads at once. Globals are still reached through
+/// `ctx.global()` / `ctx.global_ref()`, which lock the resource.
+pub type ParallelSystemFn = Box<dyn FnMut(&Context) + Send>;
+
+/// Declared data access of a parallel system
+///
+/// Reads and writes are keyed by type, so both component types and
+/// global resources can be listed. Two systems conflict when one of them
+/// writes a type the other reads or writes. Every global a system borrows
+/// must be declared, including `World` (see the module docs); an undeclared
+/// borrow panics.
+///
+/// # Example
+/// ```ignore
+/// Kon::new()
+///     .add_parallel_system(
+///         SystemAccess::new().write::<World>().read::<Velocity>().write::<Position>(),
+///         movement,
+///     )
+///     .add_parallel_system(SystemAccess::new().read::<World>().read::<Health>(), health_bar);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct SystemAccess {
+    reads: HashSet<TypeId>,
+    writes: HashSet<TypeId>,
+    main_thread: bool,
+}
+
+impl SystemAccess {
+    /// Creates an empty access declaration
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Declares shared access to a component or resource type
+    pub fn read<T: 'static>(mut self) -> Self {
+        self.reads.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// Declares exclusive access to a component or resource type
+    pub fn write<T: 'static>(mut self) -> Self {
+        self.writes.insert(TypeId::of::<T>());
+        self
+    }
+
+    /// Forces the system to run on the main thread
+    ///
+    /// Systems touching a type pinned via `App::pin_to_main_thread()`
+    /// are moved to the main thread automatically.
+    pub fn main_thread(mut self) -> Self {
+        self.main_thread = true;
+        self
+    }
+
+    /// Returns true if both systems cannot run at the same time
+    pub fn conflicts_with(&self, other: &SystemAccess) -> bool {
+        self.writes
+            .iter()
+            .any(|t| other.writes.contains(t) || other.reads.contains(t))
+            || other.writes.iter().any(|t| self.reads.contains(t))
+    }
+
+    /// Returns true if the system must run on the main thread
+    fn is_main_thread(&self, pinned: &HashSet<TypeId>) -> bool {
+        self.main_thread
+            || self
+                .reads
+                .iter()
+                .chain(&self.writes)
+                .any(|t| pinned.contains(t))
+    }
+}
+
+/// A system registered in a stage
+enum StageSystem {
+    Exclusive(SystemFn),
+    Parallel {
+        name: &'static str,
+        system: ParallelSystemFn,
+        access: Arc<SystemAccess>,
+    },
+}
+
+/// A parallel system picked for the current batch
+type BatchSystem<'a> = (&'static str, Arc<SystemAccess>, &'a mut ParallelSystemFn);
+
+/// One step of the execution plan
+#[derive(Debug, Clone, PartialEq)]
+enum Step {
+    /// Runs a single exclusive system
+    Exclusive(usize),
+    /// Runs a batch of mutually non-conflicting parallel systems
+    Batch(Vec<usize>),
+}
+
+/// Ordered list of systems executed together (startup, update or sync)
+///
+/// Exclusive systems act as barriers. Consecutive parallel systems between
+/// two barriers are split into batches: a system goes into the first batch
+/// after every earlier system it conflicts with, so registration order is
+/// kept wherever it matters.
+#[derive(Default)]
+pub(crate) struct Stage {
+    systems: Vec<StageSystem>,
+    /// Cached execution plan, rebuilt when systems are added
+    plan: Option<Vec<Step>>,
+}
+
+impl Stage {
+    pub(crate) fn add_system(&mut self, system: SystemFn) {
+        self.systems.push(StageSystem::Exclusive(system));
+        self.plan = None;
+    }
+
+    pub(crate) fn add_parallel_system(
+        &mut self,
+        name: &'static str,
+        system: ParallelSystemFn,
+        access: SystemAccess,
+    ) {
+        self.systems.push(StageSystem::Parallel {
+            name,
+            system,
+            access: Arc::new(access),
+        });
+        self.plan = None;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.systems.len()
+    }
+
+    /// Builds the execution plan from the declared accesses
+    fn build_plan(&self) -> Vec<Step> {
+        let mut plan = Vec::new();
+        let mut segment: Vec<(usize, usize)> = Vec::new();
+
+        let flush = |segment: &mut Vec<(usize, usize)>, plan: &mut Vec<Step>| {
+            let batch_count = segment.iter().map(|&(_, b)| b + 1).max().unwrap_or(0);
+            for batch in 0..batch_count {
+                plan.push(Step::Batch(
+                    segment
+                        .iter()
+                        .filter(|&&(_, b)| b == batch)
+                        .map(|&(i, _)| i)
+                        .collect(),
+                ));
+            }
+            segment.clear();
+        };
+
+        for (index, system) in self.systems.iter().enumerate() {
+            match system {
+                StageSystem::Exclusive(_) => {
+                    flush(&mut segment, &mut plan);
+                    plan.push(Step::Exclusive(index));
+                }
+                StageSystem::Parallel { access, .. } => {
+                    let batch = segment
+                        .iter()
+                        .filter(|&&(other, _)| match &self.systems[other] {
+                            StageSystem::Parallel { access: other, .. } => {
+                                access.conflicts_with(other)
+                            }
+                            StageSystem::Exclusive(_) => false,
+                        })
+                        .map(|&(_, b)| b + 1)
+                        .max()
+                        .unwrap_or(0);
+                    segment.push((index, batch));
+                }
+            }
+        }
+
+        flush(&mut segment, &mut plan);
+        plan
+    }
+
+    /// Runs every system of the stage once
+    ///
+    /// Main-thread systems of a batch run on the calling thread while the
+    /// rest are spawned on scoped worker threads.
+    pub(crate) fn run(&mut self, ctx: &mut Context, pinned: &HashSet<TypeId>) {
+        let plan = match self.plan.take() {
+            Some(plan) => plan,
+            None => self.build_plan(),
+        };
+
+        for step in &plan {
+            match step {
+                Step::Exclusive(index) => {
+                    if let StageSystem::Exclusive(system) = &mut self.systems[*index] {
+                        system(ctx);
+                    }
+                }
+                Step::Batch(indices) => self.run_batch(indices, ctx, pinned),
+            }
+        }
+
+        self.plan = Some(plan);
+    }
+
+    fn run_batch(&mut self, indices: &[usize], ctx: &mut Context, pinned: &HashSet<TypeId>) {
+        let mut main_thread = Vec::new();
+        let mut workers = Vec::new();
+
+        for (index, system) in self.systems.iter_mut().enumerate() {
+            if let StageSystem::Parallel {
+                name,
+                system,
+                access,
+            } = system
+                && indices.contains(&index)
+            {
+                let running = (*name, Arc::clone(access), system);
+                if access.is_main_thread(pinned) {
+                    main_thread.push(running);
+                } else {
+                    workers.push(running);
+                }
+            }
+        }
+
+        let run = |(name, access, system): BatchSystem<'_>, ctx: &Context| {
+            let _running = RunningSystem::enter(name, access);
+            system(ctx);
+        };
+
+        // A single system gains nothing from a worker thread
+        if workers.len() + main_thread.len() <= 1 {
+            for system in main_thread.into_iter().chain(workers) {
+                run(system, ctx);
+            }
+            return;
+        }
+
+        let ctx: &Context = ctx;
+        let _shared = SharedMode::enter(ctx);
+
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = workers
+                .into_iter()
+                .map(|system| scope.spawn(move || run(system, ctx)))
+                .collect();
+
+            for system in main_thread {
+                run(system, ctx);
+            }
+
+            for handle in handles {
+                if let Err(payload) = handle.join() {
+                    std::panic::resume_unwind(payload);
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::thread::ThreadId;
+
+    struct Position;
+    struct Velocity;
+    struct Window;
+
+    fn noop() -> ParallelSystemFn {
+        Box::new(|_| {})
+    }
+
+    #[test]
+    fn access_conflicts() {
+        let read_pos = SystemAccess::new().read::<Position>();
+        let write_pos = SystemAccess::new().write::<Position>();
+        let write_vel = SystemAccess::new().write::<Velocity>();
+
+        assert!(!read_pos.conflicts_with(&read_pos));
+        assert!(read_pos.conflicts_with(&write_pos));
+        assert!(write_pos.conflicts_with(&read_pos));
+        assert!(write_pos.conflicts_with(&write_pos));
+        assert!(!write_pos.conflicts_with(&write_vel));
+    }
+
+    #[test]
+    fn plan_batches_non_conflicting_systems() {
+        let mut stage = Stage::default();
+        stage.add_parallel_system("noop", noop(), SystemAccess::new().write::<Position>());
+        stage.add_parallel_system("noop", noop(), SystemAccess::new().write::<Velocity>());
+        stage.add_parallel_system("noop", noop(), SystemAccess::new().read::<Position>());
+
+        assert_eq!(
+            stage.build_plan(),
+            vec![Step::Batch(vec![0, 1]), Step::Batch(vec![2])]
+        );
+    }
+
+    #[test]
+    fn exclusive_system_is_a_barrier() {
+        let mut stage = Stage::default();
+        stage.add_parallel_system("noop", noop(), SystemAccess::new().read::<Position>());
+        stage.add_system(Box::new(|_| {}));
+        stage.add_parallel_system("noop", noop(), SystemAccess::new().read::<Position>());
+
+        assert_eq!(
+            stage.build_plan(),
+            vec![
+                Step::Batch(vec![0]),
+                Step::Exclusive(1),
+                Step::Batch(vec![2])
+            ]
+        );
+    }
+
+    #[test]
+    fn runs_all_systems_in_batch() {
+        let counter = Arc::new(AtomicUsize::new(0));
+        let mut stage = Stage::default();
+
+        for _ in 0..4 {
+            let counter = counter.clone();
+            stage.add_parallel_system(
+                "system",
+                Box::new(move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }),
+                SystemAccess::new().read::<Position>(),
+            );
+        }
+
+        let mut ctx = Context::new();
+        stage.run(&mut ctx, &HashSet::new());
+        stage.run(&mut ctx, &HashSet::new());
+
+        assert_eq!(counter.load(Ordering::SeqCst), 8);
+    }
+
+    #[test]
+    fn pinned_systems_run_on_main_thread() {
+        let seen: Arc<Mutex<Option<ThreadId>>> = Arc::new(Mutex::new(None));
+        let mut stage = Stage::default();
+
+        let seen_clone = seen.clone();
+        stage.add_parallel_system(
+            "system",
+            Box::new(move |_| {
+                *seen_clone.lock().unwrap() = Some(std::thread::current().id());
+            }),
+            SystemAccess::new().write::<Window>(),
+        );
+        stage.add_parallel_system("noop", noop(), SystemAccess::new().write::<Position>());
+
+        let pinned: HashSet<TypeId> = [TypeId::of::<Window>()].into();
+        let mut ctx = Context::new();
+        stage.run(&mut ctx, &pinned);
+
+        assert_eq!(*seen.lock().unwrap(), Some(std::thread::current().id()));
+    }
+
+    #[test]
+    fn parallel_systems_share_globals() {
+        let mut stage = Stage::default();
+        for _ in 0..4 {
+            stage.add_parallel_system(
+                "system",
+                Box::new(|ctx| {
+                    *ctx.global::<usize>().unwrap() += 1;
+                }),
+                SystemAccess::new().write::<usize>(),
+            );
+        }
+
+        let mut ctx = Context::new();
+        ctx.register(0usize);
+        stage.run(&mut ctx, &HashSet::new());
+
+        assert_eq!(*ctx.global::<usize>().unwrap(), 4);
+    }
+
+    #[test]
+    fn undeclared_borrow_conflict_names_the_system() {
+        let mut stage = Stage::default();
+        stage.add_parallel_system(
+            "reentrant",
+            Box::new(|ctx| {
+                let _outer = ctx.global::<usize>().unwrap();
+                let _inner = ctx.global::<usize>();
+            }),
+            SystemAccess::new().write::<usize>(),
+        );
+        stage.add_parallel_system("noop", noop(), SystemAccess::new().write::<Position>());
+
+        let mut ctx = Context::new();
+        ctx.register(0usize);
+        let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            stage.run(&mut ctx, &HashSet::new());
+        }))
+        .unwrap_err();
+
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert!(message.contains("reentrant"), "{message}");
+        assert!(!ctx.globals.is_shared());
+    }
+
+    #[test]
+    fn undeclared_global_borrow_always_panics() {
+        let mut stage = Stage::default();
+        stage.add_parallel_system(
+            "mover",
+            Box::new(|ctx| {
+                assert!(ctx.global_ref::<usize>().is_some());
+                let _count = ctx.global::<usize>();
+            }),
+            SystemAccess::new().write::<Position>().read::<usize>(),
+        );
+
+        let mut ctx = Context::new();
+        ctx.register(0usize);
+        let payload = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            stage.run(&mut ctx, &HashSet::new());
+        }))
+        .unwrap_err();
+
+        let message = payload.downcast_ref::<String>().unwrap();
+        assert!(message.contains("mover"), "{message}");
+        assert!(message.contains("write::<usize>()"), "{message}");
+        assert_eq!(running_system(), None);
+    }
+}
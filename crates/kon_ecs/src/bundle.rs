@@ -0,0 +1,394 @@
+//! Component bundles
+//!
+//! A bundle is a statically typed group of components (and tags) inserted or
+//! removed together. Bundles are implemented for tuples of 1-12 components and
+//! can be derived on structs with `#[derive(Bundle)]`.
+//!
+//! # Example
+//! ```ignore
+//! #[derive(Bundle)]
+//! #[bundle(tags = ["player", "friendly"])]
+//! struct PlayerBundle {
+//!     position: Position,
+//!     velocity: Velocity,
+//!     health: Health,
+//! }
+//!
+//! let player = world.spawn_bundle(PlayerBundle {
+//!     position: Position { x: 0.0, y: 0.0 },
+//!     velocity: Velocity { x: 1.0, y: 0.0 },
+//!     health: Health(100),
+//! });
+//!
+//! // Tuples work too
+//! let rock = world.spawn_bundle((Position { x: 5.0, y: 5.0 }, Health(10)));
+//!
+//! world.remove_bundle::<PlayerBundle>(player);
+//! ```
+
+use crate::entity::{Entity, EntityBuilder};
+use crate::{Component, World};
+use std::any::TypeId;
+
+/// A group of components inserted and removed together
+///
+/// Implemented for tuples of components and derivable with `#[derive(Bundle)]`.
+pub trait Bundle: Send + Sync + 'static {
+    /// Inserts every component of the bundle into the entity
+    fn insert_into(self, world: &mut World, entity: Entity);
+
+    /// Removes every component type of the bundle from the entity
+    fn remove_from(world: &mut World, entity: Entity);
+
+    /// Returns the component types contained in the bundle
+    fn type_ids() -> Vec<TypeId>;
+
+    /// Returns the tags attached together with the bundle
+    fn tags() -> &'static [&'static str] {
+        &[]
+    }
+
+    /// Inserts a batch of bundles, one entity each
+    ///
+    /// The default inserts bundle by bundle. Tuple and derived bundles split
+    /// the batch into one column per component and use `World::insert_batch`.
+    fn insert_batch_into(world: &mut World, batch: Vec<(Entity, Self)>)
+    where
+        Self: Sized,
+    {
+        for (entity, bundle) in batch {
+            bundle.insert_into(world, entity);
+        }
+    }
+}
+
+// Generates Bundle implementations for tuples of 1-12 components.
+macro_rules! impl_bundle_tuple {
+    ($(($name:ident, $column:ident)),+) => {
+        impl<$($name: Component),+> Bundle for ($($name,)+) {
+            fn insert_into(self, world: &mut World, entity: Entity) {
+                #[allow(non_snake_case)]
+                let ($($name,)+) = self;
+                $(world.insert(entity, $name);)+
+            }
+
+            fn remove_from(world: &mut World, entity: Entity) {
+                $(world.remove::<$name>(entity);)+
+            }
+
+            fn type_ids() -> Vec<TypeId> {
+                vec![$(TypeId::of::<$name>()),+]
+            }
+
+            fn insert_batch_into(world: &mut World, batch: Vec<(Entity, Self)>) {
+                $(let mut $column: Vec<(Entity, $name)> = Vec::with_capacity(batch.len());)+
+
+                for (entity, bundle) in batch {
+                    #[allow(non_snake_case)]
+                    let ($($name,)+) = bundle;
+                    $($column.push((entity, $name));)+
+                }
+
+                $(world.insert_batch($column);)+
+            }
+        }
+    };
+}
+
+impl_bundle_tuple!((A, a));
+impl_bundle_tuple!((A, a), (B, b));
+impl_bundle_tuple!((A, a), (B, b), (C, c));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d), (E, e));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f));
+impl_bundle_tuple!((A, a), (B, b), (C, c), (D, d), (E, e), (F, f), (G, g));
+impl_bundle_tuple!(
+    (A, a),
+    (B, b),
+    (C, c),
+    (D, d),
+    (E, e),
+    (F, f),
+    (G, g),
+    (H, h)
+);
+impl_bundle_tuple!(
+    (A, a),
+    (B, b),
+    (C, c),
+    (D, d),
+    (E, e),
+    (F, f),
+    (G, g),
+    (H, h),
+    (I, i)
+);
+impl_bundle_tuple!(
+    (A, a),
+    (B, b),
+    (C, c),
+    (D, d),
+    (E, e),
+    (F, f),
+    (G, g),
+    (H, h),
+    (I, i),
+    (J, j)
+);
+impl_bundle_tuple!(
+    (A, a),
+    (B, b),
+    (C, c),
+    (D, d),
+    (E, e),
+    (F, f),
+    (G, g),
+    (H, h),
+    (I, i),
+    (J, j),
+    (K, k)
+);
+impl_bundle_tuple!(
+    (A, a),
+    (B, b),
+    (C, c),
+    (D, d),
+    (E, e),
+    (F, f),
+    (G, g),
+    (H, h),
+    (I, i),
+    (J, j),
+    (K, k),
+    (L, l)
+);
+
+impl World {
+    /// Spawns a new entity from a bundle and returns its handle
+    ///
+    /// The bundle's tags are attached as well.
+    pub fn spawn_bundle<B: Bundle>(&mut self, bundle: B) -> Entity {
+        let entity = self.spawn().id();
+        self.insert_bundle(entity, bundle);
+        entity
+    }
+
+    /// Spawns one entity per bundle and returns their handles in order
+    ///
+    /// Entity capacity and component storages are reserved once for the
+    /// whole batch, making this the fastest way to create many entities.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let particles = world.spawn_batch((0..50_000).map(|i| {
+    ///     (Position { x: i as f32, y: 0.0 }, Velocity { x: 0.0, y: 1.0 })
+    /// }));
+    /// ```
+    pub fn spawn_batch<B, I>(&mut self, bundles: I) -> Vec<Entity>
+    where
+        B: Bundle,
+        I: IntoIterator<Item = B>,
+    {
+        let bundles = bundles.into_iter();
+        self.reserve_entities(bundles.size_hint().0);
+
+        let batch: Vec<(Entity, B)> = bundles.map(|bundle| (self.spawn().id(), bundle)).collect();
+        let entities: Vec<Entity> = batch.iter().map(|(entity, _)| *entity).collect();
+
+        B::insert_batch_into(self, batch);
+        for tag in B::tags() {
+            for &entity in &entities {
+                self.tag(entity, *tag);
+            }
+        }
+
+        entities
+    }
+
+    /// Inserts all components and tags of a bundle into an entity
+    ///
+    /// Existing components of the same types are replaced.
+    /// Does nothing if the entity is not alive.
+    pub fn insert_bundle<B: Bundle>(&mut self, entity: Entity, bundle: B) {
+        if !self.is_alive(entity) {
+            return;
+        }
+
+        bundle.insert_into(self, entity);
+        for tag in B::tags() {
+            self.tag(entity, *tag);
+        }
+    }
+
+    /// Removes all component types and tags of a bundle from an entity
+    ///
+    /// Returns false if the entity is not alive.
+    pub fn remove_bundle<B: Bundle>(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        B::remove_from(self, entity);
+        for tag in B::tags() {
+            self.untag(entity, *tag);
+        }
+
+        true
+    }
+
+    /// Checks if an entity has every component type of a bundle
+    pub fn has_bundle<B: Bundle>(&self, entity: Entity) -> bool {
+        B::type_ids()
+            .iter()
+            .all(|type_id| self.has_by_type_id(entity, type_id))
+    }
+}
+
+impl<'w> EntityBuilder<'w> {
+    /// Attaches all components and tags of a bundle to the entity
+    pub fn insert_bundle<B: Bundle>(self, bundle: B) -> Self {
+        let (world, entity) = self.into_parts();
+        world.insert_bundle(entity, bundle);
+        EntityBuilder::new(world, entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Velocity {
+        x: f32,
+        y: f32,
+    }
+
+    struct PlayerBundle {
+        health: Health,
+        position: Position,
+    }
+
+    impl Bundle for PlayerBundle {
+        fn insert_into(self, world: &mut World, entity: Entity) {
+            world.insert(entity, self.health);
+            world.insert(entity, self.position);
+        }
+
+        fn remove_from(world: &mut World, entity: Entity) {
+            world.remove::<Health>(entity);
+            world.remove::<Position>(entity);
+        }
+
+        fn type_ids() -> Vec<TypeId> {
+            vec![TypeId::of::<Health>(), TypeId::of::<Position>()]
+        }
+
+        fn tags() -> &'static [&'static str] {
+            &["player"]
+        }
+    }
+
+    #[derive(kon_macros::Bundle)]
+    #[bundle(tags = ["enemy"])]
+    struct EnemyBundle(Health, Velocity);
+
+    #[test]
+    fn derived_bundle() {
+        let mut world = World::new();
+        let entity = world.spawn_bundle(EnemyBundle(Health(5), Velocity { x: 1.0, y: 0.0 }));
+        let batch = world.spawn_batch(vec![EnemyBundle(Health(1), Velocity { x: 0.0, y: 1.0 })]);
+
+        assert!(world.has_bundle::<EnemyBundle>(entity));
+        assert!(world.has_tag(entity, "enemy"));
+        assert_eq!(world.get::<Health>(batch[0]), Some(&Health(1)));
+
+        assert!(world.remove_bundle::<EnemyBundle>(entity));
+        assert!(!world.has::<Velocity>(entity));
+        assert!(!world.has_tag(entity, "enemy"));
+    }
+
+    #[test]
+    fn spawn_tuple_bundle() {
+        let mut world = World::new();
+        let entity = world.spawn_bundle((Health(10), Position { x: 1.0, y: 2.0 }));
+
+        assert_eq!(world.get::<Health>(entity), Some(&Health(10)));
+        assert!(world.has_bundle::<(Health, Position)>(entity));
+        assert!(!world.has_bundle::<(Health, Velocity)>(entity));
+    }
+
+    #[test]
+    fn bundle_tags_are_applied_and_removed() {
+        let mut world = World::new();
+        let entity = world
+            .spawn()
+            .insert_bundle(PlayerBundle {
+                health: Health(100),
+                position: Position { x: 0.0, y: 0.0 },
+            })
+            .insert(Velocity { x: 1.0, y: 0.0 })
+            .id();
+
+        assert!(world.has_tag(entity, "player"));
+        assert!(world.has_bundle::<PlayerBundle>(entity));
+
+        assert!(world.remove_bundle::<PlayerBundle>(entity));
+
+        assert!(!world.has_tag(entity, "player"));
+        assert!(!world.has::<Health>(entity));
+        assert!(!world.has::<Position>(entity));
+        assert!(world.has::<Velocity>(entity));
+    }
+
+    #[test]
+    fn spawn_batch_returns_entities_in_order() {
+        let mut world = World::new();
+        let entities = world.spawn_batch((0..100).map(|i| {
+            (
+                Health(i),
+                Position {
+                    x: i as f32,
+                    y: 0.0,
+                },
+            )
+        }));
+
+        assert_eq!(entities.len(), 100);
+        assert_eq!(world.entity_count(), 100);
+        for (i, &entity) in entities.iter().enumerate() {
+            assert_eq!(world.get::<Health>(entity), Some(&Health(i as i32)));
+        }
+    }
+
+    #[test]
+    fn spawn_batch_applies_tags() {
+        let mut world = World::new();
+        let entities = world.spawn_batch((0..3).map(|i| PlayerBundle {
+            health: Health(i),
+            position: Position { x: 0.0, y: 0.0 },
+        }));
+
+        assert!(entities.iter().all(|&e| world.has_tag(e, "player")));
+    }
+
+    #[test]
+    fn bundle_on_dead_entity_ignored() {
+        let mut world = World::new();
+        let entity = world.spawn().id();
+        world.destroy(entity);
+
+        world.insert_bundle(entity, (Health(1),));
+
+        assert!(!world.has::<Health>(entity));
+        assert!(!world.remove_bundle::<(Health,)>(entity));
+    }
+}
@@ -0,0 +1,130 @@
+//! Component change detection
+//!
+//! Every stored component remembers the world tick at which it was added and
+//! last changed. The world tick advances in `clear_trackers()` and on every
+//! `QueryState` run. `world.select()` and `world.is_changed()` report changes
+//! since the last `clear_trackers()`, while a `QueryState` reports changes
+//! since its own previous run, so it sees changes made after it ran last frame.
+//!
+//! # Example
+//! ```ignore
+//! // Only entities whose Position was mutated this frame
+//! world.select::<(Position,)>()
+//!     .changed::<Position>()
+//!     .each(|entity, (pos,)| {
+//!         println!("{} moved to {:?}", entity, pos);
+//!     });
+//!
+//! // Entities that lost their Health this frame
+//! for entity in world.removed::<Health>() {
+//!     println!("{} lost Health", entity);
+//! }
+//! ```
+
+use crate::Component;
+use crate::entity::Entity;
+use crate::query::{FilterParam, QueryFilter};
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// Added/changed ticks of a stored component
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ComponentTicks {
+    /// World tick at which the component was inserted
+    pub added: u32,
+    /// World tick at which the component was last inserted or mutably accessed
+    pub changed: u32,
+}
+
+impl ComponentTicks {
+    pub(crate) fn new(tick: u32) -> Self {
+        Self {
+            added: tick,
+            changed: tick,
+        }
+    }
+
+    /// Returns true if the component was added after `last_run`
+    ///
+    /// `this_run` is the current world tick. Ticks are compared by their
+    /// distance to it, which stays correct when the tick wraps around.
+    #[inline(always)]
+    pub fn is_added(&self, last_run: u32, this_run: u32) -> bool {
+        is_newer(self.added, last_run, this_run)
+    }
+
+    /// Returns true if the component was changed (or added) after `last_run`
+    #[inline(always)]
+    pub fn is_changed(&self, last_run: u32, this_run: u32) -> bool {
+        is_newer(self.changed, last_run, this_run)
+    }
+}
+
+#[inline(always)]
+fn is_newer(tick: u32, last_run: u32, this_run: u32) -> bool {
+    this_run.wrapping_sub(tick) < this_run.wrapping_sub(last_run)
+}
+
+/// Query filter matching components added since the last run
+///
+/// # Example
+/// ```ignore
+/// world.select::<(Health,)>()
+///     .filter::<Added<Health>>()
+///     .each(|entity, (hp,)| println!("{} spawned with {:?}", entity, hp));
+/// ```
+pub struct Added<C>(PhantomData<C>);
+
+/// Query filter matching components changed since the last run
+///
+/// A component counts as changed when it was inserted, replaced or
+/// handed out mutably via `get_mut` or `select_mut`.
+pub struct Changed<C>(PhantomData<C>);
+
+impl<C: Component> FilterParam for Added<C> {
+    fn apply(filter: &mut QueryFilter) {
+        filter.added_components.push(TypeId::of::<C>());
+    }
+}
+
+impl<C: Component> FilterParam for Changed<C> {
+    fn apply(filter: &mut QueryFilter) {
+        filter.changed_components.push(TypeId::of::<C>());
+    }
+}
+
+/// Reader over entities that lost a component this frame
+///
+/// Populated by `World::remove` and `World::destroy`, cleared by
+/// `World::clear_trackers`. Obtained via `world.removed::<C>()`.
+pub struct RemovedComponents<'w, C> {
+    entities: std::slice::Iter<'w, Entity>,
+    _marker: PhantomData<C>,
+}
+
+impl<'w, C> RemovedComponents<'w, C> {
+    pub(crate) fn new(entities: &'w [Entity]) -> Self {
+        Self {
+            entities: entities.iter(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of remaining removals
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Returns true if no removals remain
+    pub fn is_empty(&self) -> bool {
+        self.entities.len() == 0
+    }
+}
+
+impl<C> Iterator for RemovedComponents<'_, C> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        self.entities.next().copied()
+    }
+}
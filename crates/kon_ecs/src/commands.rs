@@ -0,0 +1,413 @@
+//! Command buffers for deferred World changes
+//!
+//! `Commands` records structural changes (spawn, insert, remove, destroy,
+//! tag, untag) while the World is borrowed by a query, and applies them later
+//! in the exact order they were recorded. Spawned entities get their `Entity`
+//! handle immediately; the ID is reserved up front and becomes alive when the
+//! buffer is applied. Inserted components are kept unboxed in one queue per
+//! component type.
+//!
+//! # Example
+//! ```ignore
+//! let mut world = ctx.world();
+//! let mut commands = world.commands();
+//!
+//! world.select_mut::<(Health, Position)>().each(|entity, (hp, pos)| {
+//!     if hp.0 <= 0 {
+//!         commands.destroy(entity);
+//!         let corpse = commands.spawn().insert(pos.clone()).tag("corpse").id();
+//!         println!("{} left {}", entity, corpse);
+//!     }
+//! });
+//!
+//! // Applied by EcsPlugin at the end of the frame
+//! world.push_commands(commands);
+//! ```
+
+use crate::entity::Entity;
+use crate::observer::EntityEvent;
+use crate::tag::{OwnedTag, TagRef};
+use crate::{Component, World};
+use std::any::{Any, TypeId};
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// Boxed closure for custom deferred World operations
+pub(crate) type DeferredOp = Box<dyn FnOnce(&mut World) + Send + Sync>;
+
+/// Queued components per component type
+type InsertQueues = HashMap<TypeId, Box<dyn InsertQueue>>;
+
+/// Monomorphized insert, stored as a plain function pointer
+type InsertFn = fn(&mut World, Entity, &mut InsertQueues);
+
+/// Monomorphized remove, stored as a plain function pointer
+type RemoveFn = fn(&mut World, Entity);
+
+/// A single recorded operation
+enum Command {
+    Spawn(Entity),
+    /// The component waits in the queue of its type
+    Insert {
+        entity: Entity,
+        apply: InsertFn,
+    },
+    Remove {
+        entity: Entity,
+        apply: RemoveFn,
+    },
+    Destroy(Entity),
+    Tag(Entity, OwnedTag),
+    Untag(Entity, OwnedTag),
+    Custom(DeferredOp),
+}
+
+/// Components of one type waiting to be inserted, in recording order
+///
+/// Components are stored unboxed, one queue per type and buffer.
+trait InsertQueue: Send + Sync {
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+
+    /// Moves the components of a queue of the same type to the back
+    fn append(&mut self, other: &mut dyn InsertQueue);
+}
+
+impl<C: Component> InsertQueue for VecDeque<C> {
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+
+    fn append(&mut self, other: &mut dyn InsertQueue) {
+        if let Some(other) = other.as_any_mut().downcast_mut::<VecDeque<C>>() {
+            VecDeque::append(self, other);
+        }
+    }
+}
+
+fn insert_queued<C: Component>(world: &mut World, entity: Entity, queues: &mut InsertQueues) {
+    let component = queues
+        .get_mut(&TypeId::of::<C>())
+        .and_then(|queue| queue.as_any_mut().downcast_mut::<VecDeque<C>>())
+        .and_then(VecDeque::pop_front);
+
+    if let Some(component) = component {
+        world.insert(entity, component);
+    }
+}
+
+fn remove_typed<C: Component>(world: &mut World, entity: Entity) {
+    world.remove::<C>(entity);
+}
+
+/// Buffer of World operations applied later in recording order
+///
+/// Obtained via `world.commands()`. Buffers don't borrow the World, so they
+/// can be filled inside `each` closures and handed back with
+/// `world.push_commands()`. Buffers from the same World can be merged with
+/// `append`.
+///
+/// Entity IDs reserved by `spawn` are not reused if the buffer is dropped
+/// without being applied.
+pub struct Commands {
+    next_id: Arc<AtomicU32>,
+    commands: Vec<Command>,
+    inserts: InsertQueues,
+}
+
+impl Commands {
+    pub(crate) fn new(next_id: Arc<AtomicU32>) -> Self {
+        Self {
+            next_id,
+            commands: Vec::new(),
+            inserts: HashMap::new(),
+        }
+    }
+
+    /// Reserves a new entity and returns a builder for it
+    ///
+    /// The returned `Entity` is valid right away but only becomes alive
+    /// once the buffer is applied.
+    pub fn spawn(&mut self) -> EntityCommands<'_> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let entity = Entity::new(id, 0);
+        self.commands.push(Command::Spawn(entity));
+
+        EntityCommands {
+            commands: self,
+            entity,
+        }
+    }
+
+    /// Returns a builder recording operations for an existing entity
+    pub fn entity(&mut self, entity: Entity) -> EntityCommands<'_> {
+        EntityCommands {
+            commands: self,
+            entity,
+        }
+    }
+
+    /// Records inserting a component
+    pub fn insert<C: Component>(&mut self, entity: Entity, component: C) {
+        let queue = self
+            .inserts
+            .entry(TypeId::of::<C>())
+            .or_insert_with(|| Box::new(VecDeque::<C>::new()));
+
+        if let Some(queue) = queue.as_any_mut().downcast_mut::<VecDeque<C>>() {
+            queue.push_back(component);
+        }
+
+        self.commands.push(Command::Insert {
+            entity,
+            apply: insert_queued::<C>,
+        });
+    }
+
+    /// Records removing a component
+    pub fn remove<C: Component>(&mut self, entity: Entity) {
+        self.commands.push(Command::Remove {
+            entity,
+            apply: remove_typed::<C>,
+        });
+    }
+
+    /// Records destroying an entity
+    pub fn destroy(&mut self, entity: Entity) {
+        self.commands.push(Command::Destroy(entity));
+    }
+
+    /// Records adding a tag
+    pub fn tag<'t>(&mut self, entity: Entity, tag: impl Into<TagRef<'t>>) {
+        self.commands.push(Command::Tag(entity, tag.into().into()));
+    }
+
+    /// Records removing a tag
+    pub fn untag<'t>(&mut self, entity: Entity, tag: impl Into<TagRef<'t>>) {
+        self.commands
+            .push(Command::Untag(entity, tag.into().into()));
+    }
+
+    /// Records triggering an entity event, see `World::trigger_on`
+    pub fn trigger_on<E: EntityEvent>(&mut self, entity: Entity, event: E) {
+        self.add(move |world| {
+            world.trigger_on(entity, event);
+        });
+    }
+
+    /// Records an arbitrary operation
+    pub fn add<F: FnOnce(&mut World) + Send + Sync + 'static>(&mut self, f: F) {
+        self.commands.push(Command::Custom(Box::new(f)));
+    }
+
+    /// Moves all commands of `other` to the end of this buffer
+    ///
+    /// # Panics
+    /// Panics if the buffers belong to different Worlds.
+    pub fn append(&mut self, other: &mut Commands) {
+        assert!(
+            Arc::ptr_eq(&self.next_id, &other.next_id),
+            "Cannot merge command buffers of different Worlds"
+        );
+        self.commands.append(&mut other.commands);
+
+        for (type_id, mut queue) in other.inserts.drain() {
+            match self.inserts.entry(type_id) {
+                Entry::Occupied(mut entry) => entry.get_mut().append(&mut *queue),
+                Entry::Vacant(entry) => {
+                    entry.insert(queue);
+                }
+            }
+        }
+    }
+
+    /// Returns the number of recorded commands
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns true if no commands are recorded
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Applies all commands to the World in recording order
+    ///
+    /// # Panics
+    /// Panics if the buffer was created by a different World.
+    pub fn apply(self, world: &mut World) {
+        assert!(
+            world.owns_allocator(&self.next_id),
+            "Command buffer applied to a different World"
+        );
+
+        let mut inserts = self.inserts;
+        for command in self.commands {
+            match command {
+                Command::Spawn(entity) => world.spawn_reserved(entity),
+                Command::Insert { entity, apply } => apply(world, entity, &mut inserts),
+                Command::Remove { entity, apply } => apply(world, entity),
+                Command::Destroy(entity) => {
+                    world.destroy(entity);
+                }
+                Command::Tag(entity, tag) => world.tag(entity, tag.as_ref()),
+                Command::Untag(entity, tag) => world.untag(entity, tag.as_ref()),
+                Command::Custom(f) => f(world),
+            }
+        }
+    }
+}
+
+/// Records operations for a single entity
+///
+/// Obtained via `Commands::spawn` or `Commands::entity`.
+pub struct EntityCommands<'a> {
+    commands: &'a mut Commands,
+    entity: Entity,
+}
+
+impl EntityCommands<'_> {
+    /// Records inserting a component
+    pub fn insert<C: Component>(self, component: C) -> Self {
+        self.commands.insert(self.entity, component);
+        self
+    }
+
+    /// Records removing a component
+    pub fn remove<C: Component>(self) -> Self {
+        self.commands.remove::<C>(self.entity);
+        self
+    }
+
+    /// Records adding a tag
+    pub fn tag<'t>(self, tag: impl Into<TagRef<'t>>) -> Self {
+        self.commands.tag(self.entity, tag);
+        self
+    }
+
+    /// Records removing a tag
+    pub fn untag<'t>(self, tag: impl Into<TagRef<'t>>) -> Self {
+        self.commands.untag(self.entity, tag);
+        self
+    }
+
+    /// Records destroying the entity
+    pub fn destroy(self) {
+        self.commands.destroy(self.entity);
+    }
+
+    /// Returns the entity handle
+    pub fn id(self) -> Entity {
+        self.entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[test]
+    fn spawn_reserves_entity_until_applied() {
+        let mut world = World::new();
+        let mut commands = world.commands();
+
+        let entity = commands.spawn().insert(Health(10)).tag("enemy").id();
+        assert!(!world.is_alive(entity));
+
+        commands.apply(&mut world);
+
+        assert!(world.is_alive(entity));
+        assert_eq!(world.get::<Health>(entity), Some(&Health(10)));
+        assert!(world.has_tag(entity, "enemy"));
+    }
+
+    #[test]
+    fn reserved_ids_dont_collide_with_spawn() {
+        let mut world = World::new();
+        let mut commands = world.commands();
+
+        let reserved = commands.spawn().id();
+        let direct = world.spawn().id();
+        assert_ne!(reserved.id(), direct.id());
+
+        world.push_commands(commands);
+        world.apply_deferred();
+
+        assert!(world.is_alive(reserved));
+        assert!(world.is_alive(direct));
+        assert_eq!(world.entity_count(), 2);
+    }
+
+    #[test]
+    fn commands_from_query() {
+        let mut world = World::new();
+        for i in 0..4 {
+            world.spawn().insert(Health(i));
+        }
+
+        let mut commands = world.commands();
+        world.select::<(Health,)>().each(|entity, (hp,)| {
+            if hp.0 % 2 == 0 {
+                commands.destroy(entity);
+            }
+        });
+        world.push_commands(commands);
+        world.apply_deferred();
+
+        assert_eq!(world.entity_count(), 2);
+    }
+
+    #[test]
+    fn merged_buffers_apply_in_order() {
+        let mut world = World::new();
+        let entity = world.spawn().id();
+
+        let mut first = world.commands();
+        let mut second = world.commands();
+        first.insert(entity, Health(1));
+        second.insert(entity, Health(2));
+        second.remove::<Health>(entity);
+        second.insert(entity, Health(3));
+
+        first.append(&mut second);
+        assert!(second.is_empty());
+        assert_eq!(first.len(), 4);
+
+        first.apply(&mut world);
+        assert_eq!(world.get::<Health>(entity), Some(&Health(3)));
+    }
+
+    #[test]
+    fn interleaved_types_keep_their_entities() {
+        let mut world = World::new();
+        let a = world.spawn().id();
+        let b = world.spawn().id();
+
+        let mut commands = world.commands();
+        commands.insert(a, Health(1));
+        commands.insert(b, 10u32);
+        commands.insert(b, Health(2));
+        commands.insert(a, 20u32);
+        commands.apply(&mut world);
+
+        assert_eq!(world.get::<Health>(a), Some(&Health(1)));
+        assert_eq!(world.get::<Health>(b), Some(&Health(2)));
+        assert_eq!(world.get::<u32>(a), Some(&20));
+        assert_eq!(world.get::<u32>(b), Some(&10));
+    }
+
+    #[test]
+    #[should_panic]
+    fn apply_to_other_world_panics() {
+        let world = World::new();
+        let mut other = World::new();
+
+        let mut commands = world.commands();
+        commands.spawn();
+        commands.apply(&mut other);
+    }
+}
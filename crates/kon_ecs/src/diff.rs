@@ -0,0 +1,513 @@
+//! World diffing for tests and replays
+//!
+//! `World::diff(&before, &after)` compares two Worlds entity by entity and
+//! reports spawned and destroyed entities, added, removed and changed
+//! components and tag changes. `diff_since` compares a World against one of
+//! its own snapshots, so tests don't need to keep a second World around.
+//!
+//! Components registered with `register_component` or `register_diffable`
+//! are compared with `PartialEq` (which `#[component]` derives). Register
+//! every component type in setup code: unregistered types fall back to
+//! comparing their `Debug` output, which misses fields a custom `Debug`
+//! leaves out. Values are reported as `Debug` strings.
+//!
+//! Resources are not compared; they aren't required to implement `Debug`
+//! or `PartialEq`. Compare the ones a test cares about directly.
+//!
+//! Available in debug builds, and in release builds with the `inspect`
+//! feature enabled.
+//!
+//! # Example
+//! ```ignore
+//! world.register_component::<Health>();
+//! let before = world.snapshot()?;
+//!
+//! damage_system(&mut world);
+//!
+//! let diff = world.diff_since(&before)?;
+//! assert_eq!(diff.entities.len(), 1);
+//! println!("{}", diff);
+//! // 0 spawned, 0 destroyed, 1 modified
+//! // ~ Entity(0v0) "player"
+//! //     ~ Health: Health(10) -> Health(5)
+//! ```
+
+use crate::entity::Entity;
+use crate::hierarchy::{Children, Parent};
+use crate::snapshot::SnapshotError;
+use crate::storage::{SparseSet, Storage};
+use crate::{Component, World};
+use std::any::TypeId;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+
+/// Compares the component of one entity in two storages of the same type
+type EqFn = fn(&dyn Storage, &dyn Storage, u32) -> bool;
+
+/// Component types compared through `PartialEq`
+#[derive(Default, Clone)]
+pub(crate) struct DiffRegistry {
+    comparers: HashMap<TypeId, EqFn>,
+}
+
+fn eq_component<C: Component + PartialEq>(
+    before: &dyn Storage,
+    after: &dyn Storage,
+    id: u32,
+) -> bool {
+    let before = before.as_any().downcast_ref::<SparseSet<C>>();
+    let after = after.as_any().downcast_ref::<SparseSet<C>>();
+    before.and_then(|set| set.get(id)) == after.and_then(|set| set.get(id))
+}
+
+/// How an entity differs between two Worlds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EntityChange {
+    /// Alive only in the later World
+    Spawned,
+    /// Alive only in the earlier World
+    Destroyed,
+    /// Alive in both, with different components or tags
+    Modified,
+}
+
+/// Differences of a single entity
+///
+/// Components and tags of spawned entities are reported as added, those of
+/// destroyed entities as removed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntityDiff {
+    pub entity: Entity,
+    /// `Name` in the later World, or in the earlier one if it was removed
+    pub name: Option<String>,
+    pub change: EntityChange,
+    /// Changed components, ordered by short type name
+    pub components: Vec<ComponentDiff>,
+    pub added_tags: Vec<String>,
+    pub removed_tags: Vec<String>,
+}
+
+/// A component that was added, removed or changed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComponentDiff {
+    /// Short type name, e.g. `Position`
+    pub name: String,
+    /// Full type path, e.g. `game::components::Position`
+    pub type_name: &'static str,
+    /// `Debug` output in the earlier World, None if the component was added
+    pub before: Option<String>,
+    /// `Debug` output in the later World, None if the component was removed
+    pub after: Option<String>,
+}
+
+/// Structured differences between two Worlds
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WorldDiff {
+    /// Entities that differ, ordered by entity ID
+    pub entities: Vec<EntityDiff>,
+}
+
+impl WorldDiff {
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+
+    /// Returns the entry of an entity, None if it didn't change
+    pub fn entity(&self, entity: Entity) -> Option<&EntityDiff> {
+        self.entities.iter().find(|e| e.entity == entity)
+    }
+
+    pub fn spawned(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.with_change(EntityChange::Spawned)
+    }
+
+    pub fn destroyed(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.with_change(EntityChange::Destroyed)
+    }
+
+    pub fn modified(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.with_change(EntityChange::Modified)
+    }
+
+    fn with_change(&self, change: EntityChange) -> impl Iterator<Item = Entity> + '_ {
+        self.entities
+            .iter()
+            .filter(move |e| e.change == change)
+            .map(|e| e.entity)
+    }
+
+    /// Panics with the pretty-printed diff unless the Worlds are equal
+    ///
+    /// ```ignore
+    /// world.diff_since(&before)?.assert_empty();
+    /// ```
+    #[track_caller]
+    pub fn assert_empty(&self) {
+        if !self.is_empty() {
+            panic!("Worlds differ:\n{}", self);
+        }
+    }
+}
+
+impl EntityDiff {
+    /// Returns the change of a component by short type name
+    pub fn component(&self, name: &str) -> Option<&ComponentDiff> {
+        self.components.iter().find(|c| c.name == name)
+    }
+
+    /// Entity handle followed by its name, as in `World::describe`
+    fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {:?}", self.entity, name),
+            None => self.entity.to_string(),
+        }
+    }
+}
+
+/// Renders one line per changed entity, component and tag
+impl fmt::Display for WorldDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        writeln!(
+            f,
+            "{} spawned, {} destroyed, {} modified",
+            self.spawned().count(),
+            self.destroyed().count(),
+            self.modified().count()
+        )?;
+
+        for entity in &self.entities {
+            let marker = match entity.change {
+                EntityChange::Spawned => '+',
+                EntityChange::Destroyed => '-',
+                EntityChange::Modified => '~',
+            };
+            writeln!(f, "{} {}", marker, entity.label())?;
+
+            for component in &entity.components {
+                match (&component.before, &component.after) {
+                    (Some(before), Some(after)) => {
+                        writeln!(f, "    ~ {}: {} -> {}", component.name, before, after)?
+                    }
+                    (None, Some(after)) => writeln!(f, "    + {}: {}", component.name, after)?,
+                    (Some(before), None) => writeln!(f, "    - {}: {}", component.name, before)?,
+                    (None, None) => {}
+                }
+            }
+            for tag in &entity.added_tags {
+                writeln!(f, "    + tag {:?}", tag)?;
+            }
+            for tag in &entity.removed_tags {
+                writeln!(f, "    - tag {:?}", tag)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn short_name(type_name: &str) -> &str {
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+fn alive_entities(world: &World) -> BTreeSet<(u32, u32)> {
+    world
+        .alive
+        .iter()
+        .map(|&id| (id, world.generations[id as usize]))
+        .collect()
+}
+
+/// Storages of one component type in the compared Worlds
+struct StoragePair<'w> {
+    type_id: TypeId,
+    before: Option<&'w dyn Storage>,
+    after: Option<&'w dyn Storage>,
+}
+
+/// Diffs the entities of two Worlds, comparing only component types accepted by `compared`
+fn diff_worlds(before: &World, after: &World, compared: impl Fn(TypeId) -> bool) -> WorldDiff {
+    // Storages present in either World, ordered by short type name
+    let mut storages: BTreeMap<(&str, &str), StoragePair> = BTreeMap::new();
+    for (world, is_before) in [(before, true), (after, false)] {
+        for (type_id, storage) in world.components() {
+            if !compared(*type_id) {
+                continue;
+            }
+            let type_name = storage.type_name();
+            let entry = storages
+                .entry((short_name(type_name), type_name))
+                .or_insert(StoragePair {
+                    type_id: *type_id,
+                    before: None,
+                    after: None,
+                });
+            if is_before {
+                entry.before = Some(storage.as_ref());
+            } else {
+                entry.after = Some(storage.as_ref());
+            }
+        }
+    }
+
+    let before_alive = alive_entities(before);
+    let after_alive = alive_entities(after);
+
+    let mut entities = Vec::new();
+    for &(id, generation) in before_alive.union(&after_alive) {
+        let entity = Entity::new(id, generation);
+        let in_before = before_alive.contains(&(id, generation));
+        let in_after = after_alive.contains(&(id, generation));
+
+        let mut components = Vec::new();
+        for (&(name, type_name), pair) in &storages {
+            let entry = |storage: Option<&dyn Storage>, alive: bool| {
+                storage.filter(|_| alive).and_then(|s| s.debug_entry(id))
+            };
+            let before_value = entry(pair.before, in_before);
+            let after_value = entry(pair.after, in_after);
+
+            let equal = match (&before_value, &after_value, pair.before, pair.after) {
+                (Some(_), Some(_), Some(b), Some(a)) => {
+                    let eq = after
+                        .diff_registry
+                        .comparers
+                        .get(&pair.type_id)
+                        .or_else(|| before.diff_registry.comparers.get(&pair.type_id));
+                    match eq {
+                        Some(eq) => eq(b, a, id),
+                        None => before_value == after_value,
+                    }
+                }
+                _ => before_value == after_value,
+            };
+
+            if !equal {
+                components.push(ComponentDiff {
+                    name: name.to_string(),
+                    type_name,
+                    before: before_value,
+                    after: after_value,
+                });
+            }
+        }
+
+        let tags = |world: &World, alive: bool| -> BTreeSet<String> {
+            if alive {
+                world.get_entity_tags(id).into_iter().collect()
+            } else {
+                BTreeSet::new()
+            }
+        };
+        let before_tags = tags(before, in_before);
+        let after_tags = tags(after, in_after);
+        let added_tags: Vec<String> = after_tags.difference(&before_tags).cloned().collect();
+        let removed_tags: Vec<String> = before_tags.difference(&after_tags).cloned().collect();
+
+        let change = match (in_before, in_after) {
+            (false, _) => EntityChange::Spawned,
+            (_, false) => EntityChange::Destroyed,
+            _ if components.is_empty() && added_tags.is_empty() && removed_tags.is_empty() => {
+                continue;
+            }
+            _ => EntityChange::Modified,
+        };
+
+        let name = in_after
+            .then(|| after.name(entity))
+            .flatten()
+            .or_else(|| in_before.then(|| before.name(entity)).flatten())
+            .map(str::to_string);
+
+        entities.push(EntityDiff {
+            entity,
+            name,
+            change,
+            components,
+            added_tags,
+            removed_tags,
+        });
+    }
+
+    WorldDiff { entities }
+}
+
+impl World {
+    /// Registers a component type to be compared with `PartialEq` by `diff`
+    ///
+    /// Unregistered types are compared by their `Debug` output. See also
+    /// `register_component`, which registers cloning too.
+    pub fn register_diffable<C: Component + PartialEq>(&mut self) {
+        self.diff_registry
+            .comparers
+            .insert(TypeId::of::<C>(), eq_component::<C>);
+    }
+
+    /// Compares two Worlds entity by entity
+    ///
+    /// Entities are matched by handle, so an ID reused with a new generation
+    /// shows up as destroyed and spawned. Resources are not compared.
+    pub fn diff(before: &World, after: &World) -> WorldDiff {
+        diff_worlds(before, after, |_| true)
+    }
+
+    /// Compares a snapshot taken earlier against the current World state
+    ///
+    /// Only what snapshots store is compared: component types registered
+    /// via `register_serializable`, the hierarchy and tags.
+    pub fn diff_since(&self, snapshot: &[u8]) -> Result<WorldDiff, SnapshotError> {
+        let mut before = World::new();
+        before.scene_registry = self.scene_registry.clone();
+        before.diff_registry = self.diff_registry.clone();
+        before.restore(snapshot)?;
+
+        let compared: Vec<TypeId> = self
+            .scene_registry
+            .components
+            .values()
+            .map(|(type_id, _)| *type_id)
+            .chain([TypeId::of::<Parent>(), TypeId::of::<Children>()])
+            .collect();
+
+        Ok(diff_worlds(&before, self, |type_id| {
+            compared.contains(&type_id)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name::Name;
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[test]
+    fn reports_spawned_destroyed_and_modified_entities() {
+        let mut before = World::new();
+        let player = before
+            .spawn()
+            .insert(Name::new("player"))
+            .insert(Health(10))
+            .id();
+        let enemy = before.spawn().insert(Health(3)).tag("hostile").id();
+        let rock = before.spawn().insert(Position { x: 0.0, y: 0.0 }).id();
+
+        let mut after = World::new();
+        after.register_diffable::<Health>();
+        let p = after
+            .spawn()
+            .insert(Name::new("player"))
+            .insert(Health(7))
+            .tag("hurt")
+            .id();
+        let e = after.spawn().id();
+        let r = after.spawn().insert(Position { x: 0.0, y: 0.0 }).id();
+        after.destroy(e);
+        let bullet = after.spawn().insert(Position { x: 1.0, y: 2.0 }).id();
+        assert_eq!((p, r), (player, rock));
+
+        let diff = World::diff(&before, &after);
+
+        assert_eq!(diff.destroyed().collect::<Vec<_>>(), vec![enemy]);
+        assert_eq!(diff.spawned().collect::<Vec<_>>(), vec![bullet]);
+        assert_eq!(diff.modified().collect::<Vec<_>>(), vec![player]);
+        assert!(diff.entity(rock).is_none());
+
+        let entry = diff.entity(player).unwrap();
+        assert_eq!(entry.name.as_deref(), Some("player"));
+        assert_eq!(entry.added_tags, vec!["hurt"]);
+        let health = entry.component("Health").unwrap();
+        assert_eq!(health.before.as_deref(), Some("Health(10)"));
+        assert_eq!(health.after.as_deref(), Some("Health(7)"));
+
+        let entry = diff.entity(enemy).unwrap();
+        assert_eq!(entry.removed_tags, vec!["hostile"]);
+        assert_eq!(entry.component("Health").unwrap().after, None);
+    }
+
+    #[test]
+    fn diff_since_snapshot() {
+        let mut world = World::new();
+        world.register_serializable::<Health>("Health");
+        world.register_component::<Health>();
+        let player = world
+            .spawn()
+            .insert(Name::new("player"))
+            .insert(Health(10))
+            .id();
+        world.spawn().insert(Position { x: 0.0, y: 0.0 });
+
+        let snapshot = world.snapshot().unwrap();
+        world.diff_since(&snapshot).unwrap().assert_empty();
+
+        world.get_mut::<Health>(player).unwrap().0 = 5;
+        let child = world.spawn().id();
+        world.set_parent(child, player);
+
+        let diff = world.diff_since(&snapshot).unwrap();
+        assert_eq!(diff.spawned().collect::<Vec<_>>(), vec![child]);
+        assert_eq!(
+            diff.entity(player)
+                .unwrap()
+                .component("Health")
+                .unwrap()
+                .after
+                .as_deref(),
+            Some("Health(5)")
+        );
+        assert!(diff.entity(child).unwrap().component("Parent").is_some());
+    }
+
+    #[test]
+    fn assert_empty_prints_the_diff() {
+        let before = World::new();
+        let mut after = World::new();
+        after.spawn().insert(Name::new("ghost")).insert(Health(1));
+
+        let diff = World::diff(&before, &after);
+        let message = std::panic::catch_unwind(|| diff.assert_empty())
+            .unwrap_err()
+            .downcast::<String>()
+            .unwrap();
+
+        assert!(message.contains("1 spawned, 0 destroyed, 0 modified"));
+        assert!(message.contains("+ Entity(0v0) \"ghost\""));
+        assert!(message.contains("    + Health: Health(1)"));
+        assert_eq!(World::diff(&after, &after).to_string(), "no changes\n");
+    }
+
+    #[test]
+    fn registered_types_compare_fields_hidden_from_debug() {
+        #[derive(Clone, PartialEq)]
+        struct Secret(u32);
+
+        impl std::fmt::Debug for Secret {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str("Secret")
+            }
+        }
+
+        let mut before = World::new();
+        let entity = before.spawn().insert(Secret(1)).id();
+        let mut after = World::new();
+        after.spawn().insert(Secret(2));
+
+        assert!(World::diff(&before, &after).is_empty());
+
+        after.register_component::<Secret>();
+        let diff = World::diff(&before, &after);
+        assert_eq!(diff.modified().collect::<Vec<_>>(), vec![entity]);
+    }
+}
@@ -3,8 +3,12 @@
 //! Entities are lightweight IDs that reference component data in the World.
 //! The generation counter prevents use-after-free bugs when entities are destroyed and IDs are reused.
 
-use std::fmt::Debug;
 use crate::Component;
+use serde::de::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::fmt::Debug;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 /// Unique entity identifier with generation tracking
 ///
@@ -23,13 +27,23 @@ use crate::Component;
 ///
 /// println!("{}", entity); // "Entity(0v0)"
 /// ```
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub struct Entity {
     id: u32,
     generation: u32,
 }
 
 impl Entity {
+    /// Handle that never refers to an alive entity
+    ///
+    /// Useful as an initial value before the real entity is known. Entity
+    /// references that can't be remapped (e.g. when loading a scene) also
+    /// become `PLACEHOLDER`.
+    pub const PLACEHOLDER: Entity = Entity {
+        id: u32::MAX,
+        generation: u32::MAX,
+    };
+
     pub(crate) fn new(id: u32, generation: u32) -> Self {
         Self { id, generation }
     }
@@ -52,6 +66,26 @@ impl Entity {
     pub fn generation(&self) -> u32 {
         self.generation
     }
+
+    /// Packs the entity into a u64 (generation in the high 32 bits)
+    ///
+    /// The value is only meaningful for the World that created the entity.
+    /// Use `world.get_entity(bits)` to turn it back into a checked handle.
+    #[inline]
+    pub const fn to_bits(self) -> u64 {
+        (self.generation as u64) << 32 | self.id as u64
+    }
+
+    /// Unpacks an entity from `to_bits`
+    ///
+    /// The handle is not validated; it may be dead or belong to another World.
+    #[inline]
+    pub const fn from_bits(bits: u64) -> Self {
+        Self {
+            id: bits as u32,
+            generation: (bits >> 32) as u32,
+        }
+    }
 }
 
 impl std::fmt::Display for Entity {
@@ -60,6 +94,109 @@ impl std::fmt::Display for Entity {
     }
 }
 
+/// Stable identifier for an entity across sessions and processes
+///
+/// `Entity` handles are only valid inside the World that created them and
+/// change when a scene is reloaded. An `EntityId` is a random 128-bit value
+/// (formatted like a UUID) stored as a regular component, so it survives
+/// saving, loading and merging Worlds. Register it with
+/// `register_serializable::<EntityId>("EntityId")` to include it in scenes.
+///
+/// # Example
+/// ```ignore
+/// let id = world.assign_entity_id(player).unwrap();
+/// save_file.write(id.to_string());
+///
+/// // Later, possibly in another session
+/// let player = world.find_entity(id).unwrap();
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityId(u128);
+
+/// Per-process counter mixed into generated ids
+static ENTITY_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+impl EntityId {
+    /// Generates a new random id (UUID version 4 layout)
+    pub fn new() -> Self {
+        let count = ENTITY_ID_COUNTER.fetch_add(1, Ordering::Relaxed);
+        let nanos = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+
+        // RandomState is seeded from OS randomness, keyed differently per call
+        let random_half = |salt: u64| {
+            let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+            hasher.write_u64(count);
+            hasher.write_u64(salt);
+            hasher.write_u128(nanos);
+            hasher.finish()
+        };
+
+        let bits = (random_half(0) as u128) << 64 | random_half(1) as u128;
+        let bits = bits & !(0xF << 76) | (0x4 << 76);
+        let bits = bits & !(0x3 << 62) | (0x2 << 62);
+        Self(bits)
+    }
+
+    /// Creates an id from its raw value
+    pub const fn from_u128(value: u128) -> Self {
+        Self(value)
+    }
+
+    /// Returns the raw value
+    pub const fn as_u128(&self) -> u128 {
+        self.0
+    }
+
+    /// Parses the hyphenated form produced by `Display`
+    ///
+    /// Hyphens are optional; returns None for anything but 32 hex digits.
+    pub fn parse(text: &str) -> Option<Self> {
+        let digits: String = text.chars().filter(|&c| c != '-').collect();
+        if digits.len() != 32 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        u128::from_str_radix(&digits, 16).ok().map(Self)
+    }
+}
+
+impl Default for EntityId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Display for EntityId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex = format!("{:032x}", self.0);
+        write!(
+            f,
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+}
+
+impl Serialize for EntityId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for EntityId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        EntityId::parse(&text)
+            .ok_or_else(|| D::Error::custom(format!("invalid entity id '{}'", text)))
+    }
+}
+
 /// Fluent builder for constructing entities with components and tags
 ///
 /// Obtained via `world.spawn()`. Allows method chaining.
@@ -94,8 +231,9 @@ impl<'w> EntityBuilder<'w> {
     /// Attaches a tag to the entity
     ///
     /// Tags are lightweight string labels for filtering queries.
-    /// Uses a bitmask system internally for O(1) filtering.
-    pub fn tag(self, tag: &str) -> Self {
+    /// Accepts a tag name or an interned `Tag` handle.
+    /// Uses a bitset internally for O(1) filtering.
+    pub fn tag<'t>(self, tag: impl Into<crate::TagRef<'t>>) -> Self {
         self.world.tag(self.entity, tag);
         self
     }
@@ -104,6 +242,11 @@ impl<'w> EntityBuilder<'w> {
     pub fn id(self) -> Entity {
         self.entity
     }
+
+    /// Splits the builder into its world and entity
+    pub(crate) fn into_parts(self) -> (&'w mut crate::World, Entity) {
+        (self.world, self.entity)
+    }
 }
 
 #[cfg(test)]
@@ -126,6 +269,34 @@ mod tests {
         assert_eq!(e1, e2);
         assert_ne!(e1, e3);
     }
+
+    #[test]
+    fn entity_id_format() {
+        let id = EntityId::from_u128(0x0123_4567_89ab_4def_8123_4567_89ab_cdef);
+        assert_eq!(id.to_string(), "01234567-89ab-4def-8123-456789abcdef");
+    }
+
+    #[test]
+    fn entity_id_parse() {
+        let id = EntityId::from_u128(0x0123_4567_89ab_4def_8123_4567_89ab_cdef);
+        assert_eq!(EntityId::parse(&id.to_string()), Some(id));
+        assert_eq!(EntityId::parse("not-an-id"), None);
+    }
+
+    #[test]
+    fn entity_id_serde() {
+        let id = EntityId::from_u128(0x0123_4567_89ab_4def_8123_4567_89ab_cdef);
+        let json = serde_json::to_string(&id).unwrap();
+        assert_eq!(serde_json::from_str::<EntityId>(&json).unwrap(), id);
+    }
+
+    #[test]
+    fn entity_id_generation() {
+        let generated = EntityId::new();
+        assert_eq!(generated.to_string().as_bytes()[14], b'4');
+        assert_ne!(generated, EntityId::new());
+    }
+
     #[test]
     fn entity_display() {
         let entity = Entity::new(1, 0);
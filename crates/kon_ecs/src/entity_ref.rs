@@ -0,0 +1,331 @@
+//! Per-entity access
+//!
+//! `EntityRef` and `EntityMut` bundle a World borrow with one entity, so
+//! working on a single entity doesn't need to pass the handle around. Both
+//! are only handed out for alive entities.
+//!
+//! # Example
+//! ```ignore
+//! let mut player = world.entity_mut(player).unwrap();
+//!
+//! if let Some((pos, vel)) = player.get_many_mut::<(Position, Velocity)>() {
+//!     pos.x += vel.x;
+//! }
+//!
+//! player.insert(Shield(10)).tag("shielded");
+//! println!("{:?}", player.components());
+//! ```
+
+use crate::entity::Entity;
+use crate::query::{QueryTupleMut, check_duplicate_types};
+use crate::storage::SparseSet;
+use crate::tag::TagRef;
+use crate::{Component, World};
+use std::any::TypeId;
+use std::fmt;
+
+/// Read-only access to a single entity
+///
+/// Obtained via `world.entity(entity)`.
+#[derive(Clone, Copy)]
+pub struct EntityRef<'w> {
+    world: &'w World,
+    entity: Entity,
+}
+
+impl<'w> EntityRef<'w> {
+    pub(crate) fn new(world: &'w World, entity: Entity) -> Self {
+        Self { world, entity }
+    }
+
+    /// Returns the entity handle
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns a component of the entity
+    ///
+    /// The entity was checked to be alive when the `EntityRef` was created,
+    /// so this is a plain storage lookup.
+    pub fn get<C: Component>(&self) -> Option<&'w C> {
+        self.world
+            .components()
+            .get(&TypeId::of::<C>())?
+            .as_any()
+            .downcast_ref::<SparseSet<C>>()?
+            .get(self.entity.id())
+    }
+
+    /// Checks if the entity has a component
+    pub fn has<C: Component>(&self) -> bool {
+        self.world
+            .components()
+            .get(&TypeId::of::<C>())
+            .is_some_and(|s| s.contains(self.entity.id()))
+    }
+
+    /// Checks if the entity has a tag (name or `Tag` handle)
+    pub fn has_tag<'t>(&self, tag: impl Into<TagRef<'t>>) -> bool {
+        self.world.has_tag(self.entity, tag)
+    }
+
+    /// Returns the names of all tags of the entity
+    pub fn tags(&self) -> Vec<String> {
+        self.world.get_entity_tags(self.entity.id())
+    }
+
+    /// Returns the type names of all components of the entity, sorted
+    pub fn components(&self) -> Vec<&'static str> {
+        component_names(self.world, self.entity)
+    }
+}
+
+impl fmt::Debug for EntityRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EntityRef({})", self.world.describe(self.entity))
+    }
+}
+
+/// Read-write access to a single entity
+///
+/// Obtained via `world.entity_mut(entity)`. Structural changes go through
+/// the World, so hooks run as usual; if a hook destroys the entity, later
+/// calls do nothing and getters return None.
+pub struct EntityMut<'w> {
+    world: &'w mut World,
+    entity: Entity,
+}
+
+impl<'w> EntityMut<'w> {
+    pub(crate) fn new(world: &'w mut World, entity: Entity) -> Self {
+        Self { world, entity }
+    }
+
+    /// Returns the entity handle
+    pub fn id(&self) -> Entity {
+        self.entity
+    }
+
+    /// Returns a component of the entity
+    pub fn get<C: Component>(&self) -> Option<&C> {
+        self.world.get::<C>(self.entity)
+    }
+
+    /// Returns a component mutably, marking it as changed
+    pub fn get_mut<C: Component>(&mut self) -> Option<&mut C> {
+        self.world.get_mut::<C>(self.entity)
+    }
+
+    /// Returns several different components mutably at once
+    ///
+    /// Returns None if the entity lacks any of them, without marking the
+    /// others as changed.
+    ///
+    /// # Panics
+    /// Panics if the same component type appears twice.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let (pos, vel) = entity.get_many_mut::<(Position, Velocity)>()?;
+    /// pos.x += vel.x;
+    /// ```
+    #[track_caller]
+    pub fn get_many_mut<'a, T: QueryTupleMut<'a>>(&'a mut self) -> Option<T::Item> {
+        check_duplicate_types(&T::type_ids());
+
+        if !self.world.is_alive(self.entity) {
+            return None;
+        }
+
+        let id = self.entity.id();
+        let mut state = T::init_all(self.world)?;
+        T::fetch_all(&mut state, id)
+    }
+
+    /// Checks if the entity has a component
+    pub fn has<C: Component>(&self) -> bool {
+        self.world.has::<C>(self.entity)
+    }
+
+    /// Inserts or replaces a component
+    pub fn insert<C: Component>(&mut self, component: C) -> &mut Self {
+        self.world.insert(self.entity, component);
+        self
+    }
+
+    /// Removes a component, returning true if it existed
+    pub fn remove<C: Component>(&mut self) -> bool {
+        self.world.remove::<C>(self.entity)
+    }
+
+    /// Adds a tag (name or `Tag` handle)
+    pub fn tag<'t>(&mut self, tag: impl Into<TagRef<'t>>) -> &mut Self {
+        self.world.tag(self.entity, tag);
+        self
+    }
+
+    /// Removes a tag (name or `Tag` handle)
+    pub fn untag<'t>(&mut self, tag: impl Into<TagRef<'t>>) -> &mut Self {
+        self.world.untag(self.entity, tag);
+        self
+    }
+
+    /// Checks if the entity has a tag (name or `Tag` handle)
+    pub fn has_tag<'t>(&self, tag: impl Into<TagRef<'t>>) -> bool {
+        self.world.has_tag(self.entity, tag)
+    }
+
+    /// Returns the names of all tags of the entity
+    pub fn tags(&self) -> Vec<String> {
+        self.world.get_entity_tags(self.entity.id())
+    }
+
+    /// Returns the type names of all components of the entity, sorted
+    pub fn components(&self) -> Vec<&'static str> {
+        component_names(self.world, self.entity)
+    }
+
+    /// Returns true if the entity is still alive
+    pub fn is_alive(&self) -> bool {
+        self.world.is_alive(self.entity)
+    }
+
+    /// Destroys the entity
+    ///
+    /// Returns true if the entity was still alive.
+    pub fn despawn(self) -> bool {
+        self.world.destroy(self.entity)
+    }
+
+    /// Returns the underlying World
+    pub fn world(&mut self) -> &mut World {
+        self.world
+    }
+}
+
+impl fmt::Debug for EntityMut<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "EntityMut({})", self.world.describe(self.entity))
+    }
+}
+
+fn component_names(world: &World, entity: Entity) -> Vec<&'static str> {
+    if !world.is_alive(entity) {
+        return Vec::new();
+    }
+
+    let mut names: Vec<&'static str> = world
+        .components()
+        .values()
+        .filter(|storage| storage.contains(entity.id()))
+        .map(|storage| storage.type_name())
+        .collect();
+
+    names.sort_unstable();
+    names
+}
+
+impl World {
+    /// Returns read-only access to an entity
+    ///
+    /// Returns None if the entity is not alive.
+    pub fn entity(&self, entity: Entity) -> Option<EntityRef<'_>> {
+        self.is_alive(entity).then(|| EntityRef::new(self, entity))
+    }
+
+    /// Returns read-write access to an entity
+    ///
+    /// Returns None if the entity is not alive.
+    pub fn entity_mut(&mut self, entity: Entity) -> Option<EntityMut<'_>> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        Some(EntityMut::new(self, entity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Armor(i32);
+
+    #[test]
+    fn entity_ref_reads_components_and_tags() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(10)).tag("enemy").id();
+
+        let view = world.entity(entity).unwrap();
+        assert_eq!(view.get::<Health>(), Some(&Health(10)));
+        assert!(!view.has::<Armor>());
+        assert!(view.has_tag("enemy"));
+        assert_eq!(view.tags(), vec!["enemy".to_string()]);
+        assert_eq!(view.components().len(), 1);
+        assert!(view.components()[0].ends_with("Health"));
+    }
+
+    #[test]
+    fn entity_mut_edits_entity() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(10)).id();
+
+        let mut view = world.entity_mut(entity).unwrap();
+        view.insert(Armor(2)).tag("tank");
+        view.get_mut::<Health>().unwrap().0 -= 3;
+
+        let (hp, armor) = view.get_many_mut::<(Health, Armor)>().unwrap();
+        hp.0 += armor.0;
+        armor.0 = 0;
+
+        assert!(view.remove::<Armor>());
+        assert_eq!(view.get::<Health>(), Some(&Health(9)));
+        assert!(view.has_tag("tank"));
+        assert!(view.despawn());
+        assert!(!world.is_alive(entity));
+    }
+
+    #[test]
+    fn entity_access_requires_alive_entity() {
+        let mut world = World::new();
+        let entity = world.spawn().id();
+        world.destroy(entity);
+
+        assert!(world.entity(entity).is_none());
+        assert!(world.entity_mut(entity).is_none());
+    }
+
+    #[test]
+    #[should_panic]
+    fn get_many_mut_duplicate_types_panic() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(1)).id();
+
+        world
+            .entity_mut(entity)
+            .unwrap()
+            .get_many_mut::<(Health, Health)>();
+    }
+
+    #[test]
+    fn get_many_mut_missing_component_marks_nothing() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(1)).id();
+        world.spawn().insert(Armor(1));
+        world.clear_trackers();
+
+        let mut view = world.entity_mut(entity).unwrap();
+        assert!(view.get_many_mut::<(Health, Armor)>().is_none());
+
+        let mut changed = 0;
+        world
+            .select::<(Health,)>()
+            .changed::<Health>()
+            .each(|_, _| changed += 1);
+        assert_eq!(changed, 0);
+    }
+}
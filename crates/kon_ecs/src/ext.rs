@@ -3,26 +3,103 @@
 //! Provides convenient `world()` method on Context
 //! instead of manually calling `ctx.global::<World>()`.
 
-use std::cell::RefMut;
-use crate::World;
-use kon_core::Context;
+use crate::worlds::active_world;
+use crate::{World, Worlds};
+use kon_core::{Context, GlobalMut, GlobalRef};
 
 /// Extension trait for convenient World access from Context
 ///
 /// # Panics
 /// Panics if World is not registered. Ensure `EcsPlugin` or `DefaultPlugins` is added.
 pub trait ContextEcsExt {
-    fn world(&self) -> RefMut<'_, World>;
+    fn world(&self) -> GlobalMut<'_, World>;
+    fn world_ref(&self) -> GlobalRef<'_, World>;
+    fn worlds(&self) -> GlobalMut<'_, Worlds>;
+    fn world_named(&self, name: &str) -> GlobalMut<'_, World>;
+    fn world_named_ref(&self, name: &str) -> GlobalRef<'_, World>;
 }
 
 impl ContextEcsExt for Context {
     /// Returns a reference to the World
     ///
+    /// Inside a system wrapped with `in_world`, returns that named World.
+    /// This borrows the whole World, so parallel systems calling it must
+    /// declare `write::<World>()` in their `SystemAccess`, or
+    /// `write::<Worlds>()` for a named World (`in_world_parallel` adds it).
+    ///
     /// # Panics
     /// Panics with a helpful message if EcsPlugin is not registered
     #[track_caller]
-    fn world(&self) -> RefMut<'_, World> {
+    fn world(&self) -> GlobalMut<'_, World> {
+        if let Some(name) = active_world() {
+            return self.world_named(&name);
+        }
+
         self.global::<World>()
             .expect("Failed to access World. Ensure 'DefaultPlugins' or 'EcsPlugin' is added")
     }
+
+    /// Returns a shared reference to the World
+    ///
+    /// Parallel systems that only read components should use this so
+    /// they can run alongside each other. They declare `read::<World>()`,
+    /// or `read::<Worlds>()` inside a named World.
+    ///
+    /// # Panics
+    /// Panics with a helpful message if EcsPlugin is not registered
+    #[track_caller]
+    fn world_ref(&self) -> GlobalRef<'_, World> {
+        if let Some(name) = active_world() {
+            return self.world_named_ref(&name);
+        }
+
+        self.global_ref::<World>()
+            .expect("Failed to access World. Ensure 'DefaultPlugins' or 'EcsPlugin' is added")
+    }
+
+    /// Returns the resource holding all named Worlds
+    ///
+    /// # Panics
+    /// Panics with a helpful message if EcsPlugin is not registered
+    #[track_caller]
+    fn worlds(&self) -> GlobalMut<'_, Worlds> {
+        self.global::<Worlds>()
+            .expect("Failed to access Worlds. Ensure 'DefaultPlugins' or 'EcsPlugin' is added")
+    }
+
+    /// Returns a named World
+    ///
+    /// # Panics
+    /// Panics if no World with this name was added to `Worlds`
+    #[track_caller]
+    fn world_named(&self, name: &str) -> GlobalMut<'_, World> {
+        let worlds = self.worlds();
+        if !worlds.contains(name) {
+            panic!(
+                "No World named '{}'. Add it with 'ctx.worlds().insert()'",
+                name
+            );
+        }
+
+        GlobalMut::map(worlds, |worlds| worlds.get_mut(name).unwrap())
+    }
+
+    /// Returns a shared reference to a named World
+    ///
+    /// # Panics
+    /// Panics if no World with this name was added to `Worlds`
+    #[track_caller]
+    fn world_named_ref(&self, name: &str) -> GlobalRef<'_, World> {
+        let worlds = self
+            .global_ref::<Worlds>()
+            .expect("Failed to access Worlds. Ensure 'DefaultPlugins' or 'EcsPlugin' is added");
+        if !worlds.contains(name) {
+            panic!(
+                "No World named '{}'. Add it with 'ctx.worlds().insert()'",
+                name
+            );
+        }
+
+        GlobalRef::map(worlds, |worlds| worlds.get(name).unwrap())
+    }
 }
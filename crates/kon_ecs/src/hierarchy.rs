@@ -0,0 +1,420 @@
+//! Entity hierarchy - Parent/Children relationships
+//!
+//! `Parent` and `Children` are regular components maintained by the World,
+//! so they can be queried like any other component. Use `set_parent` /
+//! `remove_parent` / `with_children` to change them. `Parent` hooks keep
+//! `Children` in sync when a `Parent` is inserted or removed directly; a
+//! `Parent` pointing at a dead entity or creating a cycle is dropped again.
+//! Overwriting a `Parent` through `get_mut` bypasses the hooks. Destroying an
+//! entity detaches it from its parent and orphans its children.
+//!
+//! # Example
+//! ```ignore
+//! let ship = world.spawn()
+//!     .insert(Position { x: 0.0, y: 0.0 })
+//!     .with_children(|parent| {
+//!         parent.spawn().insert(Weapon::Laser);
+//!         parent.spawn().insert(Weapon::Missile);
+//!     })
+//!     .id();
+//!
+//! for weapon in world.descendants(ship) {
+//!     println!("{} is attached to {}", weapon, ship);
+//! }
+//!
+//! world.destroy_recursive(ship); // ship and both weapons
+//! ```
+
+use crate::World;
+use crate::entity::{Entity, EntityBuilder};
+
+/// Component pointing at the parent of an entity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Parent(Entity);
+
+impl Parent {
+    /// Returns the parent entity
+    #[inline]
+    pub fn get(&self) -> Entity {
+        self.0
+    }
+}
+
+/// Component listing the children of an entity in insertion order
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Children(Vec<Entity>);
+
+impl Children {
+    /// Returns the children as a slice
+    #[inline]
+    pub fn as_slice(&self) -> &[Entity] {
+        &self.0
+    }
+
+    /// Iterates over the children
+    pub fn iter(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.0.iter().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Spawns entities as children of a parent entity
+///
+/// Obtained via `EntityBuilder::with_children`.
+pub struct ChildBuilder<'w> {
+    world: &'w mut World,
+    parent: Entity,
+}
+
+impl ChildBuilder<'_> {
+    /// Spawns a new child entity
+    pub fn spawn(&mut self) -> EntityBuilder<'_> {
+        let child = self.world.spawn().id();
+        self.world.set_parent(child, self.parent);
+        EntityBuilder::new(self.world, child)
+    }
+
+    /// Returns the parent entity
+    pub fn parent_entity(&self) -> Entity {
+        self.parent
+    }
+}
+
+impl<'w> EntityBuilder<'w> {
+    /// Spawns child entities under this entity
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.spawn()
+    ///     .insert(Position { x: 0.0, y: 0.0 })
+    ///     .with_children(|parent| {
+    ///         parent.spawn().insert(Weapon::Laser);
+    ///     });
+    /// ```
+    pub fn with_children(self, f: impl FnOnce(&mut ChildBuilder<'_>)) -> Self {
+        let (world, entity) = self.into_parts();
+        f(&mut ChildBuilder {
+            world: &mut *world,
+            parent: entity,
+        });
+        EntityBuilder::new(world, entity)
+    }
+}
+
+/// Adds an entity to the `Children` of its new parent
+///
+/// A parent that is dead or a descendant of the entity is rejected by
+/// removing the `Parent` again.
+fn link_child(world: &mut World, child: Entity) {
+    let Some(parent) = world.parent(child) else {
+        return;
+    };
+
+    if !world.is_alive(parent) || parent == child || world.ancestors(parent).any(|e| e == child) {
+        world.remove::<Parent>(child);
+        return;
+    }
+
+    match world.get_mut::<Children>(parent) {
+        Some(children) if children.0.contains(&child) => {}
+        Some(children) => children.0.push(child),
+        None => world.insert(parent, Children(vec![child])),
+    }
+}
+
+/// Removes an entity from the `Children` of its current parent
+fn unlink_child(world: &mut World, child: Entity) {
+    if let Some(parent) = world.parent(child) {
+        world.remove_child_entry(parent, child);
+    }
+}
+
+/// Depth-first iterator over the descendants of an entity
+pub struct Descendants<'w> {
+    world: &'w World,
+    stack: Vec<Entity>,
+}
+
+impl Iterator for Descendants<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let entity = self.stack.pop()?;
+        if let Some(children) = self.world.get::<Children>(entity) {
+            self.stack.extend(children.0.iter().rev());
+        }
+        Some(entity)
+    }
+}
+
+/// Iterator walking from an entity's parent up to the root
+pub struct Ancestors<'w> {
+    world: &'w World,
+    current: Option<Entity>,
+}
+
+impl Iterator for Ancestors<'_> {
+    type Item = Entity;
+
+    fn next(&mut self) -> Option<Entity> {
+        let parent = self.world.get::<Parent>(self.current?)?.get();
+        self.current = Some(parent);
+        Some(parent)
+    }
+}
+
+impl World {
+    /// Attaches `child` to `parent`, detaching it from its previous parent
+    ///
+    /// Returns false if either entity is dead, they are the same entity,
+    /// or `parent` is a descendant of `child` (which would create a cycle).
+    pub fn set_parent(&mut self, child: Entity, parent: Entity) -> bool {
+        if !self.is_alive(child) || !self.is_alive(parent) || child == parent {
+            return false;
+        }
+
+        if self.ancestors(parent).any(|e| e == child) {
+            return false;
+        }
+
+        if self.parent(child) == Some(parent) {
+            return true;
+        }
+
+        // The Parent hooks update both Children lists
+        self.insert(child, Parent(parent));
+        true
+    }
+
+    /// Detaches an entity from its parent
+    ///
+    /// Returns true if the entity had a parent.
+    pub fn remove_parent(&mut self, child: Entity) -> bool {
+        self.remove::<Parent>(child)
+    }
+
+    /// Returns the parent of an entity
+    pub fn parent(&self, entity: Entity) -> Option<Entity> {
+        self.get::<Parent>(entity).map(Parent::get)
+    }
+
+    /// Returns the direct children of an entity
+    pub fn children(&self, entity: Entity) -> &[Entity] {
+        self.get::<Children>(entity)
+            .map(Children::as_slice)
+            .unwrap_or(&[])
+    }
+
+    /// Iterates over all descendants of an entity, depth-first
+    ///
+    /// The entity itself is not included.
+    pub fn descendants(&self, entity: Entity) -> Descendants<'_> {
+        Descendants {
+            world: self,
+            stack: self.children(entity).iter().rev().copied().collect(),
+        }
+    }
+
+    /// Iterates from the parent of an entity up to the root
+    ///
+    /// The entity itself is not included.
+    pub fn ancestors(&self, entity: Entity) -> Ancestors<'_> {
+        Ancestors {
+            world: self,
+            current: Some(entity),
+        }
+    }
+
+    /// Destroys an entity together with all its descendants
+    ///
+    /// Returns true if the entity was alive, false otherwise.
+    pub fn destroy_recursive(&mut self, entity: Entity) -> bool {
+        if !self.is_alive(entity) {
+            return false;
+        }
+
+        let descendants: Vec<Entity> = self.descendants(entity).collect();
+        for descendant in descendants.into_iter().rev() {
+            self.destroy(descendant);
+        }
+
+        self.destroy(entity)
+    }
+
+    /// Removes an entity from its parent's `Children` and orphans its own children
+    ///
+    /// Called by `destroy` before components are dropped.
+    pub(crate) fn detach_from_hierarchy(&mut self, entity: Entity) {
+        if let Some(parent) = self.parent(entity) {
+            self.remove_child_entry(parent, entity);
+        }
+
+        if let Some(children) = self.get::<Children>(entity) {
+            for child in children.0.clone() {
+                self.remove::<Parent>(child);
+            }
+        }
+    }
+
+    /// Installs the `Parent` hooks, called by `World::new`
+    pub(crate) fn register_hierarchy(&mut self) {
+        self.hooks::<Parent>()
+            .on_insert(link_child)
+            .on_replace(unlink_child)
+            .on_remove(unlink_child);
+    }
+
+    /// Removes `child` from the `Children` list of `parent`
+    ///
+    /// The `Children` component is dropped once it becomes empty.
+    fn remove_child_entry(&mut self, parent: Entity, child: Entity) {
+        let now_empty = match self.get_mut::<Children>(parent) {
+            Some(children) => {
+                children.0.retain(|&e| e != child);
+                children.0.is_empty()
+            }
+            None => false,
+        };
+
+        if now_empty {
+            self.remove::<Children>(parent);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Weapon(u32);
+
+    #[test]
+    fn with_children_links_both_sides() {
+        let mut world = World::new();
+        let mut kids = Vec::new();
+        let ship = world
+            .spawn()
+            .with_children(|parent| {
+                kids.push(parent.spawn().insert(Weapon(1)).id());
+                kids.push(parent.spawn().insert(Weapon(2)).id());
+            })
+            .id();
+
+        assert_eq!(world.children(ship), kids.as_slice());
+        assert_eq!(world.parent(kids[0]), Some(ship));
+        assert_eq!(world.parent(kids[1]), Some(ship));
+    }
+
+    #[test]
+    fn set_parent_moves_child() {
+        let mut world = World::new();
+        let a = world.spawn().id();
+        let b = world.spawn().id();
+        let child = world.spawn().id();
+
+        assert!(world.set_parent(child, a));
+        assert!(world.set_parent(child, b));
+
+        assert!(!world.has::<Children>(a));
+        assert_eq!(world.children(b), &[child]);
+        assert_eq!(world.parent(child), Some(b));
+    }
+
+    #[test]
+    fn set_parent_rejects_cycles() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let child = world.spawn().id();
+        world.set_parent(child, root);
+
+        assert!(!world.set_parent(root, child));
+        assert!(!world.set_parent(root, root));
+    }
+
+    #[test]
+    fn destroy_cleans_up_parent_and_children() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let middle = world.spawn().id();
+        let leaf = world.spawn().id();
+        world.set_parent(middle, root);
+        world.set_parent(leaf, middle);
+
+        world.destroy(middle);
+
+        assert!(world.children(root).is_empty());
+        assert_eq!(world.parent(leaf), None);
+        assert!(world.is_alive(leaf));
+    }
+
+    #[test]
+    fn descendants_and_ancestors() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let a = world.spawn().id();
+        let b = world.spawn().id();
+        let a1 = world.spawn().id();
+        world.set_parent(a, root);
+        world.set_parent(b, root);
+        world.set_parent(a1, a);
+
+        let descendants: Vec<_> = world.descendants(root).collect();
+        assert_eq!(descendants, vec![a, a1, b]);
+
+        let ancestors: Vec<_> = world.ancestors(a1).collect();
+        assert_eq!(ancestors, vec![a, root]);
+    }
+
+    #[test]
+    fn destroy_recursive_removes_subtree() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let mut kids = Vec::new();
+        let ship = world
+            .spawn()
+            .with_children(|parent| {
+                kids.push(parent.spawn().id());
+                kids.push(parent.spawn().id());
+            })
+            .id();
+        world.set_parent(ship, root);
+
+        assert!(world.destroy_recursive(ship));
+
+        assert!(!world.is_alive(ship));
+        assert!(kids.iter().all(|&k| !world.is_alive(k)));
+        assert!(world.children(root).is_empty());
+        assert_eq!(world.entity_count(), 1);
+    }
+
+    #[test]
+    fn inserting_and_removing_parent_keeps_children_in_sync() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let first = world.spawn().id();
+        let second = world.spawn().id();
+        world.set_parent(first, root);
+
+        let parent = *world.get::<Parent>(first).unwrap();
+        world.insert(second, parent);
+        assert_eq!(world.children(root), &[first, second]);
+
+        world.remove::<Parent>(first);
+        assert_eq!(world.children(root), &[second]);
+
+        // A copied Parent that would close a cycle is dropped
+        world.set_parent(root, first);
+        world.insert(first, parent);
+        assert_eq!(world.parent(first), None);
+        assert_eq!(world.children(root), &[second]);
+        assert_eq!(world.children(first), &[root]);
+    }
+}
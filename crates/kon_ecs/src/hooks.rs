@@ -0,0 +1,242 @@
+//! Component lifecycle hooks
+//!
+//! Hooks are callbacks registered per component type that run with full
+//! `&mut World` access whenever a component of that type is added, inserted,
+//! replaced or removed. They are useful for keeping external indices
+//! (spatial hashes, name lookups) in sync with the World.
+//!
+//! # Ordering
+//! - `on_add` → component was inserted on an entity that didn't have it
+//! - `on_insert` → component was inserted (new or replacing), runs after `on_add`
+//! - `on_replace` → component is about to be overwritten, old value still readable
+//! - `on_remove` → component is about to be removed (also by `destroy`), value still readable
+//!
+//! Hooks run synchronously: `insert`, `remove` and `destroy` queue them and
+//! run them before returning, once the World is in a consistent state, so
+//! they may freely spawn, insert, remove or destroy. Components an `on_remove`
+//! hook inserts on an entity being destroyed get their own `on_remove` before
+//! the entity goes away. While a hook
+//! runs, changes to the same component of the same entity don't trigger hooks
+//! again; e.g. an `on_remove` hook removing its own component removes it
+//! without running itself a second time.
+//!
+//! # Example
+//! ```ignore
+//! world.hooks::<Health>()
+//!     .on_add(|world, entity| println!("{} gained Health", entity))
+//!     .on_remove(|world, entity| {
+//!         let hp = world.get::<Health>(entity).unwrap();
+//!         println!("{} lost Health ({})", entity, hp.0);
+//!     });
+//! ```
+
+use crate::World;
+use crate::entity::Entity;
+use std::sync::Arc;
+
+/// Shared callback invoked by a component hook
+pub type ComponentHook = Arc<dyn Fn(&mut World, Entity) + Send + Sync>;
+
+/// Lifecycle hooks registered for a single component type
+///
+/// Obtained via `world.hooks::<C>()`. Registering a hook of the same kind
+/// again replaces the previous one.
+#[derive(Default, Clone)]
+pub struct ComponentHooks {
+    pub(crate) on_add: Option<ComponentHook>,
+    pub(crate) on_insert: Option<ComponentHook>,
+    pub(crate) on_replace: Option<ComponentHook>,
+    pub(crate) on_remove: Option<ComponentHook>,
+}
+
+/// Which lifecycle event triggered a hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum HookKind {
+    Add,
+    Insert,
+    Replace,
+    Remove,
+}
+
+impl ComponentHooks {
+    /// Runs after the component is added to an entity that didn't have it
+    pub fn on_add<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&mut World, Entity) + Send + Sync + 'static,
+    {
+        self.on_add = Some(Arc::new(hook));
+        self
+    }
+
+    /// Runs after every insert of the component, new or replacing
+    pub fn on_insert<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&mut World, Entity) + Send + Sync + 'static,
+    {
+        self.on_insert = Some(Arc::new(hook));
+        self
+    }
+
+    /// Runs before an existing component is overwritten by an insert
+    pub fn on_replace<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&mut World, Entity) + Send + Sync + 'static,
+    {
+        self.on_replace = Some(Arc::new(hook));
+        self
+    }
+
+    /// Runs before the component is removed, including removal by `destroy`
+    pub fn on_remove<F>(&mut self, hook: F) -> &mut Self
+    where
+        F: Fn(&mut World, Entity) + Send + Sync + 'static,
+    {
+        self.on_remove = Some(Arc::new(hook));
+        self
+    }
+
+    /// Returns the hook registered for the given kind
+    pub(crate) fn get(&self, kind: HookKind) -> Option<&ComponentHook> {
+        match kind {
+            HookKind::Add => self.on_add.as_ref(),
+            HookKind::Insert => self.on_insert.as_ref(),
+            HookKind::Replace => self.on_replace.as_ref(),
+            HookKind::Remove => self.on_remove.as_ref(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::World;
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Marker;
+
+    type Log = Arc<Mutex<Vec<String>>>;
+
+    fn recorder() -> (Log, Log) {
+        let log = Log::default();
+        (log.clone(), log)
+    }
+
+    #[test]
+    fn add_and_insert_hooks_run_on_new_component() {
+        let (log, out) = recorder();
+        let mut world = World::new();
+
+        let add_log = log.clone();
+        world
+            .hooks::<Health>()
+            .on_add(move |_, _| add_log.lock().unwrap().push("add".into()))
+            .on_insert(move |_, _| log.lock().unwrap().push("insert".into()));
+
+        world.spawn().insert(Health(10));
+
+        assert_eq!(*out.lock().unwrap(), vec!["add", "insert"]);
+    }
+
+    #[test]
+    fn replace_hook_sees_old_value() {
+        let (log, out) = recorder();
+        let mut world = World::new();
+
+        world.hooks::<Health>().on_replace(move |world, entity| {
+            let hp = world.get::<Health>(entity).unwrap().0;
+            log.lock().unwrap().push(format!("replace {}", hp));
+        });
+
+        let entity = world.spawn().insert(Health(10)).id();
+        world.insert(entity, Health(20));
+
+        assert_eq!(*out.lock().unwrap(), vec!["replace 10"]);
+        assert_eq!(world.get::<Health>(entity), Some(&Health(20)));
+    }
+
+    #[test]
+    fn remove_hook_runs_on_destroy() {
+        let (log, out) = recorder();
+        let mut world = World::new();
+
+        world.hooks::<Health>().on_remove(move |world, entity| {
+            let hp = world.get::<Health>(entity).unwrap().0;
+            log.lock().unwrap().push(format!("remove {}", hp));
+        });
+
+        let e1 = world.spawn().insert(Health(1)).id();
+        let e2 = world.spawn().insert(Health(2)).id();
+
+        world.remove::<Health>(e1);
+        world.destroy(e2);
+
+        assert_eq!(*out.lock().unwrap(), vec!["remove 1", "remove 2"]);
+    }
+
+    #[test]
+    fn hooks_can_modify_world() {
+        let mut world = World::new();
+
+        world.hooks::<Health>().on_add(|world, entity| {
+            world.insert(entity, Marker);
+        });
+
+        let entity = world.spawn().insert(Health(10)).id();
+
+        assert!(world.has::<Marker>(entity));
+    }
+
+    #[test]
+    fn hooks_touching_their_own_component_do_not_recurse() {
+        let (log, out) = recorder();
+        let mut world = World::new();
+
+        world
+            .hooks::<Health>()
+            .on_insert(|world, entity| {
+                // Clamp by inserting again
+                let hp = world.get::<Health>(entity).unwrap().0;
+                world.insert(entity, Health(hp.min(100)));
+            })
+            .on_remove(move |world, entity| {
+                log.lock().unwrap().push("remove".to_string());
+                world.remove::<Health>(entity);
+            });
+
+        let entity = world.spawn().insert(Health(500)).id();
+        assert_eq!(world.get::<Health>(entity), Some(&Health(100)));
+
+        world.remove::<Health>(entity);
+        assert!(!world.has::<Health>(entity));
+
+        let other = world.spawn().insert(Health(1)).id();
+        world.destroy(other);
+        assert_eq!(*out.lock().unwrap(), vec!["remove", "remove"]);
+    }
+
+    #[test]
+    fn components_inserted_while_destroying_get_remove_hooks() {
+        let (log, out) = recorder();
+        let mut world = World::new();
+
+        world.hooks::<Health>().on_remove(|world, entity| {
+            world.insert(entity, Marker);
+        });
+        world.hooks::<Marker>().on_remove(move |world, entity| {
+            assert!(world.has::<Marker>(entity));
+            log.lock().unwrap().push("marker".to_string());
+        });
+
+        let entity = world.spawn().insert(Health(1)).id();
+        assert!(world.destroy(entity));
+
+        assert!(!world.is_alive(entity));
+        assert_eq!(*out.lock().unwrap(), vec!["marker"]);
+        let mut markers = 0;
+        world.select::<(Marker,)>().each(|_, _| markers += 1);
+        assert_eq!(markers, 0);
+    }
+}
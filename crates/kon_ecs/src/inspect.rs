@@ -0,0 +1,520 @@
+//! Structured World inspection
+//!
+//! `world.inspection()` captures entities, tags, component values (as
+//! `Debug` strings) and storage statistics in a serializable report that
+//! can be exported as JSON for external tools or compared in tests.
+//! The report's `Display` impl renders the table printed by `inspect()`, and
+//! `memory_table()` the one printed by `dump_all_memory()`.
+//!
+//! Available in debug builds, and in release builds with the `inspect`
+//! feature enabled.
+//!
+//! # Example
+//! ```ignore
+//! let report = world.inspection();
+//! std::fs::write("world.json", report.to_json())?;
+//!
+//! for storage in &report.storages {
+//!     println!("{}: {}/{}", storage.name, storage.len, storage.capacity);
+//! }
+//! ```
+
+use crate::World;
+use crate::entity::Entity;
+use crate::storage::MemoryUsage;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Snapshot of a World for debugging tools
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct WorldInspection {
+    pub change_tick: u32,
+    /// Alive entities, ordered by entity ID
+    pub entities: Vec<EntityInspection>,
+    /// Component storages, ordered by short type name
+    pub storages: Vec<StorageInspection>,
+    pub resource_count: usize,
+}
+
+/// A single entity of a `WorldInspection`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EntityInspection {
+    pub entity: Entity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub parent: Option<Entity>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// `Debug` output per component, keyed by full type path
+    pub components: BTreeMap<String, String>,
+}
+
+/// Statistics of one component storage
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageInspection {
+    /// Short type name, e.g. `Position`
+    pub name: String,
+    /// Full type path, e.g. `game::components::Position`
+    pub type_name: String,
+    /// Number of stored components
+    pub len: usize,
+    /// Number of components that fit without reallocating
+    pub capacity: usize,
+    /// Size of one component value in bytes
+    pub element_size: usize,
+    /// Entity IDs in the order their components are stored
+    pub entity_ids: Vec<u32>,
+    pub memory: MemoryUsage,
+}
+
+impl WorldInspection {
+    /// Serializes the report to pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("inspection reports are valid JSON")
+    }
+
+    /// Returns the entry of an entity
+    pub fn entity(&self, entity: Entity) -> Option<&EntityInspection> {
+        self.entities.iter().find(|e| e.entity == entity)
+    }
+
+    /// Returns the total heap memory of all storages
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.storages
+            .iter()
+            .fold(MemoryUsage::default(), |total, storage| {
+                total + storage.memory
+            })
+    }
+
+    /// Renders the memory layout of every storage, as printed by
+    /// `dump_all_memory()`
+    pub fn memory_table(&self) -> impl fmt::Display + '_ {
+        MemoryTable(self)
+    }
+}
+
+impl EntityInspection {
+    /// Returns the `Debug` output of a component by full or short type name
+    ///
+    /// A short name matching several types returns the first in type path order.
+    pub fn component(&self, name: &str) -> Option<&str> {
+        self.components
+            .get(name)
+            .or_else(|| {
+                self.components
+                    .iter()
+                    .find(|(type_name, _)| short_name(type_name) == name)
+                    .map(|(_, value)| value)
+            })
+            .map(String::as_str)
+    }
+
+    /// Entity handle followed by its name, as in `World::describe`
+    fn label(&self) -> String {
+        match &self.name {
+            Some(name) => format!("{} {:?}", self.entity, name),
+            None => self.entity.to_string(),
+        }
+    }
+}
+
+fn short_name(type_name: &str) -> &str {
+    type_name.rsplit("::").next().unwrap_or(type_name)
+}
+
+/// Renders the inspector table
+impl fmt::Display for WorldInspection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "\n╔══════════════════════════════════════════════════════════════════════════╗"
+        )?;
+        writeln!(
+            f,
+            "║                            WORLD INSPECTOR                               ║"
+        )?;
+        writeln!(
+            f,
+            "╠══════════════════════════════════════════════════════════════════════════╣"
+        )?;
+        writeln!(
+            f,
+            "║  Entities: {:<5}  Component Types: {:<5}                                 ║",
+            self.entities.len(),
+            self.storages.len()
+        )?;
+        writeln!(
+            f,
+            "╚══════════════════════════════════════════════════════════════════════════╝\n"
+        )?;
+
+        if self.entities.is_empty() {
+            return writeln!(f, "  (no entities)");
+        }
+
+        let labels: Vec<String> = self.entities.iter().map(EntityInspection::label).collect();
+
+        // Calculate column widths
+        let entity_width = labels
+            .iter()
+            .map(|l| l.len() + 2)
+            .max()
+            .unwrap_or(0)
+            .max(14);
+        let tags_width = 20;
+
+        let mut col_widths: Vec<usize> = self.storages.iter().map(|s| s.name.len()).collect();
+
+        for entity in &self.entities {
+            for (storage, width) in self.storages.iter().zip(&mut col_widths) {
+                if let Some(value) = entity.components.get(&storage.type_name) {
+                    *width = (*width).max(value.len());
+                }
+            }
+        }
+
+        // Add padding
+        for w in &mut col_widths {
+            *w = (*w).max(10) + 2;
+        }
+
+        // Header
+        write!(f, "┌{:─<entity_width$}┬{:─<tags_width$}", "", "")?;
+        for w in &col_widths {
+            write!(f, "┬{:─<w$}", "")?;
+        }
+        writeln!(f, "┐")?;
+
+        write!(f, "│{:<entity_width$}│{:<tags_width$}", " Entity", " Tags")?;
+        for (storage, w) in self.storages.iter().zip(&col_widths) {
+            write!(f, "│ {:<width$}", storage.name, width = w - 1)?;
+        }
+        writeln!(f, "│")?;
+
+        write!(f, "├{:─<entity_width$}┼{:─<tags_width$}", "", "")?;
+        for w in &col_widths {
+            write!(f, "┼{:─<w$}", "")?;
+        }
+        writeln!(f, "┤")?;
+
+        // Rows
+        for (entity, label) in self.entities.iter().zip(&labels) {
+            let tags = if entity.tags.is_empty() {
+                "-".to_string()
+            } else {
+                entity.tags.join(", ")
+            };
+
+            write!(
+                f,
+                "│ {:<width$}│ {:<tags_w$}",
+                label,
+                tags,
+                width = entity_width - 1,
+                tags_w = tags_width - 1
+            )?;
+
+            for (storage, w) in self.storages.iter().zip(&col_widths) {
+                let value = entity
+                    .components
+                    .get(&storage.type_name)
+                    .map(String::as_str)
+                    .unwrap_or("-");
+                write!(f, "│ {:<width$}", value, width = w - 1)?;
+            }
+            writeln!(f, "│")?;
+        }
+
+        // Footer
+        write!(f, "└{:─<entity_width$}┴{:─<tags_width$}", "", "")?;
+        for w in &col_widths {
+            write!(f, "┴{:─<w$}", "")?;
+        }
+        writeln!(f, "┘")
+    }
+}
+
+/// Renders the storage memory layout of a report
+struct MemoryTable<'a>(&'a WorldInspection);
+
+impl fmt::Display for MemoryTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let report = self.0;
+        let memory = report.memory_usage();
+
+        writeln!(
+            f,
+            "╔══════════════════════════════════════════════════════════╗"
+        )?;
+        writeln!(
+            f,
+            "║                   WORLD MEMORY DUMP                      ║"
+        )?;
+        writeln!(
+            f,
+            "╠══════════════════════════════════════════════════════════╣"
+        )?;
+        writeln!(
+            f,
+            "║ Entities: {:<5}          Component Types: {:<5}          ║",
+            report.entities.len(),
+            report.storages.len()
+        )?;
+        writeln!(
+            f,
+            "║{:<58}║",
+            format!(
+                " Memory: {:<12} bytes     Sparse Pages: {:<5}",
+                memory.total(),
+                memory.pages
+            )
+        )?;
+        writeln!(
+            f,
+            "╚══════════════════════════════════════════════════════════╝"
+        )?;
+
+        if report.storages.is_empty() {
+            return writeln!(f, "(No components registered)");
+        }
+
+        for storage in &report.storages {
+            let memory = storage.memory;
+
+            writeln!(
+                f,
+                "\n● STORAGE: {} ({} bytes per element, {}/{} used)",
+                storage.name.to_uppercase(),
+                storage.element_size,
+                storage.len,
+                storage.capacity
+            )?;
+            writeln!(
+                f,
+                "  Memory: {} B dense, {} B metadata, {} B sparse ({} pages)",
+                memory.dense, memory.metadata, memory.sparse, memory.pages
+            )?;
+            writeln!(f, "┌─────────┬───────────┬───────────────┐")?;
+            writeln!(f, "│  Index  │ Entity ID │    Offset     │")?;
+            writeln!(f, "├─────────┼───────────┼───────────────┤")?;
+
+            for (index, entity_id) in storage.entity_ids.iter().enumerate() {
+                writeln!(
+                    f,
+                    "│ {:^7} │ {:^9} │ {:>11} B │",
+                    index,
+                    entity_id,
+                    index * storage.element_size
+                )?;
+            }
+            writeln!(f, "└─────────┴───────────┴───────────────┘")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl World {
+    /// Captures entities, components and storage statistics as a report
+    ///
+    /// Components are keyed by their full type path, so types sharing a
+    /// short name (e.g. two `Position` types in different modules) are kept
+    /// apart; the table shows both under the short name.
+    pub fn inspection(&self) -> WorldInspection {
+        let mut storages: Vec<_> = self.components().values().collect();
+        storages.sort_by_key(|s| (short_name(s.type_name()), s.type_name()));
+
+        let mut ids: Vec<u32> = self.alive.iter().copied().collect();
+        ids.sort_unstable();
+
+        let entities = ids
+            .into_iter()
+            .map(|id| {
+                let entity = Entity::new(id, self.generations[id as usize]);
+
+                EntityInspection {
+                    entity,
+                    name: self.name(entity).map(str::to_string),
+                    parent: self.parent(entity),
+                    tags: self.get_entity_tags(id),
+                    components: storages
+                        .iter()
+                        .filter_map(|s| {
+                            let value = s.debug_entry(id)?;
+                            Some((s.type_name().to_string(), value))
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        WorldInspection {
+            change_tick: self.change_tick(),
+            entities,
+            storages: storages
+                .iter()
+                .map(|s| StorageInspection {
+                    name: short_name(s.type_name()).to_string(),
+                    type_name: s.type_name().to_string(),
+                    len: s.len(),
+                    capacity: s.capacity(),
+                    element_size: s.element_size(),
+                    entity_ids: s.entity_ids().to_vec(),
+                    memory: s.memory_usage(),
+                })
+                .collect(),
+            resource_count: self.resource_count(),
+        }
+    }
+
+    /// Prints a formatted table of all entities and their components
+    ///
+    /// Shows:
+    /// - Entity IDs, generations and names
+    /// - Attached tags
+    /// - Component values
+    ///
+    /// Useful for debugging game state and verifying entity configurations.
+    /// Only enabled in debug builds or with the `inspect` feature to avoid
+    /// string formatting overhead. See `inspection()` for the data itself.
+    pub fn inspect(&self) {
+        print!("{}", self.inspection());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::name::Name;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    fn sample() -> (World, Entity, Entity) {
+        let mut world = World::new();
+        let player = world
+            .spawn()
+            .insert(Name::new("player"))
+            .insert(Health(10))
+            .tag("friendly")
+            .id();
+        let sword = world.spawn().insert(Position { x: 1.0, y: 2.0 }).id();
+        world.set_parent(sword, player);
+        (world, player, sword)
+    }
+
+    #[test]
+    fn report_lists_entities_and_components() {
+        let (world, player, sword) = sample();
+        let report = world.inspection();
+
+        assert_eq!(report.entities.len(), 2);
+
+        let entry = report.entity(player).unwrap();
+        assert_eq!(entry.name.as_deref(), Some("player"));
+        assert_eq!(entry.tags, vec!["friendly"]);
+        assert_eq!(entry.component("Health"), Some("Health(10)"));
+        assert_eq!(
+            entry.components[std::any::type_name::<Health>()],
+            "Health(10)"
+        );
+
+        let entry = report.entity(sword).unwrap();
+        assert_eq!(entry.parent, Some(player));
+        assert_eq!(
+            entry.component("Position"),
+            Some("Position { x: 1.0, y: 2.0 }")
+        );
+
+        let health = report.storages.iter().find(|s| s.name == "Health").unwrap();
+        assert_eq!(health.len, 1);
+        assert!(health.capacity >= 1);
+        assert!(health.type_name.ends_with("Health"));
+        assert_eq!(report.memory_usage(), world.memory_usage());
+    }
+
+    #[test]
+    fn report_round_trips_through_json() {
+        let (world, _, _) = sample();
+        let report = world.inspection();
+
+        let parsed: WorldInspection = serde_json::from_str(&report.to_json()).unwrap();
+        assert_eq!(parsed, report);
+    }
+
+    #[test]
+    fn memory_table_follows_storage_order() {
+        let mut world = World::new();
+        let ids: Vec<Entity> = (0..3)
+            .map(|hp| world.spawn().insert(Health(hp)).id())
+            .collect();
+        world.destroy(ids[0]);
+
+        let report = world.inspection();
+        let health = report.storages.iter().find(|s| s.name == "Health").unwrap();
+        assert_eq!(health.entity_ids, vec![ids[2].id(), ids[1].id()]);
+        assert_eq!(health.element_size, std::mem::size_of::<Health>());
+
+        let table = report.memory_table().to_string();
+        assert!(table.contains("STORAGE: HEALTH (4 bytes per element, 2/"));
+        assert!(
+            World::new()
+                .inspection()
+                .memory_table()
+                .to_string()
+                .contains("(No components registered)")
+        );
+    }
+
+    #[test]
+    fn table_shows_names_and_values() {
+        let (world, player, _) = sample();
+        let table = world.inspection().to_string();
+
+        assert!(table.contains(&format!("{} \"player\"", player)));
+        assert!(table.contains("Health(10)"));
+        assert!(table.contains("friendly"));
+
+        assert!(
+            World::new()
+                .inspection()
+                .to_string()
+                .contains("(no entities)")
+        );
+    }
+
+    #[test]
+    fn same_short_names_stay_apart() {
+        mod legacy {
+            #[derive(Debug, Clone, PartialEq)]
+            pub struct Health(pub u8);
+        }
+
+        let mut world = World::new();
+        let entity = world
+            .spawn()
+            .insert(Health(10))
+            .insert(legacy::Health(3))
+            .id();
+
+        let report = world.inspection();
+        let entry = report.entity(entity).unwrap();
+        assert_eq!(entry.components.len(), 2);
+        assert_eq!(
+            entry.component(std::any::type_name::<legacy::Health>()),
+            Some("Health(3)")
+        );
+
+        let table = report.to_string();
+        assert!(table.contains("Health(10)") && table.contains("Health(3)"));
+    }
+}
@@ -41,12 +41,34 @@
 //! }
 //! ```
 
+mod bundle;
+mod change_detection;
+mod commands;
+#[cfg(any(debug_assertions, feature = "inspect"))]
+mod diff;
 mod entity;
+mod entity_ref;
 mod ext;
+mod hierarchy;
+mod hooks;
+#[cfg(any(debug_assertions, feature = "inspect"))]
+mod inspect;
+mod name;
+mod observer;
 mod plugin;
+mod prefab;
 mod query;
+mod query_state;
+mod relation;
+mod resource;
+mod scene;
+mod snapshot;
+mod spatial;
 mod storage;
+mod tag;
+mod transfer;
 mod world;
+mod worlds;
 
 use std::{any::Any, fmt::Debug};
 
@@ -69,12 +91,37 @@ use std::{any::Any, fmt::Debug};
 pub trait Component: Any + Send + Sync + Debug + 'static {}
 impl<T: Any + Send + Sync + Debug + 'static> Component for T {}
 
-pub use entity::{Entity, EntityBuilder};
+pub use bundle::Bundle;
+pub use change_detection::{Added, Changed, ComponentTicks, RemovedComponents};
+pub use commands::{Commands, EntityCommands};
+#[cfg(any(debug_assertions, feature = "inspect"))]
+pub use diff::{ComponentDiff, EntityChange, EntityDiff, WorldDiff};
+pub use entity::{Entity, EntityBuilder, EntityId};
+pub use entity_ref::{EntityMut, EntityRef};
 pub use ext::ContextEcsExt;
+pub use hierarchy::{Ancestors, ChildBuilder, Children, Descendants, Parent};
+pub use hooks::{ComponentHook, ComponentHooks};
+#[cfg(any(debug_assertions, feature = "inspect"))]
+pub use inspect::{EntityInspection, StorageInspection, WorldInspection};
+pub use name::Name;
+pub use observer::{EntityEvent, ObserverId, Trigger};
 pub use plugin::EcsPlugin;
-pub use query::{Query, QueryMut};
+pub use prefab::{Prefab, PrefabError};
+pub use query::{FilterParam, Query, QueryFilter, QueryMut, With, Without};
+pub use query_state::{QueryState, StateTuple};
+pub use relation::{CleanupPolicy, Relation};
+pub use scene::{EntityMapper, MapEntities, Scene, SceneEntity, SceneError};
+pub use snapshot::{SNAPSHOT_VERSION, SnapshotError};
+pub use spatial::{SpatialHash, SpatialPosition};
+pub use storage::MemoryUsage;
+pub use tag::{Tag, TagMask, TagRef};
 pub use world::World;
+pub use worlds::{Worlds, in_world, in_world_parallel};
 
 pub mod prelude {
-    pub use crate::{ContextEcsExt, EcsPlugin, Entity, World};
+    pub use crate::{
+        Added, Bundle, Changed, Children, CleanupPolicy, Commands, ContextEcsExt, EcsPlugin,
+        Entity, EntityEvent, Name, Parent, QueryState, Relation, SpatialHash, SpatialPosition, Tag,
+        Trigger, With, Without, World, Worlds, in_world, in_world_parallel,
+    };
 }
@@ -0,0 +1,285 @@
+//! Built-in `Name` component and lookup by name
+//!
+//! Every World indexes `Name` components through lifecycle hooks, so
+//! `find_by_name` doesn't need a query. Names edited in place (through
+//! `get_mut` or a mutable query) are re-indexed by `clear_trackers()`; until
+//! then lookups scan the `Name` storage, so they are never stale. Names are shown by `inspect`,
+//! `describe` and the `Debug` output of `EntityRef` / `EntityMut`, and are
+//! saved in scenes and snapshots under "Name".
+//!
+//! Names don't have to be unique; `find_all_by_name` returns every holder.
+//!
+//! # Example
+//! ```ignore
+//! let player = world.spawn().insert(Name::new("player")).id();
+//!
+//! assert_eq!(world.find_by_name("player"), Some(player));
+//! println!("{}", world.describe(player)); // Entity(0v0) "player"
+//! ```
+
+use crate::World;
+use crate::change_detection::ComponentTicks;
+use crate::entity::Entity;
+use crate::storage::SparseSet;
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Deref;
+
+/// Human-readable entity name
+///
+/// Rename an entity by inserting a new `Name`, or change it in place; lookups
+/// see both right away, but in-place edits make them scan every `Name` until
+/// the next `clear_trackers()`. The index uses the `on_insert`, `on_replace`
+/// and `on_remove` hooks of `Name`, so don't replace those.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Name(String);
+
+impl Name {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self(name.into())
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl Deref for Name {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Name {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<&str> for Name {
+    fn from(name: &str) -> Self {
+        Self::new(name)
+    }
+}
+
+impl From<String> for Name {
+    fn from(name: String) -> Self {
+        Self(name)
+    }
+}
+
+/// Entities per name, in naming order
+#[derive(Default)]
+pub(crate) struct NameIndex {
+    entities: HashMap<String, Vec<Entity>>,
+    /// Indexed name of every entity, which an in-place edit leaves behind
+    indexed: HashMap<Entity, String>,
+    /// Change tick of the last re-index of in-place edits
+    synced: u32,
+}
+
+impl NameIndex {
+    fn add(&mut self, name: &str, entity: Entity) {
+        self.remove(entity);
+        self.indexed.insert(entity, name.to_string());
+        self.entities
+            .entry(name.to_string())
+            .or_default()
+            .push(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) {
+        let Some(name) = self.indexed.remove(&entity) else {
+            return;
+        };
+
+        if let Some(entities) = self.entities.get_mut(&name) {
+            entities.retain(|e| *e != entity);
+            if entities.is_empty() {
+                self.entities.remove(&name);
+            }
+        }
+    }
+}
+
+fn index_name(world: &mut World, entity: Entity) {
+    if let Some(name) = world.get::<Name>(entity).cloned() {
+        world.names.add(&name, entity);
+    }
+}
+
+fn unindex_name(world: &mut World, entity: Entity) {
+    world.names.remove(entity);
+}
+
+/// Formats an entity with an optional name, see `World::describe`
+pub(crate) fn describe_entity(entity: Entity, name: Option<&str>) -> String {
+    match name {
+        Some(name) => format!("{} {:?}", entity, name),
+        None => entity.to_string(),
+    }
+}
+
+impl World {
+    /// Installs the `Name` hooks and registrations, called by `World::new`
+    pub(crate) fn register_names(&mut self) {
+        self.hooks::<Name>()
+            .on_insert(index_name)
+            .on_replace(unindex_name)
+            .on_remove(unindex_name);
+        self.register_serializable::<Name>("Name");
+        self.register_component::<Name>();
+    }
+
+    /// Returns the first entity named `name`
+    pub fn find_by_name(&self, name: &str) -> Option<Entity> {
+        self.find_all_by_name(name).first().copied()
+    }
+
+    /// Returns every entity named `name`, in the order they were named
+    ///
+    /// Entities renamed in place since the last `clear_trackers()` come last.
+    pub fn find_all_by_name(&self, name: &str) -> Vec<Entity> {
+        let mut found: Vec<Entity> = self
+            .names
+            .entities
+            .get(name)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|e| self.name(*e) == Some(name))
+            .collect();
+
+        for (entity, renamed) in self.renamed_in_place() {
+            if renamed == name && !found.contains(&entity) {
+                found.push(entity);
+            }
+        }
+
+        found
+    }
+
+    /// Re-indexes names edited in place, called by `clear_trackers()`
+    pub(crate) fn sync_names(&mut self) {
+        let renamed: Vec<(Entity, Name)> = self
+            .renamed_in_place()
+            .filter(|(entity, name)| {
+                self.names.indexed.get(entity).map(String::as_str) != Some(name)
+            })
+            .map(|(entity, name)| (entity, Name::new(name)))
+            .collect();
+
+        for (entity, name) in renamed {
+            self.names.add(&name, entity);
+        }
+        self.names.synced = self.change_tick();
+    }
+
+    /// Iterates over names changed in place since the last re-index
+    fn renamed_in_place(&self) -> impl Iterator<Item = (Entity, &str)> {
+        let synced = self.names.synced;
+        let this_run = self.change_tick();
+        let storage = self
+            .components
+            .get(&TypeId::of::<Name>())
+            .and_then(|s| s.as_any().downcast_ref::<SparseSet<Name>>())
+            .filter(|s| ComponentTicks::new(s.last_mutated()).is_changed(synced, this_run));
+
+        storage
+            .into_iter()
+            .flat_map(|s| s.iter_with_ticks())
+            .filter(move |(_, _, ticks)| ticks.is_changed(synced, this_run))
+            .map(|(id, name, _)| (Entity::from_raw(id, self.generation(id)), name.as_str()))
+    }
+
+    /// Returns the name of an entity
+    pub fn name(&self, entity: Entity) -> Option<&str> {
+        self.get::<Name>(entity).map(Name::as_str)
+    }
+
+    /// Formats an entity with its name for logs and error messages
+    ///
+    /// `Entity(3v0) "player"`, or just `Entity(3v0)` without a `Name`.
+    pub fn describe(&self, entity: Entity) -> String {
+        describe_entity(entity, self.name(entity))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_by_name_follows_inserts_and_removals() {
+        let mut world = World::new();
+        let a = world.spawn().insert(Name::new("guard")).id();
+        let b = world.spawn().insert(Name::new("guard")).id();
+        let player = world.spawn().insert(Name::new("player")).id();
+
+        assert_eq!(world.find_by_name("player"), Some(player));
+        assert_eq!(world.find_all_by_name("guard"), vec![a, b]);
+        assert_eq!(world.find_by_name("nobody"), None);
+
+        world.insert(a, Name::new("captain"));
+        world.destroy(b);
+
+        assert_eq!(world.find_by_name("captain"), Some(a));
+        assert!(world.find_all_by_name("guard").is_empty());
+
+        world.remove::<Name>(player);
+        assert_eq!(world.find_by_name("player"), None);
+    }
+
+    #[test]
+    fn in_place_edits_are_found_under_the_new_name() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Name::new("old")).id();
+        let other = world.spawn().insert(Name::new("new")).id();
+
+        *world.get_mut::<Name>(entity).unwrap() = Name::new("new");
+        assert_eq!(world.find_by_name("old"), None);
+        assert_eq!(world.find_all_by_name("new"), vec![other, entity]);
+
+        world.clear_trackers();
+        assert_eq!(world.find_all_by_name("new"), vec![other, entity]);
+
+        world
+            .select_mut::<(Name,)>()
+            .each(|_, (name,)| *name = Name::new("renamed"));
+        assert_eq!(world.find_all_by_name("renamed").len(), 2);
+        world.clear_trackers();
+        assert_eq!(world.find_all_by_name("renamed").len(), 2);
+        assert!(world.find_all_by_name("new").is_empty());
+    }
+
+    #[test]
+    fn describe_includes_name() {
+        let mut world = World::new();
+        let named = world.spawn().insert(Name::new("player")).id();
+        let anonymous = world.spawn().id();
+
+        assert_eq!(world.describe(named), format!("{} \"player\"", named));
+        assert_eq!(world.describe(anonymous), anonymous.to_string());
+        assert_eq!(
+            format!("{:?}", world.entity(named).unwrap()),
+            format!("EntityRef({} \"player\")", named)
+        );
+    }
+
+    #[test]
+    fn names_survive_scene_round_trip() {
+        let mut world = World::new();
+        world.spawn().insert(Name::new("door"));
+        let json = world.save_scene().unwrap();
+
+        let mut loaded = World::new();
+        let entities = loaded.load_scene(&json).unwrap();
+
+        assert_eq!(loaded.find_by_name("door"), Some(entities[0]));
+    }
+}
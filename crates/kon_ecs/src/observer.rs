@@ -0,0 +1,372 @@
+//! Entity events and observers
+//!
+//! `kon_core::Events` are global and keyed only by type. Entity events are
+//! targeted instead: `world.trigger_on(entity, event)` runs the observers
+//! registered for that entity plus the global observers of the event type,
+//! with full `&mut World` access. Events with `PROPAGATE = true` then bubble
+//! up the hierarchy, running the observers of each ancestor in turn, until
+//! the root is reached or an observer calls `stop_propagation`.
+//!
+//! # Example
+//! ```ignore
+//! struct Damage(i32);
+//!
+//! impl EntityEvent for Damage {
+//!     const PROPAGATE: bool = true;
+//! }
+//!
+//! // Any entity taking damage
+//! world.observe::<Damage>(|world, trigger| {
+//!     if let Some(hp) = world.get_mut::<Health>(trigger.entity()) {
+//!         hp.0 -= trigger.event().0;
+//!     }
+//! });
+//!
+//! // The boss shield absorbs hits on the boss and all of its parts
+//! world.observe_entity::<Damage>(boss, |_, trigger| {
+//!     trigger.event_mut().0 /= 2;
+//! });
+//!
+//! world.trigger_on(boss_arm, Damage(10));
+//! ```
+
+use crate::World;
+use crate::entity::Entity;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+/// Event type that can be triggered on an entity
+///
+/// ```ignore
+/// struct Clicked;
+/// impl EntityEvent for Clicked {}
+/// ```
+pub trait EntityEvent: Any + Send + Sync {
+    /// Whether the event bubbles up to the parents of the target by default
+    const PROPAGATE: bool = false;
+}
+
+/// Handle of a registered observer, used to remove it again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObserverId(u64);
+
+/// Event being delivered to observers
+pub struct Trigger<E> {
+    event: E,
+    origin: Entity,
+    entity: Entity,
+    propagate: bool,
+}
+
+impl<E: EntityEvent> Trigger<E> {
+    /// Entity whose observers are currently running
+    ///
+    /// Differs from `origin` while the event bubbles up to ancestors.
+    pub fn entity(&self) -> Entity {
+        self.entity
+    }
+
+    /// Entity the event was originally triggered on
+    pub fn origin(&self) -> Entity {
+        self.origin
+    }
+
+    pub fn event(&self) -> &E {
+        &self.event
+    }
+
+    /// Mutable event, e.g. to reduce damage before parents see it
+    pub fn event_mut(&mut self) -> &mut E {
+        &mut self.event
+    }
+
+    /// Keeps the event from bubbling further up the hierarchy
+    ///
+    /// Remaining observers of the current entity still run.
+    pub fn stop_propagation(&mut self) {
+        self.propagate = false;
+    }
+
+    /// Overrides `E::PROPAGATE` for this trigger
+    pub fn set_propagate(&mut self, propagate: bool) {
+        self.propagate = propagate;
+    }
+}
+
+/// Type-erased observer receiving a `&mut Trigger<E>`
+type ErasedObserver = Arc<dyn Fn(&mut World, &mut dyn Any) + Send + Sync>;
+
+/// Observers of one event type
+#[derive(Default)]
+struct EventObservers {
+    global: Vec<(ObserverId, ErasedObserver)>,
+    entities: HashMap<Entity, Vec<(ObserverId, ErasedObserver)>>,
+}
+
+/// Observers per event type
+#[derive(Default)]
+pub(crate) struct Observers {
+    next_id: u64,
+    events: HashMap<TypeId, EventObservers>,
+}
+
+impl Observers {
+    fn add(
+        &mut self,
+        type_id: TypeId,
+        entity: Option<Entity>,
+        observer: ErasedObserver,
+    ) -> ObserverId {
+        let id = ObserverId(self.next_id);
+        self.next_id += 1;
+
+        let observers = self.events.entry(type_id).or_default();
+        match entity {
+            Some(entity) => observers
+                .entities
+                .entry(entity)
+                .or_default()
+                .push((id, observer)),
+            None => observers.global.push((id, observer)),
+        }
+
+        id
+    }
+
+    fn remove(&mut self, id: ObserverId) -> bool {
+        for observers in self.events.values_mut() {
+            if let Some(index) = observers.global.iter().position(|(o, _)| *o == id) {
+                observers.global.remove(index);
+                return true;
+            }
+
+            for list in observers.entities.values_mut() {
+                if let Some(index) = list.iter().position(|(o, _)| *o == id) {
+                    list.remove(index);
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Drops the observers of a destroyed entity
+    pub(crate) fn remove_entity(&mut self, entity: Entity) {
+        for observers in self.events.values_mut() {
+            observers.entities.remove(&entity);
+        }
+    }
+
+    /// Entity observers first, then global ones, in registration order
+    fn collect(&self, type_id: TypeId, entity: Entity) -> Vec<ErasedObserver> {
+        let Some(observers) = self.events.get(&type_id) else {
+            return Vec::new();
+        };
+
+        observers
+            .entities
+            .get(&entity)
+            .into_iter()
+            .flatten()
+            .chain(&observers.global)
+            .map(|(_, observer)| observer.clone())
+            .collect()
+    }
+}
+
+fn erase<E, F>(observer: F) -> ErasedObserver
+where
+    E: EntityEvent,
+    F: Fn(&mut World, &mut Trigger<E>) + Send + Sync + 'static,
+{
+    Arc::new(move |world, trigger| {
+        if let Some(trigger) = trigger.downcast_mut::<Trigger<E>>() {
+            observer(world, trigger);
+        }
+    })
+}
+
+impl World {
+    /// Registers an observer running whenever `E` is triggered on any entity
+    ///
+    /// For propagating events it runs once per entity the event reaches.
+    pub fn observe<E: EntityEvent>(
+        &mut self,
+        observer: impl Fn(&mut World, &mut Trigger<E>) + Send + Sync + 'static,
+    ) -> ObserverId {
+        self.observers.add(TypeId::of::<E>(), None, erase(observer))
+    }
+
+    /// Registers an observer running whenever `E` reaches `entity`
+    ///
+    /// The observer is dropped when the entity is destroyed. Returns None,
+    /// registering nothing, if the entity is not alive.
+    pub fn observe_entity<E: EntityEvent>(
+        &mut self,
+        entity: Entity,
+        observer: impl Fn(&mut World, &mut Trigger<E>) + Send + Sync + 'static,
+    ) -> Option<ObserverId> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        Some(
+            self.observers
+                .add(TypeId::of::<E>(), Some(entity), erase(observer)),
+        )
+    }
+
+    /// Removes an observer, returns false if it was already removed
+    pub fn unobserve(&mut self, id: ObserverId) -> bool {
+        self.observers.remove(id)
+    }
+
+    /// Triggers an event on an entity
+    ///
+    /// Runs the observers of `entity` and the global observers of `E`
+    /// immediately. If the event propagates, the observers of each ancestor
+    /// run next. Returns the event, including changes made by observers.
+    /// Nothing runs if the entity is not alive.
+    pub fn trigger_on<E: EntityEvent>(&mut self, entity: Entity, event: E) -> E {
+        let type_id = TypeId::of::<E>();
+        let mut trigger = Trigger {
+            event,
+            origin: entity,
+            entity,
+            propagate: E::PROPAGATE,
+        };
+
+        while self.is_alive(trigger.entity) {
+            for observer in self.observers.collect(type_id, trigger.entity) {
+                observer(self, &mut trigger);
+            }
+
+            match self.parent(trigger.entity) {
+                Some(parent) if trigger.propagate => trigger.entity = parent,
+                _ => break,
+            }
+        }
+
+        trigger.event
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    struct Damage(i32);
+    impl EntityEvent for Damage {
+        const PROPAGATE: bool = true;
+    }
+
+    struct Clicked;
+    impl EntityEvent for Clicked {}
+
+    type Log = Arc<Mutex<Vec<Entity>>>;
+
+    #[test]
+    fn entity_and_global_observers_run() {
+        let mut world = World::new();
+        let a = world.spawn().insert(Health(10)).id();
+        let b = world.spawn().insert(Health(10)).id();
+
+        world.observe::<Damage>(|world, trigger| {
+            let amount = trigger.event().0;
+            world.get_mut::<Health>(trigger.entity()).unwrap().0 -= amount;
+        });
+        world.observe_entity::<Damage>(a, |_, trigger| trigger.event_mut().0 /= 2);
+
+        world.trigger_on(a, Damage(4));
+        world.trigger_on(b, Damage(4));
+
+        assert_eq!(world.get::<Health>(a), Some(&Health(8)));
+        assert_eq!(world.get::<Health>(b), Some(&Health(6)));
+    }
+
+    #[test]
+    fn propagating_events_bubble_to_ancestors() {
+        let mut world = World::new();
+        let root = world.spawn().id();
+        let arm = world.spawn().id();
+        let hand = world.spawn().id();
+        world.set_parent(arm, root);
+        world.set_parent(hand, arm);
+
+        let log = Log::default();
+        let seen = log.clone();
+        world.observe::<Damage>(move |_, trigger| {
+            assert_eq!(trigger.origin(), hand);
+            seen.lock().unwrap().push(trigger.entity());
+        });
+        let out = log.clone();
+        world.observe::<Clicked>(move |_, trigger| log.lock().unwrap().push(trigger.entity()));
+
+        world.trigger_on(hand, Damage(1));
+        world.trigger_on(hand, Clicked);
+
+        // Clicked doesn't propagate, so only the hand sees it
+        assert_eq!(*out.lock().unwrap(), vec![hand, arm, root, hand]);
+    }
+
+    #[test]
+    fn stop_propagation_and_returned_event() {
+        let mut world = World::new();
+        let parent = world.spawn().id();
+        let child = world.spawn().id();
+        world.set_parent(child, parent);
+
+        world.observe_entity::<Damage>(child, |_, trigger| {
+            trigger.event_mut().0 += 1;
+            trigger.stop_propagation();
+        });
+        world.observe_entity::<Damage>(parent, |_, trigger| trigger.event_mut().0 += 100);
+
+        assert_eq!(world.trigger_on(child, Damage(0)).0, 1);
+        assert_eq!(world.trigger_on(parent, Damage(0)).0, 100);
+    }
+
+    #[test]
+    fn observers_are_removed() {
+        let mut world = World::new();
+        let entity = world.spawn().id();
+
+        let id = world
+            .observe_entity::<Damage>(entity, |_, trigger| trigger.event_mut().0 = 1)
+            .unwrap();
+        assert!(world.unobserve(id));
+        assert!(!world.unobserve(id));
+        assert_eq!(world.trigger_on(entity, Damage(0)).0, 0);
+
+        world.observe_entity::<Damage>(entity, |_, trigger| trigger.event_mut().0 = 1);
+        world.destroy(entity);
+        assert!(
+            world
+                .observers
+                .collect(TypeId::of::<Damage>(), entity)
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn dead_entities_cannot_be_observed() {
+        let mut world = World::new();
+        let entity = world.spawn().id();
+        world.destroy(entity);
+
+        let id = world.observe_entity::<Damage>(entity, |_, trigger| trigger.event_mut().0 = 1);
+        assert!(id.is_none());
+        assert!(
+            world
+                .observers
+                .collect(TypeId::of::<Damage>(), entity)
+                .is_empty()
+        );
+    }
+}
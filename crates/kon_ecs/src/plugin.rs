@@ -1,13 +1,14 @@
 //! ECS Plugin for registering World as a global resource
 
-use crate::{ContextEcsExt, World};
+use crate::{World, Worlds};
 use kon_core::{App, Context, Plugin};
 
 /// ECS Plugin - registers World as a global resource
 ///
 /// This plugin:
 /// - Creates and registers `World` in Context
-/// - Adds `apply_deferred_system` to execute deferred operations each frame
+/// - Registers an empty `Worlds` resource for named Worlds
+/// - Adds `apply_deferred_system` to advance change ticks and execute deferred operations each frame
 ///
 /// Required for using `ctx.world()`.
 pub struct EcsPlugin;
@@ -15,14 +16,29 @@ pub struct EcsPlugin;
 impl Plugin for EcsPlugin {
     fn build(&self, app: &mut App) {
         app.register(World::new());
+        app.register(Worlds::new());
         app.add_sync_system(apply_deferred_system);
     }
 }
 
 /// System that applies deferred World operations each frame
 ///
-/// Operations deferred via `world.defer()` are executed here.
+/// Change trackers are cleared first, so components touched by deferred
+/// operations show up as added/changed during the next frame.
+/// Command buffers pushed via `world.push_commands()` and operations
+/// deferred via `world.defer()` are executed here, in submission order.
 /// This prevents issues with modifying the World during queries.
+/// Named Worlds in `Worlds` are processed the same way, each on its own.
 fn apply_deferred_system(ctx: &mut Context) {
-    ctx.world().apply_deferred();
+    if let Some(mut world) = ctx.global::<World>() {
+        world.clear_trackers();
+        world.apply_deferred();
+    }
+
+    if let Some(mut worlds) = ctx.global::<Worlds>() {
+        for (_, world) in worlds.iter_mut() {
+            world.clear_trackers();
+            world.apply_deferred();
+        }
+    }
 }
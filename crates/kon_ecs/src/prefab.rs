@@ -0,0 +1,447 @@
+//! Prefabs - spawnable entity templates
+//!
+//! A prefab describes the components, tags and children of an entity. It can
+//! extend a named prefab registered in the World (`base`), children may be
+//! prefabs themselves, and every instance can apply overrides on top.
+//! Component values use the names registered via `register_serializable`.
+//!
+//! # Example
+//! ```ignore
+//! // enemy.json
+//! // { "tags": ["enemy"],
+//! //   "components": { "Health": 100, "Speed": 2.0 },
+//! //   "children": [ { "base": "weapon" } ] }
+//! world.register_prefab("weapon", Prefab::load("prefabs/weapon.json")?);
+//! let enemy = Prefab::load("prefabs/enemy.json")?;
+//!
+//! let grunt = world.instantiate(&enemy)?;
+//!
+//! // Partial overrides are merged field by field
+//! let boss = world.instantiate_with(
+//!     &enemy,
+//!     &Prefab::from_json(r#"{ "tags": ["boss"], "components": { "Health": 500 } }"#)?,
+//! )?;
+//! ```
+
+use crate::World;
+use crate::entity::Entity;
+use crate::scene::{BoxedComponent, EntityMapper};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+
+/// Entity template with components, tags and children
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Prefab {
+    /// Name of a registered prefab this one extends
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub components: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Prefab>,
+}
+
+/// Errors produced while loading or instantiating a prefab
+#[derive(Debug)]
+pub enum PrefabError {
+    /// The prefab file could not be read
+    Io(std::io::Error),
+    /// The prefab text is not valid prefab JSON
+    Parse(serde_json::Error),
+    /// `base` names a prefab that is not registered
+    UnknownPrefab(String),
+    /// A chain of `base` references loops back on itself, or a child
+    /// extends a prefab it is part of
+    Cycle(String),
+    /// A component name is not registered via `register_serializable`
+    UnknownComponent(String),
+    /// A component value doesn't match its registered type
+    InvalidComponent {
+        name: String,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for PrefabError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PrefabError::Io(err) => write!(f, "failed to read prefab: {}", err),
+            PrefabError::Parse(err) => write!(f, "invalid prefab: {}", err),
+            PrefabError::UnknownPrefab(name) => write!(f, "unknown prefab '{}'", name),
+            PrefabError::Cycle(name) => write!(f, "prefab '{}' extends itself", name),
+            PrefabError::UnknownComponent(name) => write!(f, "unknown component '{}'", name),
+            PrefabError::InvalidComponent { name, source } => {
+                write!(f, "invalid component '{}': {}", name, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for PrefabError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PrefabError::Io(err) => Some(err),
+            PrefabError::Parse(err) | PrefabError::InvalidComponent { source: err, .. } => {
+                Some(err)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Prefab {
+    /// Creates an empty prefab
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a prefab from JSON
+    pub fn from_json(json: &str) -> Result<Self, PrefabError> {
+        serde_json::from_str(json).map_err(PrefabError::Parse)
+    }
+
+    /// Reads and parses a prefab JSON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PrefabError> {
+        let json = std::fs::read_to_string(path).map_err(PrefabError::Io)?;
+        Self::from_json(&json)
+    }
+
+    /// Serializes the prefab to pretty-printed JSON
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("prefab values are valid JSON")
+    }
+
+    /// Returns a copy of this prefab with `overrides` applied on top
+    ///
+    /// - Component objects are merged field by field, other values replaced
+    /// - Tags are added
+    /// - Children are appended
+    /// - A `base` in `overrides` replaces this prefab's base
+    pub fn merged(&self, overrides: &Prefab) -> Prefab {
+        let mut merged = self.clone();
+
+        if overrides.base.is_some() {
+            merged.base = overrides.base.clone();
+        }
+
+        for tag in &overrides.tags {
+            if !merged.tags.contains(tag) {
+                merged.tags.push(tag.clone());
+            }
+        }
+
+        for (name, value) in &overrides.components {
+            match merged.components.get_mut(name) {
+                Some(existing) => merge_value(existing, value),
+                None => {
+                    merged.components.insert(name.clone(), value.clone());
+                }
+            }
+        }
+
+        merged.children.extend(overrides.children.iter().cloned());
+        merged
+    }
+}
+
+/// Recursively merges JSON objects, replacing any other value
+fn merge_value(target: &mut Value, overrides: &Value) {
+    match (target, overrides) {
+        (Value::Object(target), Value::Object(overrides)) => {
+            for (key, value) in overrides {
+                match target.get_mut(key) {
+                    Some(existing) => merge_value(existing, value),
+                    None => {
+                        target.insert(key.clone(), value.clone());
+                    }
+                }
+            }
+        }
+        (target, overrides) => *target = overrides.clone(),
+    }
+}
+
+type InsertFn = fn(&mut World, Entity, BoxedComponent, &EntityMapper);
+
+/// Prefab with bases resolved and components decoded, ready to spawn
+struct ResolvedPrefab {
+    tags: Vec<String>,
+    components: Vec<(InsertFn, BoxedComponent)>,
+    children: Vec<ResolvedPrefab>,
+}
+
+impl World {
+    /// Registers a prefab under a name so other prefabs can extend it
+    pub fn register_prefab(&mut self, name: &str, prefab: Prefab) {
+        self.prefabs.insert(name.to_string(), prefab);
+    }
+
+    /// Returns a registered prefab
+    pub fn prefab(&self, name: &str) -> Option<&Prefab> {
+        self.prefabs.get(name)
+    }
+
+    /// Spawns an entity (with children) from a prefab and returns the root
+    ///
+    /// The prefab is fully resolved and validated first, so on error
+    /// nothing is spawned.
+    pub fn instantiate(&mut self, prefab: &Prefab) -> Result<Entity, PrefabError> {
+        let resolved = self.resolve_prefab(prefab, &mut Vec::new())?;
+        Ok(self.spawn_resolved(resolved))
+    }
+
+    /// Spawns an entity from a prefab with per-instance overrides
+    ///
+    /// See `Prefab::merged` for how overrides are applied.
+    pub fn instantiate_with(
+        &mut self,
+        prefab: &Prefab,
+        overrides: &Prefab,
+    ) -> Result<Entity, PrefabError> {
+        self.instantiate(&prefab.merged(overrides))
+    }
+
+    /// Spawns an entity from a registered prefab
+    pub fn instantiate_named(&mut self, name: &str) -> Result<Entity, PrefabError> {
+        let prefab = self
+            .prefabs
+            .get(name)
+            .cloned()
+            .ok_or_else(|| PrefabError::UnknownPrefab(name.to_string()))?;
+        let resolved = self.resolve_prefab(&prefab, &mut vec![name.to_string()])?;
+        Ok(self.spawn_resolved(resolved))
+    }
+
+    /// Flattens `base` chains and decodes component values
+    ///
+    /// `stack` holds the prefab names being resolved, from the root down.
+    fn resolve_prefab(
+        &self,
+        prefab: &Prefab,
+        stack: &mut Vec<String>,
+    ) -> Result<ResolvedPrefab, PrefabError> {
+        let depth = stack.len();
+        let flat = self.flatten_prefab(prefab, stack)?;
+
+        // Keep the bases that contribute children while resolving them; a
+        // child extending one of those would expand forever
+        let chain = stack.split_off(depth);
+        stack.extend(chain.into_iter().filter(|name| {
+            self.prefabs
+                .get(name)
+                .is_some_and(|base| !base.children.is_empty())
+        }));
+
+        let mut components = Vec::with_capacity(flat.components.len());
+        for (name, value) in flat.components {
+            let (_, serde) = self
+                .scene_registry
+                .components
+                .get(&name)
+                .ok_or_else(|| PrefabError::UnknownComponent(name.clone()))?;
+
+            let component = (serde.deserialize)(value)
+                .map_err(|source| PrefabError::InvalidComponent { name, source })?;
+            components.push((serde.insert, component));
+        }
+
+        let children = flat
+            .children
+            .iter()
+            .map(|child| self.resolve_prefab(child, stack))
+            .collect::<Result<_, _>>()?;
+        stack.truncate(depth);
+
+        Ok(ResolvedPrefab {
+            tags: flat.tags,
+            components,
+            children,
+        })
+    }
+
+    /// Applies a prefab on top of its (recursively flattened) base
+    ///
+    /// Pushes the names of all bases onto `stack`.
+    fn flatten_prefab(
+        &self,
+        prefab: &Prefab,
+        stack: &mut Vec<String>,
+    ) -> Result<Prefab, PrefabError> {
+        let Some(base_name) = &prefab.base else {
+            return Ok(prefab.clone());
+        };
+
+        if stack.contains(base_name) {
+            return Err(PrefabError::Cycle(base_name.clone()));
+        }
+
+        let base = self
+            .prefabs
+            .get(base_name)
+            .ok_or_else(|| PrefabError::UnknownPrefab(base_name.clone()))?;
+
+        stack.push(base_name.clone());
+        let flat_base = self.flatten_prefab(base, stack)?;
+
+        let own = Prefab {
+            base: None,
+            ..prefab.clone()
+        };
+        Ok(flat_base.merged(&own))
+    }
+
+    fn spawn_resolved(&mut self, prefab: ResolvedPrefab) -> Entity {
+        let entity = self.spawn().id();
+        let mapper = EntityMapper::identity();
+
+        for tag in &prefab.tags {
+            self.tag(entity, tag);
+        }
+
+        for (insert, component) in prefab.components {
+            insert(self, entity, component, &mapper);
+        }
+
+        for child in prefab.children {
+            let child = self.spawn_resolved(child);
+            self.set_parent(child, entity);
+        }
+
+        entity
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    fn registered_world() -> World {
+        let mut world = World::new();
+        world.register_serializable::<Health>("Health");
+        world.register_serializable::<Position>("Position");
+        world
+    }
+
+    const ENEMY: &str = r#"{
+        "tags": ["enemy"],
+        "components": { "Health": 100, "Position": { "x": 0.0, "y": 0.0 } },
+        "children": [ { "base": "weapon" } ]
+    }"#;
+
+    #[test]
+    fn instantiate_spawns_tree() {
+        let mut world = registered_world();
+        world.register_prefab(
+            "weapon",
+            Prefab::from_json(r#"{ "tags": ["weapon"], "components": { "Health": 5 } }"#).unwrap(),
+        );
+
+        let enemy = world
+            .instantiate(&Prefab::from_json(ENEMY).unwrap())
+            .unwrap();
+
+        assert_eq!(world.get::<Health>(enemy), Some(&Health(100)));
+        assert!(world.has_tag(enemy, "enemy"));
+
+        let weapon = world.children(enemy)[0];
+        assert_eq!(world.get::<Health>(weapon), Some(&Health(5)));
+        assert!(world.has_tag(weapon, "weapon"));
+    }
+
+    #[test]
+    fn overrides_merge_fields() {
+        let mut world = registered_world();
+        world.register_prefab("weapon", Prefab::new());
+        let enemy = Prefab::from_json(ENEMY).unwrap();
+        let overrides = Prefab::from_json(
+            r#"{ "tags": ["boss"], "components": { "Position": { "x": 5.0 } } }"#,
+        )
+        .unwrap();
+
+        let boss = world.instantiate_with(&enemy, &overrides).unwrap();
+
+        assert_eq!(
+            world.get::<Position>(boss),
+            Some(&Position { x: 5.0, y: 0.0 })
+        );
+        assert!(world.has_tag(boss, "enemy"));
+        assert!(world.has_tag(boss, "boss"));
+    }
+
+    #[test]
+    fn base_prefab_is_extended() {
+        let mut world = registered_world();
+        world.register_prefab(
+            "unit",
+            Prefab::from_json(r#"{ "components": { "Health": 10 } }"#).unwrap(),
+        );
+        world.register_prefab(
+            "tank",
+            Prefab::from_json(r#"{ "base": "unit", "components": { "Health": 80 } }"#).unwrap(),
+        );
+
+        let tank = world.instantiate_named("tank").unwrap();
+        assert_eq!(world.get::<Health>(tank), Some(&Health(80)));
+    }
+
+    #[test]
+    fn cycles_and_unknown_names_are_errors() {
+        let mut world = registered_world();
+        world.register_prefab("a", Prefab::from_json(r#"{ "base": "b" }"#).unwrap());
+        world.register_prefab("b", Prefab::from_json(r#"{ "base": "a" }"#).unwrap());
+
+        assert!(matches!(
+            world.instantiate_named("a"),
+            Err(PrefabError::Cycle(_))
+        ));
+        assert!(matches!(
+            world.instantiate_named("missing"),
+            Err(PrefabError::UnknownPrefab(_))
+        ));
+
+        // A child extending its own parent prefab
+        let tree = r#"{ "children": [{ "base": "tree" }] }"#;
+        world.register_prefab("tree", Prefab::from_json(tree).unwrap());
+        assert!(matches!(
+            world.instantiate_named("tree"),
+            Err(PrefabError::Cycle(_))
+        ));
+        world.register_prefab(
+            "forest",
+            Prefab::from_json(r#"{ "base": "tree" }"#).unwrap(),
+        );
+        assert!(matches!(
+            world.instantiate_named("forest"),
+            Err(PrefabError::Cycle(_))
+        ));
+
+        // Parent and child sharing a childless base is fine
+        world.register_prefab("unit", Prefab::new());
+        let squad = r#"{ "base": "unit", "children": [{ "base": "unit" }] }"#;
+        world.register_prefab("squad", Prefab::from_json(squad).unwrap());
+        let squad = world.instantiate_named("squad").unwrap();
+        assert_eq!(world.children(squad).len(), 1);
+        world.destroy_recursive(squad);
+
+        let unknown = Prefab::from_json(r#"{ "components": { "Mana": 3 } }"#).unwrap();
+        assert!(matches!(
+            world.instantiate(&unknown),
+            Err(PrefabError::UnknownComponent(_))
+        ));
+        assert_eq!(world.entity_count(), 0);
+    }
+}
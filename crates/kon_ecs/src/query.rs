@@ -26,9 +26,12 @@
 //! ```
 
 use crate::entity::Entity;
+use crate::relation::Relation;
 use crate::storage::SparseSet;
+use crate::tag::{TagMask, TagRef};
 use crate::{Component, World};
-use std::any::TypeId;
+use std::any::{Any, TypeId};
+use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::marker::PhantomData;
 
@@ -38,20 +41,29 @@ use std::marker::PhantomData;
 
 /// Filter configuration for entity queries
 ///
-/// Uses bitmasks for O(1) tag filtering and TypeId lists for component filtering.
+/// Uses tag bitsets for O(1) tag filtering and TypeId lists for component filtering.
 #[derive(Default, Clone)]
 pub struct QueryFilter {
-    /// Bitmask of required tags (entity must have ALL)
-    required_mask: u128,
-    /// Bitmask of excluded tags (entity must have NONE)
-    excluded_mask: u128,
+    /// Bitset of required tags (entity must have ALL)
+    pub(crate) required_mask: TagMask,
+    /// Bitset of excluded tags (entity must have NONE)
+    pub(crate) excluded_mask: TagMask,
     /// Component types entity must have (in addition to queried components)
-    required_components: Vec<TypeId>,
+    pub(crate) required_components: Vec<TypeId>,
     /// Component types entity must NOT have
-    excluded_components: Vec<TypeId>,
+    pub(crate) excluded_components: Vec<TypeId>,
+    /// Component types that must have been added since `last_run`
+    pub(crate) added_components: Vec<TypeId>,
+    /// Component types that must have been changed since `last_run`
+    pub(crate) changed_components: Vec<TypeId>,
+    /// Tick that added/changed components are compared against,
+    /// `None` for the World's last `clear_trackers()`
+    pub(crate) last_run: Option<u32>,
+    /// Relations (type, target) the entity must be a source of
+    relations: Vec<(TypeId, Entity)>,
     /// Set to true if query requires a tag that was never registered.
     /// Optimization: immediately returns no results without checking entities.
-    impossible: bool,
+    pub(crate) impossible: bool,
 }
 
 impl QueryFilter {
@@ -67,6 +79,8 @@ impl QueryFilter {
     /// - Entity has excluded tag bits
     /// - Entity missing required components
     /// - Entity has excluded components
+    /// - Entity is not related to a required relation target
+    /// - Entity's tracked components were not added/changed since the last run
     #[inline(always)]
     pub fn matches(&self, world: &World, entity: Entity) -> bool {
         if self.impossible {
@@ -74,11 +88,11 @@ impl QueryFilter {
         }
 
         let entity_mask = world.get_tag_mask(entity.id());
-        if (entity_mask & self.required_mask) != self.required_mask {
+        if !entity_mask.contains_all(&self.required_mask) {
             return false;
         }
 
-        if (entity_mask & self.excluded_mask) != 0 {
+        if entity_mask.intersects(&self.excluded_mask) {
             return false;
         }
 
@@ -94,10 +108,90 @@ impl QueryFilter {
             }
         }
 
+        for (type_id, target) in &self.relations {
+            if !world.has_relation_by_type_id(type_id, entity, *target) {
+                return false;
+            }
+        }
+
+        let last_run = self.last_run.unwrap_or_else(|| world.last_change_tick());
+        let this_run = world.change_tick();
+
+        for type_id in &self.added_components {
+            if !world
+                .ticks_by_type_id(entity, type_id)
+                .is_some_and(|t| t.is_added(last_run, this_run))
+            {
+                return false;
+            }
+        }
+
+        for type_id in &self.changed_components {
+            if !world
+                .ticks_by_type_id(entity, type_id)
+                .is_some_and(|t| t.is_changed(last_run, this_run))
+            {
+                return false;
+            }
+        }
+
         true
     }
 }
 
+/// Type-level query filter
+///
+/// Implemented by filter markers such as `Added<C>` and `Changed<C>`.
+/// Used with `Query::filter::<F>()`.
+pub trait FilterParam {
+    fn apply(filter: &mut QueryFilter);
+}
+
+/// Query filter matching entities that have component `C`
+///
+/// Type-level form of `Query::with`, mainly for `QueryState<T, F>`.
+pub struct With<C>(PhantomData<C>);
+
+/// Query filter matching entities that don't have component `C`
+///
+/// Type-level form of `Query::without`, mainly for `QueryState<T, F>`.
+pub struct Without<C>(PhantomData<C>);
+
+impl<C: Component> FilterParam for With<C> {
+    fn apply(filter: &mut QueryFilter) {
+        filter.required_components.push(TypeId::of::<C>());
+    }
+}
+
+impl<C: Component> FilterParam for Without<C> {
+    fn apply(filter: &mut QueryFilter) {
+        filter.excluded_components.push(TypeId::of::<C>());
+    }
+}
+
+/// No filter
+impl FilterParam for () {
+    fn apply(_: &mut QueryFilter) {}
+}
+
+// Tuples of filters apply every element, e.g. `(With<A>, Without<B>)`
+macro_rules! impl_filter_tuple {
+    ($($name:ident),+) => {
+        impl<$($name: FilterParam),+> FilterParam for ($($name,)+) {
+            fn apply(filter: &mut QueryFilter) {
+                $($name::apply(filter);)+
+            }
+        }
+    };
+}
+
+impl_filter_tuple!(A);
+impl_filter_tuple!(A, B);
+impl_filter_tuple!(A, B, C);
+impl_filter_tuple!(A, B, C, D);
+impl_filter_tuple!(A, B, C, D, E);
+impl_filter_tuple!(A, B, C, D, E, F);
+
 // ============================================================================
 // Fetch Trait - How to fetch a single component
 // ============================================================================
@@ -124,6 +218,7 @@ pub trait FetchMut<'w> {
     type State;
 
     fn init(world: &'w mut World) -> Option<Self::State>;
+    fn contains(state: &Self::State, entity_id: u32) -> bool;
     fn fetch(state: &mut Self::State, entity_id: u32) -> Option<Self::Item>;
     fn type_id() -> TypeId;
 }
@@ -150,18 +245,25 @@ impl<'w, T: Component> Fetch<'w> for T {
 
 impl<'w, T: Component> FetchMut<'w> for T {
     type Item = &'w mut T;
-    type State = *mut SparseSet<T>;
+    /// Storage pointer and the tick used to mark fetched components as changed
+    type State = (*mut SparseSet<T>, u32);
 
     fn init(world: &'w mut World) -> Option<Self::State> {
+        let tick = world.change_tick();
         world
             .components_mut()
             .get_mut(&TypeId::of::<T>())
             .and_then(|s| s.as_any_mut().downcast_mut::<SparseSet<T>>())
-            .map(|s| s as *mut SparseSet<T>)
+            .map(|s| (s as *mut SparseSet<T>, tick))
+    }
+
+    fn contains(state: &Self::State, entity_id: u32) -> bool {
+        unsafe { (*state.0).contains(entity_id) }
     }
 
-    fn fetch(storage: &mut Self::State, entity_id: u32) -> Option<Self::Item> {
-        unsafe { (*(*storage)).get_mut(entity_id) }
+    fn fetch(state: &mut Self::State, entity_id: u32) -> Option<Self::Item> {
+        let (storage, tick) = *state;
+        unsafe { (*storage).get_mut_tracked(entity_id, tick) }
     }
 
     fn type_id() -> TypeId {
@@ -278,6 +380,11 @@ macro_rules! impl_query_tuple {
                 #[allow(non_snake_case)]
                 let (first_state, $($rest),*) = state;
 
+                // Check first so a partial match doesn't mark anything changed
+                if !($first::contains(first_state, entity_id) $(&& $rest::contains($rest, entity_id))*) {
+                    return None;
+                }
+
                 Some((
                     $first::fetch(first_state, entity_id)?,
                     $($rest::fetch($rest, entity_id)?),*
@@ -317,7 +424,7 @@ impl_query_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
 /// Example: `select::<(Health, Health>()` will panic.
 /// This prevents undefined behavior from aliasing mutable references.
 #[track_caller]
-fn check_duplicate_types(type_ids: &[TypeId]) {
+pub(crate) fn check_duplicate_types(type_ids: &[TypeId]) {
     let unique: HashSet<_> = type_ids.iter().collect();
     if unique.len() != type_ids.len() {
         panic!("Duplicate component types in query");
@@ -357,10 +464,10 @@ impl<'w, T: QueryTuple<'w>> Query<'w, T> {
         }
     }
 
-    /// Require entities to have this tag
-    pub fn tagged(mut self, tag: &str) -> Self {
-        if let Some(tag_id) = self.world.get_tag_id(tag) {
-            self.filter.required_mask |= 1 << tag_id
+    /// Require entities to have this tag (name or `Tag` handle)
+    pub fn tagged<'t>(mut self, tag: impl Into<TagRef<'t>>) -> Self {
+        if let Some(tag_id) = self.world.get_tag_id(tag.into()) {
+            self.filter.required_mask.insert(tag_id);
         } else {
             self.filter.impossible = true
         }
@@ -368,10 +475,10 @@ impl<'w, T: QueryTuple<'w>> Query<'w, T> {
         self
     }
 
-    /// Exclude entities with this tag
-    pub fn not_tagged(mut self, tag: &str) -> Self {
-        if let Some(tag_id) = self.world.get_tag_id(tag) {
-            self.filter.excluded_mask |= 1 << tag_id
+    /// Exclude entities with this tag (name or `Tag` handle)
+    pub fn not_tagged<'t>(mut self, tag: impl Into<TagRef<'t>>) -> Self {
+        if let Some(tag_id) = self.world.get_tag_id(tag.into()) {
+            self.filter.excluded_mask.insert(tag_id);
         }
 
         self
@@ -411,6 +518,52 @@ impl<'w, T: QueryTuple<'w>> Query<'w, T> {
         self
     }
 
+    /// Filters entities whose component was added this frame
+    ///
+    /// Shorthand for `.filter::<Added<C>>()`.
+    pub fn added<C: Component>(mut self) -> Self {
+        self.filter.added_components.push(TypeId::of::<C>());
+        self
+    }
+
+    /// Filters entities whose component was changed this frame
+    ///
+    /// Shorthand for `.filter::<Changed<C>>()`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.select::<(Position,)>()
+    ///     .changed::<Position>()
+    ///     .each(|entity, (pos,)| {
+    ///         println!("{} moved", entity);
+    ///     });
+    /// ```
+    pub fn changed<C: Component>(mut self) -> Self {
+        self.filter.changed_components.push(TypeId::of::<C>());
+        self
+    }
+
+    /// Applies a type-level filter such as `Added<C>` or `Changed<C>`
+    pub fn filter<F: FilterParam>(mut self) -> Self {
+        F::apply(&mut self.filter);
+        self
+    }
+
+    /// Filters entities related to `target` with relation `R`
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.select::<(Position,)>()
+    ///     .related_to::<OwnedBy>(player)
+    ///     .each(|item, (pos,)| {
+    ///         println!("{} at {:?}", item, pos);
+    ///     });
+    /// ```
+    pub fn related_to<R: Relation>(mut self, target: Entity) -> Self {
+        self.filter.relations.push((TypeId::of::<R>(), target));
+        self
+    }
+
     /// Iterate over all matching entities
     #[inline(always)]
     pub fn each<F>(self, mut f: F)
@@ -440,6 +593,72 @@ impl<'w, T: QueryTuple<'w>> Query<'w, T> {
             }
         }
     }
+
+    /// Collects all matching entities sorted by `compare`
+    ///
+    /// The sort is stable, so equal items keep storage order.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let order = world.select::<(Initiative,)>()
+    ///     .sorted_by(|(_, (a,)), (_, (b,))| b.0.cmp(&a.0));
+    ///
+    /// for (entity, _) in order {
+    ///     take_turn(entity);
+    /// }
+    /// ```
+    pub fn sorted_by<F>(self, mut compare: F) -> Vec<(Entity, T::Item)>
+    where
+        F: FnMut(&(Entity, T::Item), &(Entity, T::Item)) -> Ordering,
+    {
+        let mut items = Vec::new();
+        self.each(|entity, components| items.push((entity, components)));
+        items.sort_by(&mut compare);
+        items
+    }
+
+    /// Collects all matching entities sorted by a key
+    ///
+    /// # Example
+    /// ```ignore
+    /// for (entity, (sprite,)) in world.select::<(Sprite,)>()
+    ///     .sorted_by_key(|(_, (sprite,))| sprite.layer)
+    /// {
+    ///     draw(sprite);
+    /// }
+    /// ```
+    pub fn sorted_by_key<K, F>(self, mut key: F) -> Vec<(Entity, T::Item)>
+    where
+        K: Ord,
+        F: FnMut(&(Entity, T::Item)) -> K,
+    {
+        self.sorted_by(|a, b| key(a).cmp(&key(b)))
+    }
+
+    /// Iterate over all matching entities, passing a World resource along
+    ///
+    /// Does nothing if the World has no resource of type `R`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.select::<(Position,)>()
+    ///     .each_with_resource(|entity, (pos,), bounds: &MapBounds| {
+    ///         if !bounds.contains(pos) {
+    ///             println!("{} left the map", entity);
+    ///         }
+    ///     });
+    /// ```
+    pub fn each_with_resource<R, F>(self, mut f: F)
+    where
+        R: Any + Send + Sync,
+        F: FnMut(Entity, T::Item, &R),
+    {
+        let Some(resource) = self.world.resource::<R>() else {
+            return;
+        };
+
+        self.each(|entity, components| f(entity, components, resource));
+    }
 }
 
 // ============================================================================
@@ -474,10 +693,10 @@ impl<'w, T: QueryTupleMut<'w>> QueryMut<'w, T> {
         }
     }
 
-    /// Require entities to have this tag
-    pub fn tagged(mut self, tag: &str) -> Self {
-        if let Some(tag_id) = self.world.get_tag_id(tag) {
-            self.filter.required_mask |= 1 << tag_id
+    /// Require entities to have this tag (name or `Tag` handle)
+    pub fn tagged<'t>(mut self, tag: impl Into<TagRef<'t>>) -> Self {
+        if let Some(tag_id) = self.world.get_tag_id(tag.into()) {
+            self.filter.required_mask.insert(tag_id);
         } else {
             self.filter.impossible = true
         }
@@ -485,10 +704,10 @@ impl<'w, T: QueryTupleMut<'w>> QueryMut<'w, T> {
         self
     }
 
-    /// Exclude entities with this tag
-    pub fn not_tagged(mut self, tag: &str) -> Self {
-        if let Some(tag_id) = self.world.get_tag_id(tag) {
-            self.filter.excluded_mask |= 1 << tag_id
+    /// Exclude entities with this tag (name or `Tag` handle)
+    pub fn not_tagged<'t>(mut self, tag: impl Into<TagRef<'t>>) -> Self {
+        if let Some(tag_id) = self.world.get_tag_id(tag.into()) {
+            self.filter.excluded_mask.insert(tag_id);
         }
 
         self
@@ -528,6 +747,52 @@ impl<'w, T: QueryTupleMut<'w>> QueryMut<'w, T> {
         self
     }
 
+    /// Filters entities whose component was added this frame
+    ///
+    /// Shorthand for `.filter::<Added<C>>()`.
+    pub fn added<C: Component>(mut self) -> Self {
+        self.filter.added_components.push(TypeId::of::<C>());
+        self
+    }
+
+    /// Filters entities whose component was changed this frame
+    ///
+    /// Shorthand for `.filter::<Changed<C>>()`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.select::<(Position,)>()
+    ///     .changed::<Position>()
+    ///     .each(|entity, (pos,)| {
+    ///         println!("{} moved", entity);
+    ///     });
+    /// ```
+    pub fn changed<C: Component>(mut self) -> Self {
+        self.filter.changed_components.push(TypeId::of::<C>());
+        self
+    }
+
+    /// Applies a type-level filter such as `Added<C>` or `Changed<C>`
+    pub fn filter<F: FilterParam>(mut self) -> Self {
+        F::apply(&mut self.filter);
+        self
+    }
+
+    /// Filters entities related to `target` with relation `R`
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.select::<(Position,)>()
+    ///     .related_to::<OwnedBy>(player)
+    ///     .each(|item, (pos,)| {
+    ///         println!("{} at {:?}", item, pos);
+    ///     });
+    /// ```
+    pub fn related_to<R: Relation>(mut self, target: Entity) -> Self {
+        self.filter.relations.push((TypeId::of::<R>(), target));
+        self
+    }
+
     /// Iterate over all matching entities
     #[inline(always)]
     pub fn each<F>(self, mut f: F)
@@ -565,11 +830,78 @@ impl<'w, T: QueryTupleMut<'w>> QueryMut<'w, T> {
             }
         }
     }
+
+    /// Collects all matching entities sorted by `compare`
+    ///
+    /// The sort is stable, so equal items keep storage order.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let order = world.select_mut::<(Initiative,)>()
+    ///     .sorted_by(|(_, (a,)), (_, (b,))| b.0.cmp(&a.0));
+    ///
+    /// for (entity, _) in order {
+    ///     take_turn(entity);
+    /// }
+    /// ```
+    pub fn sorted_by<F>(self, mut compare: F) -> Vec<(Entity, T::Item)>
+    where
+        F: FnMut(&(Entity, T::Item), &(Entity, T::Item)) -> Ordering,
+    {
+        let mut items = Vec::new();
+        self.each(|entity, components| items.push((entity, components)));
+        items.sort_by(&mut compare);
+        items
+    }
+
+    /// Collects all matching entities sorted by a key
+    ///
+    /// # Example
+    /// ```ignore
+    /// for (entity, (sprite,)) in world.select_mut::<(Sprite,)>()
+    ///     .sorted_by_key(|(_, (sprite,))| sprite.layer)
+    /// {
+    ///     draw(sprite);
+    /// }
+    /// ```
+    pub fn sorted_by_key<K, F>(self, mut key: F) -> Vec<(Entity, T::Item)>
+    where
+        K: Ord,
+        F: FnMut(&(Entity, T::Item)) -> K,
+    {
+        self.sorted_by(|a, b| key(a).cmp(&key(b)))
+    }
+
+    /// Iterate over all matching entities, passing a World resource along
+    ///
+    /// Does nothing if the World has no resource of type `R`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.select_mut::<(Velocity,)>()
+    ///     .each_with_resource(|_, (vel,), gravity: &Gravity| {
+    ///         vel.y -= gravity.0;
+    ///     });
+    /// ```
+    pub fn each_with_resource<R, F>(self, mut f: F)
+    where
+        R: Any + Send + Sync,
+        F: FnMut(Entity, T::Item, &R),
+    {
+        // Resources are stored apart from component storages, so the
+        // resource is never aliased by the mutable component borrows
+        let resource: *const R = match self.world.resource::<R>() {
+            Some(resource) => resource,
+            None => return,
+        };
+
+        self.each(|entity, components| f(entity, components, unsafe { &*resource }));
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::World;
+    use crate::{Added, Changed, World};
 
     #[derive(Debug, Clone, PartialEq)]
     struct Health(i32);
@@ -713,6 +1045,31 @@ mod tests {
         assert_eq!(entity_count, 0);
     }
 
+    #[test]
+    fn query_tag_handle_past_inline_bits() {
+        let mut world = World::new();
+        for i in 0..200 {
+            world.register_tag(&format!("filler_{}", i));
+        }
+        let boss = world.register_tag("boss");
+
+        world.spawn().insert(Health(100)).tag(boss);
+        world.spawn().insert(Health(50)).tag(boss).tag("frozen");
+        world.spawn().insert(Health(10));
+
+        let mut entity_count = 0;
+        world
+            .select::<(Health,)>()
+            .tagged(boss)
+            .not_tagged("frozen")
+            .each(|_, _| {
+                entity_count += 1;
+            });
+
+        assert!(boss.slot() >= 128);
+        assert_eq!(entity_count, 1);
+    }
+
     #[test]
     fn query_with_component() {
         let mut world = World::new();
@@ -859,6 +1216,147 @@ mod tests {
         assert_eq!(entity_count, 0);
     }
 
+    #[test]
+    fn query_added_filter() {
+        let mut world = World::new();
+        world.spawn().insert(Health(100));
+        world.clear_trackers();
+        let e = world.spawn().insert(Health(50)).id();
+
+        let mut found = Vec::new();
+        world
+            .select::<(Health,)>()
+            .filter::<Added<Health>>()
+            .each(|entity, _| found.push(entity));
+
+        assert_eq!(found, vec![e]);
+    }
+
+    #[test]
+    fn query_mut_marks_changed() {
+        let mut world = World::new();
+        let e = world
+            .spawn()
+            .insert(Health(100))
+            .insert(Position { x: 0.0, y: 0.0 })
+            .id();
+        world.spawn().insert(Position { x: 1.0, y: 1.0 });
+        world.clear_trackers();
+
+        world.select_mut::<(Health,)>().each(|_, (health,)| {
+            health.0 -= 1;
+        });
+
+        let mut changed = Vec::new();
+        world
+            .select::<(Health,)>()
+            .filter::<Changed<Health>>()
+            .each(|entity, _| changed.push(entity));
+        assert_eq!(changed, vec![e]);
+
+        let mut moved = 0;
+        world
+            .select::<(Position,)>()
+            .changed::<Position>()
+            .each(|_, _| moved += 1);
+        assert_eq!(moved, 0);
+    }
+
+    #[test]
+    fn partial_matches_are_not_marked_changed() {
+        let mut world = World::new();
+        let lone = world.spawn().insert(Position { x: 0.0, y: 0.0 }).id();
+        world
+            .spawn()
+            .insert(Position { x: 1.0, y: 1.0 })
+            .insert(Velocity { x: 1.0, y: 0.0 });
+        world.clear_trackers();
+
+        world.select_mut::<(Position, Velocity)>().each(|_, _| {});
+        let sorted = world
+            .select_mut::<(Position, Velocity)>()
+            .sorted_by_key(|(entity, _)| entity.id());
+        assert_eq!(sorted.len(), 1);
+
+        let mut changed = Vec::new();
+        world
+            .select::<(Position,)>()
+            .changed::<Position>()
+            .each(|entity, _| changed.push(entity));
+        assert!(!changed.contains(&lone));
+    }
+
+    #[test]
+    fn query_sorted_by_key() {
+        let mut world = World::new();
+        for hp in [30, 10, 20] {
+            world.spawn().insert(Health(hp));
+        }
+
+        let order: Vec<i32> = world
+            .select::<(Health,)>()
+            .sorted_by_key(|(_, (hp,))| hp.0)
+            .into_iter()
+            .map(|(_, (hp,))| hp.0)
+            .collect();
+        assert_eq!(order, vec![10, 20, 30]);
+
+        let mut sorted = world
+            .select_mut::<(Health,)>()
+            .sorted_by(|(_, (a,)), (_, (b,))| b.0.cmp(&a.0));
+        sorted[0].1.0.0 += 1;
+        assert_eq!(
+            sorted.iter().map(|(_, (hp,))| hp.0).collect::<Vec<_>>(),
+            vec![31, 20, 10]
+        );
+    }
+
+    #[test]
+    fn query_follows_sorted_storage() {
+        let mut world = World::new();
+        let mut spawned = Vec::new();
+        for hp in [3, 1, 2] {
+            spawned.push(world.spawn().insert(Health(hp)).id());
+        }
+        world.clear_trackers();
+
+        world.sort_components_by_key::<Health, _>(|hp| hp.0);
+
+        let mut order = Vec::new();
+        world
+            .select::<(Health,)>()
+            .each(|entity, _| order.push(entity));
+        assert_eq!(order, vec![spawned[1], spawned[2], spawned[0]]);
+        assert!(!world.is_changed::<Health>(spawned[0]));
+    }
+
+    #[test]
+    fn query_reads_resource() {
+        struct Gravity(f32);
+
+        let mut world = World::new();
+        let entity = world.spawn().insert(Velocity { x: 0.0, y: 0.0 }).id();
+
+        let mut visited = 0;
+        world
+            .select_mut::<(Velocity,)>()
+            .each_with_resource(|_, _, _: &Gravity| visited += 1);
+        assert_eq!(visited, 0);
+
+        world.insert_resource(Gravity(9.8));
+        world
+            .select_mut::<(Velocity,)>()
+            .each_with_resource(|_, (vel,), gravity: &Gravity| vel.y -= gravity.0);
+
+        let mut fallen = Vec::new();
+        world
+            .select::<(Velocity,)>()
+            .each_with_resource(|entity, (vel,), gravity: &Gravity| {
+                fallen.push((entity, vel.y == -gravity.0));
+            });
+        assert_eq!(fallen, vec![(entity, true)]);
+    }
+
     #[test]
     #[should_panic]
     fn query_duplicate_component_type() {
@@ -0,0 +1,520 @@
+//! Cached query state for queries that run every frame
+//!
+//! `world.select::<T>()` checks for duplicate types, resolves tag names and
+//! builds its filter on each call. `QueryState` does that work once and
+//! keeps the result until the World layout changes, i.e. until a new
+//! component type gets a storage or a tag is registered or unregistered.
+//! Each call then only looks up the storages by type.
+//!
+//! Like `world.select()`, iteration follows the storage of the first
+//! component, so an order set by `sort_components_by` on it is kept. Put
+//! the rarest component first when order doesn't matter.
+//!
+//! Type-level filters go in the second parameter, e.g. `With<C>`,
+//! `Without<C>`, `Added<C>`, `Changed<C>` or a tuple of them. `Added<C>` and
+//! `Changed<C>` match components touched since the previous run of the same
+//! state (since the last `clear_trackers()` on the first run), so changes
+//! made after the query ran in one frame are seen in the next.
+//!
+//! # Example
+//! ```ignore
+//! struct MovementSystem {
+//!     query: QueryState<(Position, Velocity), Without<Frozen>>,
+//! }
+//!
+//! impl MovementSystem {
+//!     fn new() -> Self {
+//!         Self { query: QueryState::new().not_tagged("paused") }
+//!     }
+//!
+//!     fn run(&mut self, world: &mut World) {
+//!         self.query.each_mut(world, |_, (pos, vel)| {
+//!             pos.x += vel.x;
+//!             pos.y += vel.y;
+//!         });
+//!     }
+//! }
+//! ```
+
+use crate::entity::Entity;
+use crate::query::{FilterParam, QueryFilter, check_duplicate_types};
+use crate::storage::SparseSet;
+use crate::tag::{OwnedTag, TagRef};
+use crate::{Component, World};
+use std::any::TypeId;
+use std::marker::PhantomData;
+
+/// Component tuples usable with `QueryState`
+///
+/// Implemented via macro for tuples of 1-12 components.
+pub trait StateTuple: 'static {
+    type Item<'w>;
+    type ItemMut<'w>;
+    /// Shared storage references, one per component type
+    type Storages<'w>: Copy;
+    /// Raw storage pointers of a single `each_mut` call
+    type StoragesMut: Copy;
+
+    fn type_ids() -> Vec<TypeId>;
+
+    /// Looks up all storages for reading
+    fn resolve(world: &World) -> Option<Self::Storages<'_>>;
+
+    /// Looks up all storages for reading and writing
+    fn resolve_mut(world: &mut World) -> Option<Self::StoragesMut>;
+
+    /// Returns the entity IDs of the first storage, which drives iteration
+    fn entity_ids<'w>(storages: Self::Storages<'w>) -> &'w [u32];
+
+    fn fetch<'w>(storages: Self::Storages<'w>, entity_id: u32) -> Option<Self::Item<'w>>;
+
+    /// Returns the entity IDs of the first storage, which drives iteration
+    ///
+    /// # Safety
+    /// The storages must come from `resolve_mut` on a World mutably
+    /// borrowed for `'w`.
+    unsafe fn entity_ids_mut<'w>(storages: Self::StoragesMut) -> &'w [u32];
+
+    /// # Safety
+    /// The storages must come from `resolve_mut` on a World mutably
+    /// borrowed for `'w`, and no other item for the entity may be alive.
+    unsafe fn fetch_mut<'w>(
+        storages: Self::StoragesMut,
+        entity_id: u32,
+        tick: u32,
+    ) -> Option<Self::ItemMut<'w>>;
+}
+
+macro_rules! impl_state_tuple {
+    ($($name:ident),+) => {
+        #[allow(non_snake_case)]
+        impl<$($name: Component),+> StateTuple for ($($name,)+) {
+            type Item<'w> = ($(&'w $name,)+);
+            type ItemMut<'w> = ($(&'w mut $name,)+);
+            type Storages<'w> = ($(&'w SparseSet<$name>,)+);
+            type StoragesMut = ($(*mut SparseSet<$name>,)+);
+
+            fn type_ids() -> Vec<TypeId> {
+                vec![$(TypeId::of::<$name>()),+]
+            }
+
+            fn resolve(world: &World) -> Option<Self::Storages<'_>> {
+                let components = world.components();
+                Some(($(
+                    components
+                        .get(&TypeId::of::<$name>())?
+                        .as_any()
+                        .downcast_ref::<SparseSet<$name>>()?,
+                )+))
+            }
+
+            fn resolve_mut(world: &mut World) -> Option<Self::StoragesMut> {
+                // Types are distinct (checked in `QueryState::new`)
+                let [$($name,)+] = world
+                    .components_mut()
+                    .get_disjoint_mut([$(&TypeId::of::<$name>()),+]);
+
+                Some(($(
+                    $name?.as_any_mut().downcast_mut::<SparseSet<$name>>()? as *mut SparseSet<$name>,
+                )+))
+            }
+
+            fn entity_ids<'w>(storages: Self::Storages<'w>) -> &'w [u32] {
+                storages.0.entities()
+            }
+
+            fn fetch<'w>(storages: Self::Storages<'w>, entity_id: u32) -> Option<Self::Item<'w>> {
+                let ($($name,)+) = storages;
+                Some(($($name.get(entity_id)?,)+))
+            }
+
+            unsafe fn entity_ids_mut<'w>(storages: Self::StoragesMut) -> &'w [u32] {
+                unsafe { (*storages.0).entities() }
+            }
+
+            unsafe fn fetch_mut<'w>(
+                storages: Self::StoragesMut,
+                entity_id: u32,
+                tick: u32,
+            ) -> Option<Self::ItemMut<'w>> {
+                let ($($name,)+) = storages;
+                unsafe {
+                    // Check first so a partial match doesn't mark anything changed
+                    if !($((*$name).contains(entity_id))&&+) {
+                        return None;
+                    }
+
+                    Some(($((*$name).get_mut_tracked(entity_id, tick)?,)+))
+                }
+            }
+        }
+    };
+}
+
+impl_state_tuple!(A);
+impl_state_tuple!(A, B);
+impl_state_tuple!(A, B, C);
+impl_state_tuple!(A, B, C, D);
+impl_state_tuple!(A, B, C, D, E);
+impl_state_tuple!(A, B, C, D, E, F);
+impl_state_tuple!(A, B, C, D, E, F, G);
+impl_state_tuple!(A, B, C, D, E, F, G, H);
+impl_state_tuple!(A, B, C, D, E, F, G, H, I);
+impl_state_tuple!(A, B, C, D, E, F, G, H, I, J);
+impl_state_tuple!(A, B, C, D, E, F, G, H, I, J, K);
+impl_state_tuple!(A, B, C, D, E, F, G, H, I, J, K, L);
+
+/// Everything resolved against one World layout
+struct Cache {
+    world_id: u64,
+    layout: (usize, u64),
+    filter: QueryFilter,
+}
+
+/// Reusable query with cached tag masks and filter
+///
+/// Build it once, e.g. as a field of a system struct, and call `each` /
+/// `each_mut` every frame. Using the same state with another World is
+/// allowed; it is simply rebuilt.
+pub struct QueryState<T: StateTuple, F: FilterParam = ()> {
+    /// Required tags (true) and excluded tags (false)
+    tags: Vec<(OwnedTag, bool)>,
+    cache: Option<Cache>,
+    /// World id and change tick of the previous run
+    last_run: Option<(u64, u32)>,
+    _marker: PhantomData<fn() -> (T, F)>,
+}
+
+impl<T: StateTuple, F: FilterParam> Default for QueryState<T, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: StateTuple, F: FilterParam> QueryState<T, F> {
+    /// Creates a query state; nothing is resolved until first use
+    ///
+    /// # Panics
+    /// Panics if the same component type appears twice in `T`.
+    #[track_caller]
+    pub fn new() -> Self {
+        check_duplicate_types(&T::type_ids());
+
+        Self {
+            tags: Vec::new(),
+            cache: None,
+            last_run: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Require entities to have this tag (name or `Tag` handle)
+    pub fn tagged<'t>(mut self, tag: impl Into<TagRef<'t>>) -> Self {
+        self.tags.push((tag.into().into(), true));
+        self.cache = None;
+        self
+    }
+
+    /// Exclude entities with this tag (name or `Tag` handle)
+    pub fn not_tagged<'t>(mut self, tag: impl Into<TagRef<'t>>) -> Self {
+        self.tags.push((tag.into().into(), false));
+        self.cache = None;
+        self
+    }
+
+    /// Returns true if the cached state is up to date for this World
+    pub fn is_cached(&self, world: &World) -> bool {
+        self.cache
+            .as_ref()
+            .is_some_and(|cache| Self::is_current(cache, world))
+    }
+
+    /// Iterate over all matching entities
+    pub fn each<'w, G>(&mut self, world: &'w World, mut f: G)
+    where
+        G: FnMut(Entity, T::Item<'w>),
+    {
+        let (last_run, _) = self.start_run(world);
+        let cache = self.cache(world);
+        cache.filter.last_run = Some(last_run);
+
+        if cache.filter.impossible {
+            return;
+        }
+
+        let Some(storages) = T::resolve(world) else {
+            return;
+        };
+
+        for &id in T::entity_ids(storages) {
+            let entity = Entity::from_raw(id, world.generation(id));
+
+            if !cache.filter.matches(world, entity) {
+                continue;
+            }
+
+            if let Some(components) = T::fetch(storages, id) {
+                f(entity, components);
+            }
+        }
+    }
+
+    /// Iterate over all matching entities with mutable access
+    pub fn each_mut<'w, G>(&mut self, world: &'w mut World, mut f: G)
+    where
+        G: FnMut(Entity, T::ItemMut<'w>),
+    {
+        let (last_run, tick) = self.start_run(world);
+        let cache = self.cache(world);
+        cache.filter.last_run = Some(last_run);
+
+        if cache.filter.impossible {
+            return;
+        }
+
+        let Some(storages) = T::resolve_mut(world) else {
+            return;
+        };
+
+        // SAFETY: the storages were just resolved from `world`, which stays
+        // mutably borrowed for 'w; only shared World reads happen below.
+        let world: &'w World = world;
+        let entity_ids = unsafe { T::entity_ids_mut(storages) };
+
+        for &id in entity_ids {
+            let entity = Entity::from_raw(id, world.generation(id));
+
+            if !cache.filter.matches(world, entity) {
+                continue;
+            }
+
+            if let Some(components) = unsafe { T::fetch_mut(storages, id, tick) } {
+                f(entity, components);
+            }
+        }
+    }
+
+    /// Advances the World tick and returns the ticks of the previous and this run
+    ///
+    /// Components this run marks as changed get this run's tick, so the next
+    /// run of the state doesn't see its own writes.
+    fn start_run(&mut self, world: &World) -> (u32, u32) {
+        let last_run = match self.last_run {
+            Some((world_id, tick)) if world_id == world.id() => tick,
+            _ => world.last_change_tick(),
+        };
+
+        let this_run = world.advance_change_tick();
+        self.last_run = Some((world.id(), this_run));
+        (last_run, this_run)
+    }
+
+    fn is_current(cache: &Cache, world: &World) -> bool {
+        cache.world_id == world.id() && cache.layout == world.layout_version()
+    }
+
+    /// Returns the cache for this World, rebuilding it if the layout changed
+    fn cache(&mut self, world: &World) -> &mut Cache {
+        if !self.is_cached(world) {
+            self.cache = Some(self.build(world));
+        }
+
+        self.cache.as_mut().expect("cache was just built")
+    }
+
+    /// Resolves the filter against the current tags of the World
+    fn build(&self, world: &World) -> Cache {
+        let mut filter = QueryFilter::new();
+        F::apply(&mut filter);
+
+        for (tag, required) in &self.tags {
+            match (world.get_tag_id(tag.as_ref()), required) {
+                (Some(slot), true) => {
+                    filter.required_mask.insert(slot);
+                }
+                (Some(slot), false) => {
+                    filter.excluded_mask.insert(slot);
+                }
+                (None, true) => filter.impossible = true,
+                (None, false) => {}
+            }
+        }
+
+        Cache {
+            world_id: world.id(),
+            layout: world.layout_version(),
+            filter,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::query::{With, Without};
+    use crate::{Added, Changed};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Shield(i32);
+
+    #[test]
+    fn state_matches_select() {
+        let mut world = World::new();
+        world.spawn().insert(Health(1)).insert(Shield(5));
+        world.spawn().insert(Health(2)).tag("enemy");
+        world.spawn().insert(Shield(3));
+
+        let mut all = QueryState::<(Health,)>::new();
+        let mut total = 0;
+        all.each(&world, |_, (hp,)| total += hp.0);
+        assert_eq!(total, 3);
+
+        let mut shielded = QueryState::<(Health,), With<Shield>>::new();
+        let mut count = 0;
+        shielded.each(&world, |_, _| count += 1);
+        assert_eq!(count, 1);
+
+        let mut enemies = QueryState::<(Health,), Without<Shield>>::new().tagged("enemy");
+        let mut found = Vec::new();
+        enemies.each(&world, |_, (hp,)| found.push(hp.0));
+        assert_eq!(found, vec![2]);
+    }
+
+    #[test]
+    fn state_each_mut_marks_changed() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(1)).insert(Shield(1)).id();
+        world.clear_trackers();
+
+        let mut state = QueryState::<(Health, Shield)>::new();
+        state.each_mut(&mut world, |_, (hp, shield)| {
+            hp.0 += 10;
+            shield.0 += 1;
+        });
+
+        assert_eq!(world.get::<Health>(entity), Some(&Health(11)));
+        assert!(world.is_changed::<Shield>(entity));
+
+        let mut changed = QueryState::<(Health,), (Changed<Health>, With<Shield>)>::new();
+        let mut count = 0;
+        changed.each(&world, |_, _| count += 1);
+        assert_eq!(count, 1);
+
+        let mut added = QueryState::<(Health,), Added<Health>>::new();
+        added.each(&world, |_, _| count += 1);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn state_sees_changes_made_after_its_run() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(1)).id();
+        world.clear_trackers();
+
+        let mut changed = QueryState::<(Health,), Changed<Health>>::new();
+        let mut writer = QueryState::<(Health,)>::new();
+        let mut seen = 0;
+
+        // Frame 1: the reader runs first, the write happens later
+        changed.each(&world, |_, _| seen += 1);
+        writer.each_mut(&mut world, |_, (hp,)| hp.0 += 1);
+        world.clear_trackers();
+        assert_eq!(seen, 0);
+
+        // Frame 2: the write from frame 1 is reported once
+        changed.each(&world, |e, _| {
+            assert_eq!(e, entity);
+            seen += 1;
+        });
+        changed.each(&world, |_, _| seen += 1);
+        assert_eq!(seen, 1);
+
+        // A state doesn't see its own writes on the next run
+        world.get_mut::<Health>(entity).unwrap().0 += 1;
+        let mut clamp = QueryState::<(Health,), Changed<Health>>::new();
+        clamp.each_mut(&mut world, |_, (hp,)| {
+            hp.0 = hp.0.min(2);
+            seen += 1;
+        });
+        clamp.each_mut(&mut world, |_, _| seen += 1);
+        assert_eq!(seen, 2);
+        assert!(world.is_changed::<Health>(entity));
+    }
+
+    #[test]
+    fn state_rebuilds_when_layout_changes() {
+        let mut world = World::new();
+        let mut state = QueryState::<(Health,)>::new().tagged("enemy");
+
+        let mut count = 0;
+        state.each(&world, |_, _| count += 1);
+        assert_eq!(count, 0);
+        assert!(state.is_cached(&world));
+
+        // First Health storage and first "enemy" tag
+        world.spawn().insert(Health(1)).tag("enemy");
+        assert!(!state.is_cached(&world));
+        state.each(&world, |_, _| count += 1);
+        assert_eq!(count, 1);
+
+        // Existing storage and tag keep the cache
+        world.spawn().insert(Health(2)).tag("enemy");
+        assert!(state.is_cached(&world));
+        state.each(&world, |_, _| count += 1);
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn state_rebuilds_for_another_world() {
+        let mut first = World::new();
+        first.spawn().insert(Health(1));
+        let mut second = World::new();
+        second.spawn().insert(Health(2));
+        second.spawn().insert(Health(3));
+
+        let mut state = QueryState::<(Health,)>::new();
+        let mut count = 0;
+        state.each(&first, |_, _| count += 1);
+        state.each(&second, |_, _| count += 1);
+        assert_eq!(count, 3);
+        assert!(!state.is_cached(&first));
+    }
+
+    #[test]
+    fn state_follows_sorted_first_storage() {
+        let mut world = World::new();
+        let mut entities = Vec::new();
+        for hp in [3, 1, 2] {
+            entities.push(world.spawn().insert(Health(hp)).id());
+        }
+        // Shield is the smaller storage, but Health comes first
+        world.insert(entities[0], Shield(0));
+        world.insert(entities[2], Shield(0));
+        world.sort_components_by_key::<Health, _>(|hp| hp.0);
+
+        let mut state = QueryState::<(Health,)>::new();
+        let mut order = Vec::new();
+        state.each(&world, |_, (hp,)| order.push(hp.0));
+        assert_eq!(order, vec![1, 2, 3]);
+
+        let mut shielded = QueryState::<(Health, Shield)>::new();
+        order.clear();
+        shielded.each_mut(&mut world, |_, (hp, _)| order.push(hp.0));
+        assert_eq!(order, vec![2, 3]);
+    }
+
+    #[test]
+    fn state_is_send_and_sync() {
+        fn assert_send_sync<S: Send + Sync>() {}
+        assert_send_sync::<QueryState<(Health, Shield), Changed<Health>>>();
+    }
+
+    #[test]
+    #[should_panic]
+    fn state_duplicate_types_panic() {
+        QueryState::<(Health, Health)>::new();
+    }
+}
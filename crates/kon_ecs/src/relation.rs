@@ -0,0 +1,365 @@
+//! Generic entity relations with cleanup policies
+//!
+//! A relation is a typed, directed link from a source entity to a target
+//! entity ("targets", "owned_by", "docked_at"). A source may be related to
+//! several targets. When a target is destroyed, the relation's
+//! `CleanupPolicy` decides what happens to its sources.
+//!
+//! # Example
+//! ```ignore
+//! struct OwnedBy;
+//! impl Relation for OwnedBy {
+//!     const CLEANUP: CleanupPolicy = CleanupPolicy::DestroySource;
+//! }
+//!
+//! world.relate::<OwnedBy>(sword, player);
+//!
+//! world.select::<(Position,)>()
+//!     .related_to::<OwnedBy>(player)
+//!     .each(|item, (pos,)| println!("{} at {:?}", item, pos));
+//!
+//! world.destroy(player); // sword is destroyed too
+//! ```
+
+use crate::World;
+use crate::entity::Entity;
+use crate::scene::EntityMapper;
+use std::any::TypeId;
+use std::collections::HashMap;
+
+/// What happens to sources when the target of a relation is destroyed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CleanupPolicy {
+    /// Only the relation is removed, the source stays alive
+    #[default]
+    RemoveRelation,
+    /// The source entity is destroyed as well
+    DestroySource,
+}
+
+/// Marker trait for relation types
+///
+/// Relation types are usually empty structs used as labels.
+pub trait Relation: Send + Sync + 'static {
+    /// Cleanup applied to sources when a target is destroyed
+    const CLEANUP: CleanupPolicy = CleanupPolicy::RemoveRelation;
+}
+
+/// Both directions of all links of a single relation type
+pub(crate) struct RelationStorage {
+    cleanup: CleanupPolicy,
+    /// Source entity ID -> targets
+    targets: HashMap<u32, Vec<Entity>>,
+    /// Target entity ID -> sources
+    sources: HashMap<u32, Vec<Entity>>,
+}
+
+impl RelationStorage {
+    fn new(cleanup: CleanupPolicy) -> Self {
+        Self {
+            cleanup,
+            targets: HashMap::new(),
+            sources: HashMap::new(),
+        }
+    }
+
+    fn contains(&self, source: Entity, target: Entity) -> bool {
+        self.targets
+            .get(&source.id())
+            .is_some_and(|targets| targets.contains(&target))
+    }
+
+    fn link(&mut self, source: Entity, target: Entity) {
+        if !self.contains(source, target) {
+            self.targets.entry(source.id()).or_default().push(target);
+            self.sources.entry(target.id()).or_default().push(source);
+        }
+    }
+
+    fn unlink(&mut self, source: Entity, target: Entity) -> bool {
+        let removed = remove_from(&mut self.targets, source.id(), target);
+        remove_from(&mut self.sources, target.id(), source);
+        removed
+    }
+}
+
+/// Removes `entity` from the list stored under `key`, dropping empty lists
+fn remove_from(map: &mut HashMap<u32, Vec<Entity>>, key: u32, entity: Entity) -> bool {
+    let Some(list) = map.get_mut(&key) else {
+        return false;
+    };
+
+    let len = list.len();
+    list.retain(|&e| e != entity);
+    let removed = list.len() != len;
+
+    if list.is_empty() {
+        map.remove(&key);
+    }
+
+    removed
+}
+
+impl World {
+    /// Relates `source` to `target` with relation `R`
+    ///
+    /// Returns false if either entity is not alive.
+    pub fn relate<R: Relation>(&mut self, source: Entity, target: Entity) -> bool {
+        if !self.is_alive(source) || !self.is_alive(target) {
+            return false;
+        }
+
+        let storage = self
+            .relations
+            .entry(TypeId::of::<R>())
+            .or_insert_with(|| RelationStorage::new(R::CLEANUP));
+
+        storage.link(source, target);
+        true
+    }
+
+    /// Removes relation `R` between `source` and `target`
+    ///
+    /// Returns true if the relation existed.
+    pub fn unrelate<R: Relation>(&mut self, source: Entity, target: Entity) -> bool {
+        self.relations
+            .get_mut(&TypeId::of::<R>())
+            .is_some_and(|storage| storage.unlink(source, target))
+    }
+
+    /// Checks if `source` is related to `target` with relation `R`
+    pub fn has_relation<R: Relation>(&self, source: Entity, target: Entity) -> bool {
+        self.has_relation_by_type_id(&TypeId::of::<R>(), source, target)
+    }
+
+    /// Returns all targets of `source` for relation `R`
+    pub fn targets<R: Relation>(&self, source: Entity) -> &[Entity] {
+        if !self.is_alive(source) {
+            return &[];
+        }
+
+        self.relations
+            .get(&TypeId::of::<R>())
+            .and_then(|s| s.targets.get(&source.id()))
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Returns all sources related to `target` with relation `R`
+    pub fn sources<R: Relation>(&self, target: Entity) -> &[Entity] {
+        if !self.is_alive(target) {
+            return &[];
+        }
+
+        self.relations
+            .get(&TypeId::of::<R>())
+            .and_then(|s| s.sources.get(&target.id()))
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Checks a relation by TypeId (used by query filters)
+    pub(crate) fn has_relation_by_type_id(
+        &self,
+        type_id: &TypeId,
+        source: Entity,
+        target: Entity,
+    ) -> bool {
+        self.is_alive(source)
+            && self
+                .relations
+                .get(type_id)
+                .is_some_and(|s| s.contains(source, target))
+    }
+
+    /// Returns the targets of a source by relation TypeId (used by scenes)
+    pub(crate) fn targets_by_type_id(&self, type_id: &TypeId, source: Entity) -> &[Entity] {
+        self.relations
+            .get(type_id)
+            .and_then(|s| s.targets.get(&source.id()))
+            .map(|v| v.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Gives `clone` every outgoing relation of `source` (used by `clone_entity`)
+    pub(crate) fn copy_relations(&mut self, source: Entity, clone: Entity) {
+        for storage in self.relations.values_mut() {
+            let Some(targets) = storage.targets.get(&source.id()).cloned() else {
+                continue;
+            };
+
+            for target in targets {
+                storage.link(clone, target);
+            }
+        }
+    }
+
+    /// Copies all relations of another World, remapping both ends (used by `merge`)
+    ///
+    /// Links whose ends don't map to alive entities are dropped.
+    pub(crate) fn import_relations(&mut self, other: &World, mapper: &EntityMapper) {
+        for (type_id, theirs) in &other.relations {
+            for (&source_id, targets) in &theirs.targets {
+                let source = mapper.map(Entity::new(source_id, other.generation(source_id)));
+                if !self.is_alive(source) {
+                    continue;
+                }
+
+                for &target in targets {
+                    let target = mapper.map(target);
+                    if self.is_alive(target) {
+                        self.relations
+                            .entry(*type_id)
+                            .or_insert_with(|| RelationStorage::new(theirs.cleanup))
+                            .link(source, target);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Removes every relation the entity takes part in
+    ///
+    /// Returns the sources that must be destroyed because of a
+    /// `CleanupPolicy::DestroySource` relation targeting the entity.
+    /// Called by `destroy` before components are dropped.
+    pub(crate) fn detach_relations(&mut self, entity: Entity) -> Vec<Entity> {
+        let mut doomed = Vec::new();
+
+        for storage in self.relations.values_mut() {
+            if let Some(targets) = storage.targets.remove(&entity.id()) {
+                for target in targets {
+                    remove_from(&mut storage.sources, target.id(), entity);
+                }
+            }
+
+            if let Some(sources) = storage.sources.remove(&entity.id()) {
+                for source in sources {
+                    remove_from(&mut storage.targets, source.id(), entity);
+                    if storage.cleanup == CleanupPolicy::DestroySource {
+                        doomed.push(source);
+                    }
+                }
+            }
+        }
+
+        doomed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Targets;
+    impl Relation for Targets {}
+
+    struct OwnedBy;
+    impl Relation for OwnedBy {
+        const CLEANUP: CleanupPolicy = CleanupPolicy::DestroySource;
+    }
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[test]
+    fn relate_and_lookup() {
+        let mut world = World::new();
+        let turret = world.spawn().id();
+        let a = world.spawn().id();
+        let b = world.spawn().id();
+
+        assert!(world.relate::<Targets>(turret, a));
+        assert!(world.relate::<Targets>(turret, b));
+        assert!(world.relate::<Targets>(turret, a));
+
+        assert_eq!(world.targets::<Targets>(turret), &[a, b]);
+        assert_eq!(world.sources::<Targets>(a), &[turret]);
+        assert!(world.has_relation::<Targets>(turret, b));
+        assert!(!world.has_relation::<OwnedBy>(turret, b));
+    }
+
+    #[test]
+    fn unrelate_removes_both_directions() {
+        let mut world = World::new();
+        let turret = world.spawn().id();
+        let a = world.spawn().id();
+        world.relate::<Targets>(turret, a);
+
+        assert!(world.unrelate::<Targets>(turret, a));
+        assert!(!world.unrelate::<Targets>(turret, a));
+        assert!(world.targets::<Targets>(turret).is_empty());
+        assert!(world.sources::<Targets>(a).is_empty());
+    }
+
+    #[test]
+    fn destroy_target_removes_relation() {
+        let mut world = World::new();
+        let turret = world.spawn().id();
+        let a = world.spawn().id();
+        world.relate::<Targets>(turret, a);
+
+        world.destroy(a);
+
+        assert!(world.is_alive(turret));
+        assert!(world.targets::<Targets>(turret).is_empty());
+    }
+
+    #[test]
+    fn destroy_target_destroys_sources() {
+        let mut world = World::new();
+        let player = world.spawn().id();
+        let sword = world.spawn().id();
+        let gem = world.spawn().id();
+        world.relate::<OwnedBy>(sword, player);
+        world.relate::<OwnedBy>(gem, sword);
+
+        world.destroy(player);
+
+        assert!(!world.is_alive(sword));
+        assert!(!world.is_alive(gem));
+        assert_eq!(world.entity_count(), 0);
+    }
+
+    #[test]
+    fn destroy_source_clears_target_index() {
+        let mut world = World::new();
+        let player = world.spawn().id();
+        let sword = world.spawn().id();
+        world.relate::<OwnedBy>(sword, player);
+
+        world.destroy(sword);
+
+        assert!(world.sources::<OwnedBy>(player).is_empty());
+    }
+
+    #[test]
+    fn relate_dead_entity_fails() {
+        let mut world = World::new();
+        let a = world.spawn().id();
+        let b = world.spawn().id();
+        world.destroy(b);
+
+        assert!(!world.relate::<Targets>(a, b));
+    }
+
+    #[test]
+    fn query_related_to() {
+        let mut world = World::new();
+        let player = world.spawn().id();
+        let other = world.spawn().id();
+        let sword = world.spawn().insert(Health(10)).id();
+        world.spawn().insert(Health(20));
+        let shield = world.spawn().insert(Health(30)).id();
+        world.relate::<OwnedBy>(sword, player);
+        world.relate::<OwnedBy>(shield, other);
+
+        let mut owned = Vec::new();
+        world
+            .select::<(Health,)>()
+            .related_to::<OwnedBy>(player)
+            .each(|entity, _| owned.push(entity));
+
+        assert_eq!(owned, vec![sword]);
+    }
+}
@@ -0,0 +1,144 @@
+//! World-local resources
+//!
+//! Resources are singletons stored inside a `World`, one value per type.
+//! Unlike `Context` globals they belong to the World, so resources registered
+//! via `register_serializable_resource` are saved in scenes and snapshots and
+//! restored together with the entities. Queries can read a resource while
+//! iterating components via `each_with_resource`.
+//!
+//! # Example
+//! ```ignore
+//! world.insert_resource(Score(0));
+//! world.register_serializable_resource::<Score>("Score");
+//!
+//! world.resource_mut::<Score>().unwrap().0 += 10;
+//!
+//! world.select_mut::<(Velocity,)>()
+//!     .each_with_resource(|_, (vel,), gravity: &Gravity| {
+//!         vel.y -= gravity.0;
+//!     });
+//! ```
+
+use crate::World;
+use std::any::{Any, TypeId};
+use std::collections::HashMap;
+
+/// Resource values keyed by type
+#[derive(Default)]
+pub(crate) struct Resources {
+    values: HashMap<TypeId, Box<dyn Any + Send + Sync>>,
+}
+
+impl Resources {
+    pub(crate) fn insert_boxed(
+        &mut self,
+        type_id: TypeId,
+        value: Box<dyn Any + Send + Sync>,
+    ) -> Option<Box<dyn Any + Send + Sync>> {
+        self.values.insert(type_id, value)
+    }
+
+    pub(crate) fn remove_boxed(&mut self, type_id: &TypeId) -> Option<Box<dyn Any + Send + Sync>> {
+        self.values.remove(type_id)
+    }
+
+    pub(crate) fn get<R: Any + Send + Sync>(&self) -> Option<&R> {
+        self.values.get(&TypeId::of::<R>())?.downcast_ref()
+    }
+
+    pub(crate) fn get_mut<R: Any + Send + Sync>(&mut self) -> Option<&mut R> {
+        self.values.get_mut(&TypeId::of::<R>())?.downcast_mut()
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.values.len()
+    }
+}
+
+impl World {
+    /// Inserts a resource, returning the one it replaced
+    pub fn insert_resource<R: Any + Send + Sync>(&mut self, resource: R) -> Option<R> {
+        self.resources
+            .insert_boxed(TypeId::of::<R>(), Box::new(resource))
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    /// Inserts `R::default()` unless the resource already exists
+    pub fn init_resource<R: Any + Send + Sync + Default>(&mut self) -> &mut R {
+        if !self.has_resource::<R>() {
+            self.insert_resource(R::default());
+        }
+
+        self.resources.get_mut().unwrap()
+    }
+
+    /// Removes a resource and returns it
+    pub fn remove_resource<R: Any + Send + Sync>(&mut self) -> Option<R> {
+        self.resources
+            .remove_boxed(&TypeId::of::<R>())
+            .and_then(|old| old.downcast().ok())
+            .map(|old| *old)
+    }
+
+    pub fn resource<R: Any + Send + Sync>(&self) -> Option<&R> {
+        self.resources.get()
+    }
+
+    pub fn resource_mut<R: Any + Send + Sync>(&mut self) -> Option<&mut R> {
+        self.resources.get_mut()
+    }
+
+    pub fn has_resource<R: Any + Send + Sync>(&self) -> bool {
+        self.resources.get::<R>().is_some()
+    }
+
+    /// Returns the number of resources in this World
+    pub fn resource_count(&self) -> usize {
+        self.resources.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Default, PartialEq)]
+    struct Score(u32);
+
+    #[derive(Debug, PartialEq)]
+    struct Health(i32);
+
+    #[test]
+    fn insert_replace_remove() {
+        let mut world = World::new();
+        assert_eq!(world.insert_resource(Score(1)), None);
+        assert_eq!(world.insert_resource(Score(2)), Some(Score(1)));
+
+        world.resource_mut::<Score>().unwrap().0 += 3;
+        assert_eq!(world.resource::<Score>(), Some(&Score(5)));
+        assert_eq!(world.resource_count(), 1);
+
+        assert_eq!(world.remove_resource::<Score>(), Some(Score(5)));
+        assert!(!world.has_resource::<Score>());
+    }
+
+    #[test]
+    fn init_keeps_existing_value() {
+        let mut world = World::new();
+        world.init_resource::<Score>().0 = 7;
+        assert_eq!(world.init_resource::<Score>(), &mut Score(7));
+    }
+
+    #[test]
+    fn resources_survive_clear() {
+        let mut world = World::new();
+        world.insert_resource(Score(3));
+        world.spawn().insert(Health(1));
+
+        world.clear();
+
+        assert_eq!(world.entity_count(), 0);
+        assert_eq!(world.resource::<Score>(), Some(&Score(3)));
+    }
+}
@@ -0,0 +1,859 @@
+//! Human-readable scene files (JSON)
+//!
+//! A scene stores entities with their tags, hierarchy, relations and every
+//! component whose type was registered as serializable, plus registered
+//! World resources. Entity references are
+//! remapped on load, so a scene can be loaded into any World, even one that
+//! already contains entities.
+//!
+//! # Example
+//! ```ignore
+//! #[derive(Debug, Serialize, Deserialize)]
+//! struct Health(i32);
+//!
+//! #[derive(Debug, Serialize, Deserialize)]
+//! struct Target(Entity);
+//!
+//! impl MapEntities for Target {
+//!     fn map_entities(&mut self, mapper: &EntityMapper) {
+//!         self.0 = mapper.map(self.0);
+//!     }
+//! }
+//!
+//! world.register_serializable::<Health>("Health");
+//! world.register_serializable_mapped::<Target>("Target");
+//!
+//! let json = world.save_scene()?;
+//! std::fs::write("level.json", &json)?;
+//!
+//! let mut other = World::new();
+//! other.register_serializable::<Health>("Health");
+//! other.register_serializable_mapped::<Target>("Target");
+//! let spawned = other.load_scene(&json)?;
+//! ```
+
+use crate::entity::Entity;
+use crate::name::describe_entity;
+use crate::relation::Relation;
+use crate::{Component, World};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::any::{Any, TypeId};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+
+/// Scene contents, one entry per entity
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Scene {
+    pub entities: Vec<SceneEntity>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub resources: BTreeMap<String, Value>,
+}
+
+/// A single entity of a scene
+///
+/// `entity` is the handle the entity had when the scene was saved. It is only
+/// used to resolve references inside the scene and is remapped on load.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SceneEntity {
+    pub entity: Entity,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub children: Vec<Entity>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub components: BTreeMap<String, Value>,
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub relations: BTreeMap<String, Vec<Entity>>,
+}
+
+impl SceneEntity {
+    /// Returns the saved `Name` of the entity
+    fn name(&self) -> Option<String> {
+        self.components
+            .get("Name")
+            .and_then(Value::as_str)
+            .map(str::to_string)
+    }
+}
+
+/// Errors produced while saving or loading a scene
+///
+/// `entity_name` is the saved `Name` of the scene entity, if it has one, and
+/// is shown next to the entity like `World::describe` does.
+#[derive(Debug)]
+pub enum SceneError {
+    /// The scene text is not valid scene JSON
+    Parse(serde_json::Error),
+    /// A component name is not registered in the loading World
+    UnknownComponent {
+        entity: Entity,
+        entity_name: Option<String>,
+        name: String,
+    },
+    /// A relation name is not registered in the loading World
+    UnknownRelation {
+        entity: Entity,
+        entity_name: Option<String>,
+        name: String,
+    },
+    /// A resource name is not registered in the loading World
+    UnknownResource(String),
+    /// A component value doesn't match its registered type
+    InvalidComponent {
+        entity: Entity,
+        entity_name: Option<String>,
+        name: String,
+        source: serde_json::Error,
+    },
+    /// A resource value doesn't match its registered type
+    InvalidResource {
+        name: String,
+        source: serde_json::Error,
+    },
+    /// A child or relation target refers to an entity missing from the scene
+    MissingEntity {
+        entity: Entity,
+        entity_name: Option<String>,
+        reference: Entity,
+    },
+    /// Two scene entities share the same entity id
+    DuplicateEntity {
+        entity: Entity,
+        entity_name: Option<String>,
+    },
+    /// A component failed to serialize
+    Serialize {
+        name: String,
+        source: serde_json::Error,
+    },
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneError::Parse(err) => write!(f, "invalid scene: {}", err),
+            SceneError::UnknownComponent {
+                entity,
+                entity_name,
+                name,
+            } => {
+                let entity = describe_entity(*entity, entity_name.as_deref());
+                write!(f, "{}: unknown component '{}'", entity, name)
+            }
+            SceneError::UnknownRelation {
+                entity,
+                entity_name,
+                name,
+            } => {
+                let entity = describe_entity(*entity, entity_name.as_deref());
+                write!(f, "{}: unknown relation '{}'", entity, name)
+            }
+            SceneError::UnknownResource(name) => write!(f, "unknown resource '{}'", name),
+            SceneError::InvalidComponent {
+                entity,
+                entity_name,
+                name,
+                source,
+            } => {
+                let entity = describe_entity(*entity, entity_name.as_deref());
+                write!(f, "{}: invalid component '{}': {}", entity, name, source)
+            }
+            SceneError::InvalidResource { name, source } => {
+                write!(f, "invalid resource '{}': {}", name, source)
+            }
+            SceneError::MissingEntity {
+                entity,
+                entity_name,
+                reference,
+            } => {
+                let entity = describe_entity(*entity, entity_name.as_deref());
+                write!(
+                    f,
+                    "{}: references {} which is not in the scene",
+                    entity, reference
+                )
+            }
+            SceneError::DuplicateEntity {
+                entity,
+                entity_name,
+            } => {
+                let entity = describe_entity(*entity, entity_name.as_deref());
+                write!(f, "{}: appears more than once in the scene", entity)
+            }
+            SceneError::Serialize { name, source } => {
+                write!(f, "failed to serialize component '{}': {}", name, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneError::Parse(err)
+            | SceneError::InvalidComponent { source: err, .. }
+            | SceneError::InvalidResource { source: err, .. }
+            | SceneError::Serialize { source: err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Maps entity handles stored in a scene to the entities spawned for them
+pub struct EntityMapper<'a> {
+    /// None maps every entity to itself
+    map: Option<&'a HashMap<Entity, Entity>>,
+}
+
+impl<'a> EntityMapper<'a> {
+    pub(crate) fn new(map: &'a HashMap<Entity, Entity>) -> Self {
+        Self { map: Some(map) }
+    }
+
+    /// Mapper keeping every handle unchanged (used when restoring snapshots)
+    pub(crate) fn identity() -> Self {
+        Self { map: None }
+    }
+
+    /// Returns the new handle of a saved entity
+    ///
+    /// References to entities outside the scene map to `Entity::PLACEHOLDER`.
+    pub fn map(&self, entity: Entity) -> Entity {
+        match self.map {
+            Some(map) => map
+                .get(&entity)
+                .copied()
+                .unwrap_or(Entity::PLACEHOLDER),
+            None => entity,
+        }
+    }
+}
+
+/// Components holding `Entity` references that must be remapped on load
+pub trait MapEntities {
+    fn map_entities(&mut self, mapper: &EntityMapper);
+}
+
+pub(crate) type BoxedComponent = Box<dyn Any + Send + Sync>;
+
+type SerializeFn = fn(&World, Entity) -> Option<Result<Value, serde_json::Error>>;
+type EncodeFn = fn(&World, Entity) -> Option<Result<Vec<u8>, bincode::Error>>;
+type SerializeResourceFn = fn(&World) -> Option<Result<Value, serde_json::Error>>;
+type EncodeResourceFn = fn(&World) -> Option<Result<Vec<u8>, bincode::Error>>;
+
+/// Type-erased serde functions of a registered component
+///
+/// Text scenes use the JSON pair, binary snapshots the bincode pair.
+#[derive(Clone, Copy)]
+pub(crate) struct ComponentSerde {
+    pub(crate) serialize: SerializeFn,
+    pub(crate) deserialize: fn(Value) -> Result<BoxedComponent, serde_json::Error>,
+    pub(crate) encode: EncodeFn,
+    pub(crate) decode: fn(&[u8]) -> Result<BoxedComponent, bincode::Error>,
+    pub(crate) insert: fn(&mut World, Entity, BoxedComponent, &EntityMapper),
+}
+
+/// Type-erased serde functions of a registered resource
+#[derive(Clone, Copy)]
+pub(crate) struct ResourceSerde {
+    pub(crate) serialize: SerializeResourceFn,
+    pub(crate) deserialize: fn(Value) -> Result<BoxedComponent, serde_json::Error>,
+    pub(crate) encode: EncodeResourceFn,
+    pub(crate) decode: fn(&[u8]) -> Result<BoxedComponent, bincode::Error>,
+    pub(crate) insert: fn(&mut World, BoxedComponent, &EntityMapper),
+}
+
+/// Type-erased relation functions of a registered relation
+#[derive(Clone, Copy)]
+pub(crate) struct RelationSerde {
+    pub(crate) relate: fn(&mut World, Entity, Entity) -> bool,
+}
+
+/// Component, relation and resource types known to scenes and snapshots
+#[derive(Default, Clone)]
+pub(crate) struct SceneRegistry {
+    pub(crate) components: BTreeMap<String, (TypeId, ComponentSerde)>,
+    pub(crate) relations: BTreeMap<String, (TypeId, RelationSerde)>,
+    pub(crate) resources: BTreeMap<String, (TypeId, ResourceSerde)>,
+}
+
+fn serialize_component<C: Component + Serialize>(
+    world: &World,
+    entity: Entity,
+) -> Option<Result<Value, serde_json::Error>> {
+    world.get::<C>(entity).map(serde_json::to_value)
+}
+
+fn deserialize_component<C: Component + DeserializeOwned>(
+    value: Value,
+) -> Result<BoxedComponent, serde_json::Error> {
+    Ok(Box::new(serde_json::from_value::<C>(value)?))
+}
+
+fn encode_component<C: Component + Serialize>(
+    world: &World,
+    entity: Entity,
+) -> Option<Result<Vec<u8>, bincode::Error>> {
+    world.get::<C>(entity).map(bincode::serialize)
+}
+
+fn decode_component<C: Component + DeserializeOwned>(
+    bytes: &[u8],
+) -> Result<BoxedComponent, bincode::Error> {
+    Ok(Box::new(bincode::deserialize::<C>(bytes)?))
+}
+
+fn insert_component<C: Component>(
+    world: &mut World,
+    entity: Entity,
+    component: BoxedComponent,
+    _: &EntityMapper,
+) {
+    if let Ok(component) = component.downcast::<C>() {
+        world.insert(entity, *component);
+    }
+}
+
+fn insert_mapped_component<C: Component + MapEntities>(
+    world: &mut World,
+    entity: Entity,
+    component: BoxedComponent,
+    mapper: &EntityMapper,
+) {
+    if let Ok(mut component) = component.downcast::<C>() {
+        component.map_entities(mapper);
+        world.insert(entity, *component);
+    }
+}
+
+fn serialize_resource<R: Any + Send + Sync + Serialize>(
+    world: &World,
+) -> Option<Result<Value, serde_json::Error>> {
+    world.resource::<R>().map(serde_json::to_value)
+}
+
+fn deserialize_resource<R: Any + Send + Sync + DeserializeOwned>(
+    value: Value,
+) -> Result<BoxedComponent, serde_json::Error> {
+    Ok(Box::new(serde_json::from_value::<R>(value)?))
+}
+
+fn encode_resource<R: Any + Send + Sync + Serialize>(
+    world: &World,
+) -> Option<Result<Vec<u8>, bincode::Error>> {
+    world.resource::<R>().map(bincode::serialize)
+}
+
+fn decode_resource<R: Any + Send + Sync + DeserializeOwned>(
+    bytes: &[u8],
+) -> Result<BoxedComponent, bincode::Error> {
+    Ok(Box::new(bincode::deserialize::<R>(bytes)?))
+}
+
+fn insert_resource<R: Any + Send + Sync>(
+    world: &mut World,
+    resource: BoxedComponent,
+    _: &EntityMapper,
+) {
+    if let Ok(resource) = resource.downcast::<R>() {
+        world.insert_resource(*resource);
+    }
+}
+
+fn insert_mapped_resource<R: Any + Send + Sync + MapEntities>(
+    world: &mut World,
+    resource: BoxedComponent,
+    mapper: &EntityMapper,
+) {
+    if let Ok(mut resource) = resource.downcast::<R>() {
+        resource.map_entities(mapper);
+        world.insert_resource(*resource);
+    }
+}
+
+fn relate_typed<R: Relation>(world: &mut World, source: Entity, target: Entity) -> bool {
+    world.relate::<R>(source, target)
+}
+
+impl World {
+    /// Registers a component type for scenes under the given name
+    ///
+    /// Only registered components are saved; loading a scene that contains an
+    /// unregistered name fails with `SceneError::UnknownComponent`.
+    pub fn register_serializable<C>(&mut self, name: &str)
+    where
+        C: Component + Serialize + DeserializeOwned,
+    {
+        self.register_component_serde::<C>(
+            name,
+            ComponentSerde {
+                serialize: serialize_component::<C>,
+                deserialize: deserialize_component::<C>,
+                encode: encode_component::<C>,
+                decode: decode_component::<C>,
+                insert: insert_component::<C>,
+            },
+        );
+    }
+
+    /// Registers a component type holding `Entity` references for scenes
+    ///
+    /// References are remapped via `MapEntities` when the scene is loaded.
+    pub fn register_serializable_mapped<C>(&mut self, name: &str)
+    where
+        C: Component + Serialize + DeserializeOwned + MapEntities,
+    {
+        self.register_component_serde::<C>(
+            name,
+            ComponentSerde {
+                serialize: serialize_component::<C>,
+                deserialize: deserialize_component::<C>,
+                encode: encode_component::<C>,
+                decode: decode_component::<C>,
+                insert: insert_mapped_component::<C>,
+            },
+        );
+        self.register_map_entities::<C>();
+    }
+
+    /// Registers a relation type for scenes under the given name
+    pub fn register_serializable_relation<R: Relation>(&mut self, name: &str) {
+        self.scene_registry.relations.insert(
+            name.to_string(),
+            (
+                TypeId::of::<R>(),
+                RelationSerde {
+                    relate: relate_typed::<R>,
+                },
+            ),
+        );
+    }
+
+    /// Registers a resource type for scenes and snapshots under the given name
+    ///
+    /// Saved scenes and snapshots include the resource if the World holds it.
+    pub fn register_serializable_resource<R>(&mut self, name: &str)
+    where
+        R: Any + Send + Sync + Serialize + DeserializeOwned,
+    {
+        self.register_resource_serde::<R>(name, insert_resource::<R>);
+    }
+
+    /// Registers a resource type holding `Entity` references for scenes
+    ///
+    /// References are remapped via `MapEntities` when the scene is loaded.
+    pub fn register_serializable_resource_mapped<R>(&mut self, name: &str)
+    where
+        R: Any + Send + Sync + Serialize + DeserializeOwned + MapEntities,
+    {
+        self.register_resource_serde::<R>(name, insert_mapped_resource::<R>);
+    }
+
+    fn register_resource_serde<R>(
+        &mut self,
+        name: &str,
+        insert: fn(&mut World, BoxedComponent, &EntityMapper),
+    ) where
+        R: Any + Send + Sync + Serialize + DeserializeOwned,
+    {
+        let type_id = TypeId::of::<R>();
+        let serde = ResourceSerde {
+            serialize: serialize_resource::<R>,
+            deserialize: deserialize_resource::<R>,
+            encode: encode_resource::<R>,
+            decode: decode_resource::<R>,
+            insert,
+        };
+
+        self.scene_registry
+            .resources
+            .retain(|_, (id, _)| *id != type_id);
+        self.scene_registry
+            .resources
+            .insert(name.to_string(), (type_id, serde));
+    }
+
+    fn register_component_serde<C: Component>(&mut self, name: &str, serde: ComponentSerde) {
+        let type_id = TypeId::of::<C>();
+        self.scene_registry
+            .components
+            .retain(|_, (id, _)| *id != type_id);
+        self.scene_registry
+            .components
+            .insert(name.to_string(), (type_id, serde));
+    }
+
+    /// Captures all alive entities and registered resources as a `Scene`,
+    /// ordered by entity ID
+    pub fn to_scene(&self) -> Result<Scene, SceneError> {
+        let mut entities: Vec<Entity> = self.entities().collect();
+        entities.sort_by_key(|e| e.id());
+
+        let mut scene = Scene::default();
+
+        for entity in entities {
+            let mut saved = SceneEntity {
+                entity,
+                tags: self.get_entity_tags(entity.id()),
+                children: self.children(entity).to_vec(),
+                components: BTreeMap::new(),
+                relations: BTreeMap::new(),
+            };
+
+            for (name, (type_id, serde)) in &self.scene_registry.components {
+                if !self.has_by_type_id(entity, type_id) {
+                    continue;
+                }
+
+                let value = (serde.serialize)(self, entity)
+                    .expect("component checked above")
+                    .map_err(|source| SceneError::Serialize {
+                        name: name.clone(),
+                        source,
+                    })?;
+                saved.components.insert(name.clone(), value);
+            }
+
+            for (name, (type_id, _)) in &self.scene_registry.relations {
+                let targets = self.targets_by_type_id(type_id, entity);
+                if !targets.is_empty() {
+                    saved.relations.insert(name.clone(), targets.to_vec());
+                }
+            }
+
+            scene.entities.push(saved);
+        }
+
+        for (name, (_, serde)) in &self.scene_registry.resources {
+            if let Some(value) = (serde.serialize)(self) {
+                let value = value.map_err(|source| SceneError::Serialize {
+                    name: name.clone(),
+                    source,
+                })?;
+                scene.resources.insert(name.clone(), value);
+            }
+        }
+
+        Ok(scene)
+    }
+
+    /// Serializes all alive entities to pretty-printed scene JSON
+    pub fn save_scene(&self) -> Result<String, SceneError> {
+        let scene = self.to_scene()?;
+        serde_json::to_string_pretty(&scene).map_err(|source| SceneError::Serialize {
+            name: "Scene".to_string(),
+            source,
+        })
+    }
+
+    /// Parses scene JSON and spawns its entities
+    ///
+    /// See `spawn_scene`.
+    pub fn load_scene(&mut self, json: &str) -> Result<Vec<Entity>, SceneError> {
+        let scene: Scene = serde_json::from_str(json).map_err(SceneError::Parse)?;
+        self.spawn_scene(&scene)
+    }
+
+    /// Spawns the entities of a scene and returns them in scene order
+    ///
+    /// The scene is fully validated first, so on error the World is unchanged.
+    /// Children, relations and `MapEntities` components are remapped to the
+    /// newly spawned entities. Resources in the scene replace existing ones.
+    pub fn spawn_scene(&mut self, scene: &Scene) -> Result<Vec<Entity>, SceneError> {
+        let mut in_scene = HashSet::with_capacity(scene.entities.len());
+        for saved in &scene.entities {
+            if !in_scene.insert(saved.entity) {
+                return Err(SceneError::DuplicateEntity {
+                    entity: saved.entity,
+                    entity_name: saved.name(),
+                });
+            }
+        }
+
+        // Validate and deserialize everything before touching the World
+        let mut decoded = Vec::with_capacity(scene.entities.len());
+        for saved in &scene.entities {
+            let mut components = Vec::with_capacity(saved.components.len());
+            for (name, value) in &saved.components {
+                let (_, serde) = self.scene_registry.components.get(name).ok_or_else(|| {
+                    SceneError::UnknownComponent {
+                        entity: saved.entity,
+                        entity_name: saved.name(),
+                        name: name.clone(),
+                    }
+                })?;
+
+                let component = (serde.deserialize)(value.clone()).map_err(|source| {
+                    SceneError::InvalidComponent {
+                        entity: saved.entity,
+                        entity_name: saved.name(),
+                        name: name.clone(),
+                        source,
+                    }
+                })?;
+                components.push((serde.insert, component));
+            }
+
+            for (name, targets) in &saved.relations {
+                if !self.scene_registry.relations.contains_key(name) {
+                    return Err(SceneError::UnknownRelation {
+                        entity: saved.entity,
+                        entity_name: saved.name(),
+                        name: name.clone(),
+                    });
+                }
+
+                check_references(saved, targets, &in_scene)?;
+            }
+            check_references(saved, &saved.children, &in_scene)?;
+
+            decoded.push(components);
+        }
+
+        let mut resources = Vec::with_capacity(scene.resources.len());
+        for (name, value) in &scene.resources {
+            let (_, serde) = self
+                .scene_registry
+                .resources
+                .get(name)
+                .ok_or_else(|| SceneError::UnknownResource(name.clone()))?;
+
+            let resource = (serde.deserialize)(value.clone()).map_err(|source| {
+                SceneError::InvalidResource {
+                    name: name.clone(),
+                    source,
+                }
+            })?;
+            resources.push((serde.insert, resource));
+        }
+
+        self.reserve_entities(scene.entities.len());
+        let map: HashMap<Entity, Entity> = scene
+            .entities
+            .iter()
+            .map(|saved| (saved.entity, self.spawn().id()))
+            .collect();
+        let mapper = EntityMapper::new(&map);
+
+        for (saved, components) in scene.entities.iter().zip(decoded) {
+            let entity = mapper.map(saved.entity);
+
+            for tag in &saved.tags {
+                self.tag(entity, tag);
+            }
+
+            for (insert, component) in components {
+                insert(self, entity, component, &mapper);
+            }
+        }
+
+        for saved in &scene.entities {
+            let entity = mapper.map(saved.entity);
+
+            for &child in &saved.children {
+                self.set_parent(mapper.map(child), entity);
+            }
+
+            for (name, targets) in &saved.relations {
+                let (_, serde) = self.scene_registry.relations[name];
+                for &target in targets {
+                    (serde.relate)(self, entity, mapper.map(target));
+                }
+            }
+        }
+
+        for (insert, resource) in resources {
+            insert(self, resource, &mapper);
+        }
+
+        Ok(scene
+            .entities
+            .iter()
+            .map(|e| mapper.map(e.entity))
+            .collect())
+    }
+}
+
+fn check_references(
+    saved: &SceneEntity,
+    references: &[Entity],
+    in_scene: &HashSet<Entity>,
+) -> Result<(), SceneError> {
+    match references.iter().find(|r| !in_scene.contains(r)) {
+        Some(&reference) => Err(SceneError::MissingEntity {
+            entity: saved.entity,
+            entity_name: saved.name(),
+            reference,
+        }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Name;
+    use crate::relation::CleanupPolicy;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Target(Entity);
+
+    impl MapEntities for Target {
+        fn map_entities(&mut self, mapper: &EntityMapper) {
+            self.0 = mapper.map(self.0);
+        }
+    }
+
+    struct OwnedBy;
+    impl Relation for OwnedBy {
+        const CLEANUP: CleanupPolicy = CleanupPolicy::DestroySource;
+    }
+
+    fn registered_world() -> World {
+        let mut world = World::new();
+        world.register_serializable::<Health>("Health");
+        world.register_serializable::<Position>("Position");
+        world.register_serializable_mapped::<Target>("Target");
+        world.register_serializable_relation::<OwnedBy>("OwnedBy");
+        world
+    }
+
+    #[test]
+    fn round_trip_components_and_tags() {
+        let mut world = registered_world();
+        world
+            .spawn()
+            .insert(Health(100))
+            .insert(Position { x: 1.0, y: 2.0 })
+            .tag("player");
+
+        let json = world.save_scene().unwrap();
+
+        let mut loaded = registered_world();
+        let entities = loaded.load_scene(&json).unwrap();
+
+        assert_eq!(entities.len(), 1);
+        assert_eq!(loaded.get::<Health>(entities[0]), Some(&Health(100)));
+        assert_eq!(
+            loaded.get::<Position>(entities[0]),
+            Some(&Position { x: 1.0, y: 2.0 })
+        );
+        assert!(loaded.has_tag(entities[0], "player"));
+    }
+
+    #[test]
+    fn references_are_remapped() {
+        let mut world = registered_world();
+        let player = world.spawn().insert(Health(100)).id();
+        let sword = world.spawn().insert(Target(player)).id();
+        world.relate::<OwnedBy>(sword, player);
+        world.set_parent(sword, player);
+
+        let json = world.save_scene().unwrap();
+
+        // Occupy the saved IDs so the remap is observable
+        let mut loaded = registered_world();
+        loaded.spawn().id();
+        loaded.spawn().id();
+        let entities = loaded.load_scene(&json).unwrap();
+        let (new_player, new_sword) = (entities[0], entities[1]);
+
+        assert_ne!(new_player, player);
+        assert_eq!(loaded.get::<Target>(new_sword), Some(&Target(new_player)));
+        assert!(loaded.has_relation::<OwnedBy>(new_sword, new_player));
+        assert_eq!(loaded.children(new_player), &[new_sword]);
+    }
+
+    #[test]
+    fn resources_round_trip_with_remapping() {
+        let mut world = registered_world();
+        world.register_serializable_resource::<Health>("Score");
+        world.register_serializable_resource_mapped::<Target>("Player");
+        let player = world.spawn().insert(Health(100)).id();
+        world.insert_resource(Health(42));
+        world.insert_resource(Target(player));
+
+        let json = world.save_scene().unwrap();
+
+        let mut loaded = registered_world();
+        loaded.spawn().id();
+        let err = loaded.load_scene(&json).unwrap_err();
+        assert!(matches!(err, SceneError::UnknownResource(ref name) if name == "Player"));
+        assert_eq!(loaded.entity_count(), 1);
+
+        loaded.register_serializable_resource::<Health>("Score");
+        loaded.register_serializable_resource_mapped::<Target>("Player");
+        let entities = loaded.load_scene(&json).unwrap();
+
+        assert_eq!(loaded.resource::<Health>(), Some(&Health(42)));
+        assert_eq!(loaded.resource::<Target>(), Some(&Target(entities[0])));
+    }
+
+    #[test]
+    fn unknown_component_is_an_error() {
+        let mut world = registered_world();
+        let hero = world
+            .spawn()
+            .insert(Health(1))
+            .insert(Name::new("hero"))
+            .id();
+        let json = world.save_scene().unwrap();
+
+        let mut loaded = World::new();
+        let err = loaded.load_scene(&json).unwrap_err();
+
+        assert!(matches!(err, SceneError::UnknownComponent { ref name, .. } if name == "Health"));
+        assert_eq!(
+            err.to_string(),
+            format!("{} \"hero\": unknown component 'Health'", hero)
+        );
+        assert_eq!(loaded.entity_count(), 0);
+    }
+
+    #[test]
+    fn invalid_component_is_an_error() {
+        let mut loaded = registered_world();
+        let json = r#"{ "entities": [
+            { "entity": { "id": 0, "generation": 0 }, "components": { "Health": "lots" } }
+        ] }"#;
+
+        let err = loaded.load_scene(json).unwrap_err();
+        assert!(matches!(err, SceneError::InvalidComponent { .. }));
+        assert_eq!(loaded.entity_count(), 0);
+    }
+
+    #[test]
+    fn malformed_json_is_an_error() {
+        let mut loaded = registered_world();
+        assert!(matches!(
+            loaded.load_scene("{ not json"),
+            Err(SceneError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn duplicate_entity_ids_are_an_error() {
+        let mut world = registered_world();
+        world.spawn().insert(Health(1));
+        let mut scene = world.to_scene().unwrap();
+        scene.entities.push(scene.entities[0].clone());
+
+        let mut loaded = registered_world();
+        assert!(matches!(
+            loaded.spawn_scene(&scene),
+            Err(SceneError::DuplicateEntity { .. })
+        ));
+        assert_eq!(loaded.entity_count(), 0);
+    }
+}
@@ -0,0 +1,646 @@
+//! Binary world snapshots for save games and rollback
+//!
+//! A snapshot captures the complete entity table (IDs, generations, free
+//! list), tags, hierarchy, registered relations, every component type
+//! registered via `register_serializable` and every resource registered via
+//! `register_serializable_resource`. Restoring it recreates identical
+//! entity handles, so entities saved elsewhere (network messages, replays)
+//! stay valid.
+//!
+//! # Format
+//! `KONS` magic, a little-endian `u16` format version, then a bincode body
+//! with one section per registered component type and resource. Sections and entries are
+//! sorted, so equal worlds produce byte-identical snapshots.
+//!
+//! # Example
+//! ```ignore
+//! let quicksave = world.snapshot()?;
+//!
+//! // ... simulate ahead, then roll back
+//! world.restore(&quicksave)?;
+//!
+//! // Compare with a remote peer to detect desyncs
+//! if world.checksum()? != remote_checksum {
+//!     log::error!("Desync detected");
+//! }
+//! ```
+
+use crate::World;
+use crate::entity::Entity;
+use crate::scene::{BoxedComponent, EntityMapper};
+use serde::{Deserialize, Serialize};
+use std::any::TypeId;
+use std::collections::HashSet;
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+const MAGIC: &[u8; 4] = b"KONS";
+
+/// Current snapshot format version
+pub const SNAPSHOT_VERSION: u16 = 2;
+
+/// Errors produced while creating or restoring a snapshot
+#[derive(Debug)]
+pub enum SnapshotError {
+    /// The blob doesn't start with the snapshot magic bytes
+    InvalidHeader,
+    /// The blob was written by an unsupported format version
+    UnsupportedVersion(u16),
+    /// The snapshot body is truncated or malformed
+    Corrupt(bincode::Error),
+    /// A component section names a type not registered in this World
+    UnknownComponent(String),
+    /// A relation section names a type not registered in this World
+    UnknownRelation(String),
+    /// A resource section names a type not registered in this World
+    UnknownResource(String),
+    /// A component value doesn't match its registered type
+    InvalidComponent {
+        entity: Entity,
+        name: String,
+        source: bincode::Error,
+    },
+    /// A resource value doesn't match its registered type
+    InvalidResource {
+        name: String,
+        source: bincode::Error,
+    },
+    /// Command buffers of this World still hold reserved entities, which
+    /// restoring the entity allocator would hand out a second time
+    PendingReservations,
+    /// A component failed to serialize
+    Serialize {
+        name: String,
+        source: bincode::Error,
+    },
+}
+
+impl fmt::Display for SnapshotError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotError::InvalidHeader => write!(f, "not a world snapshot"),
+            SnapshotError::UnsupportedVersion(version) => {
+                write!(f, "unsupported snapshot version {}", version)
+            }
+            SnapshotError::Corrupt(err) => write!(f, "corrupt snapshot: {}", err),
+            SnapshotError::UnknownComponent(name) => write!(f, "unknown component '{}'", name),
+            SnapshotError::UnknownRelation(name) => write!(f, "unknown relation '{}'", name),
+            SnapshotError::UnknownResource(name) => write!(f, "unknown resource '{}'", name),
+            SnapshotError::InvalidComponent {
+                entity,
+                name,
+                source,
+            } => write!(f, "{}: invalid component '{}': {}", entity, name, source),
+            SnapshotError::InvalidResource { name, source } => {
+                write!(f, "invalid resource '{}': {}", name, source)
+            }
+            SnapshotError::PendingReservations => write!(
+                f,
+                "command buffers hold reserved entities; apply or drop them before restoring"
+            ),
+            SnapshotError::Serialize { name, source } => {
+                write!(f, "failed to serialize component '{}': {}", name, source)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SnapshotError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SnapshotError::Corrupt(err)
+            | SnapshotError::InvalidComponent { source: err, .. }
+            | SnapshotError::InvalidResource { source: err, .. }
+            | SnapshotError::Serialize { source: err, .. } => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Snapshot body, encoded with bincode after the header
+#[derive(Serialize, Deserialize)]
+struct SnapshotBody {
+    next_id: u32,
+    generations: Vec<u32>,
+    /// Alive entity IDs, ascending
+    alive: Vec<u32>,
+    /// Free list in pop order, so restored worlds reuse IDs identically
+    free_ids: Vec<u32>,
+    /// (entity ID, tag names) for tagged entities
+    tags: Vec<(u32, Vec<String>)>,
+    /// (entity ID, children in order) for parents
+    children: Vec<(u32, Vec<Entity>)>,
+    relations: Vec<RelationSection>,
+    components: Vec<ComponentSection>,
+    /// (resource name, encoded resource) pairs, ascending by name
+    resources: Vec<(String, Vec<u8>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct RelationSection {
+    name: String,
+    /// (source, targets) pairs, ascending by source ID
+    links: Vec<(Entity, Vec<Entity>)>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ComponentSection {
+    name: String,
+    /// (entity ID, encoded component) pairs, ascending by entity ID
+    entries: Vec<(u32, Vec<u8>)>,
+}
+
+/// FNV-1a 64-bit hash
+fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    bytes.iter().fold(OFFSET, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(PRIME)
+    })
+}
+
+impl World {
+    /// Captures the complete World state as a compact binary blob
+    ///
+    /// Only component types registered via `register_serializable` (and
+    /// relations and resources registered via `register_serializable_relation`
+    /// and `register_serializable_resource`) are stored.
+    pub fn snapshot(&self) -> Result<Vec<u8>, SnapshotError> {
+        let mut alive: Vec<u32> = self.alive.iter().copied().collect();
+        alive.sort_unstable();
+
+        let entity = |id: u32| Entity::new(id, self.generations[id as usize]);
+
+        let tags = alive
+            .iter()
+            .map(|&id| {
+                let mut tags = self.get_entity_tags(id);
+                tags.sort_unstable();
+                (id, tags)
+            })
+            .filter(|(_, tags)| !tags.is_empty())
+            .collect();
+
+        let children = alive
+            .iter()
+            .map(|&id| (id, self.children(entity(id)).to_vec()))
+            .filter(|(_, children)| !children.is_empty())
+            .collect();
+
+        let relations = self
+            .scene_registry
+            .relations
+            .iter()
+            .map(|(name, (type_id, _))| RelationSection {
+                name: name.clone(),
+                links: alive
+                    .iter()
+                    .map(|&id| {
+                        (
+                            entity(id),
+                            self.targets_by_type_id(type_id, entity(id)).to_vec(),
+                        )
+                    })
+                    .filter(|(_, targets)| !targets.is_empty())
+                    .collect(),
+            })
+            .collect();
+
+        let mut components = Vec::new();
+        for (name, (_, serde)) in &self.scene_registry.components {
+            let mut entries = Vec::new();
+            for &id in &alive {
+                if let Some(encoded) = (serde.encode)(self, entity(id)) {
+                    let bytes = encoded.map_err(|source| SnapshotError::Serialize {
+                        name: name.clone(),
+                        source,
+                    })?;
+                    entries.push((id, bytes));
+                }
+            }
+
+            components.push(ComponentSection {
+                name: name.clone(),
+                entries,
+            });
+        }
+
+        let mut resources = Vec::new();
+        for (name, (_, serde)) in &self.scene_registry.resources {
+            if let Some(encoded) = (serde.encode)(self) {
+                let bytes = encoded.map_err(|source| SnapshotError::Serialize {
+                    name: name.clone(),
+                    source,
+                })?;
+                resources.push((name.clone(), bytes));
+            }
+        }
+
+        let body = SnapshotBody {
+            next_id: self.next_id.load(Ordering::Relaxed),
+            generations: self.generations.clone(),
+            alive,
+            free_ids: self.free_ids.clone(),
+            tags,
+            children,
+            relations,
+            components,
+            resources,
+        };
+
+        let mut blob = Vec::new();
+        blob.extend_from_slice(MAGIC);
+        blob.extend_from_slice(&SNAPSHOT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut blob, &body).map_err(|source| SnapshotError::Serialize {
+            name: "Snapshot".to_string(),
+            source,
+        })?;
+
+        Ok(blob)
+    }
+
+    /// Replaces the World state with a snapshot
+    ///
+    /// Entity IDs, generations and the free list are restored exactly.
+    /// The blob is fully decoded first, so on error the World is unchanged.
+    /// Existing entities are destroyed (running `on_remove` hooks) and
+    /// restored components are inserted normally (running `on_add` hooks).
+    /// Registered resources missing from the snapshot are removed.
+    /// Registrations, hooks, unregistered resources and non-serializable
+    /// components of other entities are not part of the snapshot.
+    ///
+    /// Fails with `PendingReservations` while a command buffer that reserved
+    /// entities via `Commands::spawn` is still alive or deferred.
+    pub fn restore(&mut self, blob: &[u8]) -> Result<(), SnapshotError> {
+        if self.has_pending_reservations() {
+            return Err(SnapshotError::PendingReservations);
+        }
+
+        let body = decode_body(blob)?;
+
+        // Decode every component before touching the World
+        let mut decoded: Vec<(u32, BoxedComponent, usize)> = Vec::new();
+        let mut inserts = Vec::with_capacity(body.components.len());
+        for section in &body.components {
+            let (_, serde) = self
+                .scene_registry
+                .components
+                .get(&section.name)
+                .ok_or_else(|| SnapshotError::UnknownComponent(section.name.clone()))?;
+
+            for (id, bytes) in &section.entries {
+                let entity = Entity::new(
+                    *id,
+                    body.generations.get(*id as usize).copied().unwrap_or(0),
+                );
+                let component =
+                    (serde.decode)(bytes).map_err(|source| SnapshotError::InvalidComponent {
+                        entity,
+                        name: section.name.clone(),
+                        source,
+                    })?;
+                decoded.push((*id, component, inserts.len()));
+            }
+            inserts.push(serde.insert);
+        }
+
+        let mut relates = Vec::with_capacity(body.relations.len());
+        for section in &body.relations {
+            let (_, serde) = self
+                .scene_registry
+                .relations
+                .get(&section.name)
+                .ok_or_else(|| SnapshotError::UnknownRelation(section.name.clone()))?;
+            relates.push(serde.relate);
+        }
+
+        let mut resources = Vec::with_capacity(body.resources.len());
+        for (name, bytes) in &body.resources {
+            let (_, serde) = self
+                .scene_registry
+                .resources
+                .get(name)
+                .ok_or_else(|| SnapshotError::UnknownResource(name.clone()))?;
+
+            let resource =
+                (serde.decode)(bytes).map_err(|source| SnapshotError::InvalidResource {
+                    name: name.clone(),
+                    source,
+                })?;
+            resources.push((serde.insert, resource));
+        }
+
+        self.clear();
+
+        let registered: Vec<TypeId> = self
+            .scene_registry
+            .resources
+            .values()
+            .map(|(type_id, _)| *type_id)
+            .collect();
+        for type_id in &registered {
+            self.resources.remove_boxed(type_id);
+        }
+
+        self.next_id.store(body.next_id, Ordering::Relaxed);
+        self.generations = body.generations;
+        self.alive = body.alive.into_iter().collect();
+        self.free_ids = body.free_ids;
+
+        let entity = |world: &World, id: u32| Entity::new(id, world.generations[id as usize]);
+
+        for (id, tags) in &body.tags {
+            let entity = entity(self, *id);
+            for tag in tags {
+                self.tag(entity, tag);
+            }
+        }
+
+        let mapper = EntityMapper::identity();
+        for (insert, resource) in resources {
+            insert(self, resource, &mapper);
+        }
+
+        for (id, component, insert) in decoded {
+            let entity = entity(self, id);
+            inserts[insert](self, entity, component, &mapper);
+        }
+
+        for (id, children) in &body.children {
+            let parent = entity(self, *id);
+            for &child in children {
+                self.set_parent(child, parent);
+            }
+        }
+
+        for (section, relate) in body.relations.iter().zip(relates) {
+            for (source, targets) in &section.links {
+                for &target in targets {
+                    relate(self, *source, target);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks for entity IDs reserved by a command buffer that may still be applied
+    ///
+    /// Reserved IDs only get a generation slot once applied. The World and
+    /// its deferred buffer each hold one handle to the allocator; any other
+    /// handle is a live `Commands` buffer.
+    fn has_pending_reservations(&self) -> bool {
+        let unapplied = self.next_id.load(Ordering::Relaxed) as usize > self.generations.len();
+        let buffers = Arc::strong_count(&self.next_id) > 2 || !self.deferred.is_empty();
+        unapplied && buffers
+    }
+
+    /// Returns a deterministic FNV-1a checksum of the World state
+    ///
+    /// Covers everything a snapshot covers. Two Worlds with equal checksums
+    /// are in sync for all registered component types.
+    pub fn checksum(&self) -> Result<u64, SnapshotError> {
+        self.snapshot().map(|blob| fnv1a(&blob))
+    }
+}
+
+fn corrupt(message: &str) -> SnapshotError {
+    SnapshotError::Corrupt(Box::new(bincode::ErrorKind::Custom(message.to_string())))
+}
+
+fn decode_body(blob: &[u8]) -> Result<SnapshotBody, SnapshotError> {
+    if blob.len() < MAGIC.len() + 2 || &blob[..MAGIC.len()] != MAGIC {
+        return Err(SnapshotError::InvalidHeader);
+    }
+
+    let version = u16::from_le_bytes([blob[4], blob[5]]);
+    if version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(version));
+    }
+
+    let body: SnapshotBody = bincode::deserialize(&blob[6..]).map_err(SnapshotError::Corrupt)?;
+
+    let in_range = |id: &u32| (*id as usize) < body.generations.len();
+    if !body.alive.iter().all(in_range) || !body.free_ids.iter().all(in_range) {
+        return Err(corrupt("entity ID out of range"));
+    }
+
+    // Every entity referenced by a section must be alive in the snapshot
+    let alive: HashSet<u32> = body.alive.iter().copied().collect();
+    let is_alive = |id: u32| alive.contains(&id);
+    let is_alive_entity = |entity: &Entity| {
+        is_alive(entity.id()) && body.generations[entity.id() as usize] == entity.generation()
+    };
+
+    let valid = body.tags.iter().all(|(id, _)| is_alive(*id))
+        && body
+            .children
+            .iter()
+            .all(|(id, children)| is_alive(*id) && children.iter().all(is_alive_entity))
+        && body.relations.iter().all(|section| {
+            section.links.iter().all(|(source, targets)| {
+                is_alive_entity(source) && targets.iter().all(is_alive_entity)
+            })
+        })
+        && body
+            .components
+            .iter()
+            .all(|section| section.entries.iter().all(|(id, _)| is_alive(*id)));
+    if !valid {
+        return Err(corrupt("reference to an entity that is not alive"));
+    }
+
+    Ok(body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::relation::Relation;
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Health(i32);
+
+    #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    struct Targets;
+    impl Relation for Targets {}
+
+    fn registered_world() -> World {
+        let mut world = World::new();
+        world.register_serializable::<Health>("Health");
+        world.register_serializable::<Position>("Position");
+        world.register_serializable_relation::<Targets>("Targets");
+        world
+    }
+
+    #[test]
+    fn restore_recreates_identical_entities() {
+        let mut world = registered_world();
+        let a = world.spawn().insert(Health(10)).tag("enemy").id();
+        let dead = world.spawn().id();
+        let b = world
+            .spawn()
+            .insert(Position { x: 1.0, y: 2.0 })
+            .insert(Health(5))
+            .id();
+        world.destroy(dead);
+        world.set_parent(b, a);
+        world.relate::<Targets>(a, b);
+
+        let blob = world.snapshot().unwrap();
+
+        let mut restored = registered_world();
+        restored.spawn().insert(Health(999));
+        restored.restore(&blob).unwrap();
+
+        assert_eq!(restored.entity_count(), 2);
+        assert_eq!(restored.get::<Health>(a), Some(&Health(10)));
+        assert_eq!(restored.get::<Health>(b), Some(&Health(5)));
+        assert!(restored.has_tag(a, "enemy"));
+        assert_eq!(restored.children(a), &[b]);
+        assert!(restored.has_relation::<Targets>(a, b));
+
+        // The free list is restored, so the next spawn reuses the same ID
+        assert_eq!(world.spawn().id(), restored.spawn().id());
+    }
+
+    #[test]
+    fn rollback_restores_previous_state() {
+        let mut world = registered_world();
+        let entity = world.spawn().insert(Health(100)).id();
+        let blob = world.snapshot().unwrap();
+        let before = world.checksum().unwrap();
+
+        world.get_mut::<Health>(entity).unwrap().0 -= 30;
+        world.spawn().insert(Health(1));
+        assert_ne!(world.checksum().unwrap(), before);
+
+        world.restore(&blob).unwrap();
+
+        assert_eq!(world.get::<Health>(entity), Some(&Health(100)));
+        assert_eq!(world.entity_count(), 1);
+        assert_eq!(world.checksum().unwrap(), before);
+    }
+
+    #[test]
+    fn rollback_restores_resources() {
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Score(u32);
+
+        #[derive(Debug, PartialEq, Serialize, Deserialize)]
+        struct Wave(u32);
+
+        let mut world = registered_world();
+        world.register_serializable_resource::<Score>("Score");
+        world.register_serializable_resource::<Wave>("Wave");
+        world.insert_resource(Score(10));
+        world.insert_resource("not registered");
+        let blob = world.snapshot().unwrap();
+
+        world.resource_mut::<Score>().unwrap().0 = 99;
+        world.insert_resource(Wave(2));
+        world.restore(&blob).unwrap();
+
+        assert_eq!(world.resource::<Score>(), Some(&Score(10)));
+        assert!(!world.has_resource::<Wave>());
+        assert_eq!(world.resource::<&str>(), Some(&"not registered"));
+    }
+
+    #[test]
+    fn checksum_matches_for_equal_worlds() {
+        let build = || {
+            let mut world = registered_world();
+            for i in 0..10 {
+                world.spawn().insert(Health(i)).tag("unit");
+            }
+            world
+        };
+
+        assert_eq!(build().checksum().unwrap(), build().checksum().unwrap());
+    }
+
+    #[test]
+    fn invalid_blobs_are_rejected() {
+        let mut world = registered_world();
+        world.spawn().insert(Health(1));
+        let mut blob = world.snapshot().unwrap();
+
+        assert!(matches!(
+            world.restore(b"nope"),
+            Err(SnapshotError::InvalidHeader)
+        ));
+
+        let mut future = blob.clone();
+        future[4] = 99;
+        assert!(matches!(
+            world.restore(&future),
+            Err(SnapshotError::UnsupportedVersion(99))
+        ));
+
+        blob.truncate(blob.len() - 3);
+        assert!(matches!(
+            world.restore(&blob),
+            Err(SnapshotError::Corrupt(_))
+        ));
+        assert_eq!(world.entity_count(), 1);
+    }
+
+    #[test]
+    fn dangling_entity_ids_are_rejected() {
+        let mut world = registered_world();
+        let entity = world.spawn().insert(Health(1)).id();
+        let blob = world.snapshot().unwrap();
+
+        let mut body = decode_body(&blob).unwrap();
+        body.tags.push((1000, vec!["ghost".to_string()]));
+        let mut corrupt = blob[..6].to_vec();
+        bincode::serialize_into(&mut corrupt, &body).unwrap();
+
+        assert!(matches!(
+            world.restore(&corrupt),
+            Err(SnapshotError::Corrupt(_))
+        ));
+        assert_eq!(world.get::<Health>(entity), Some(&Health(1)));
+    }
+
+    #[test]
+    fn restore_fails_while_entities_are_reserved() {
+        let mut world = registered_world();
+        let blob = world.snapshot().unwrap();
+
+        let mut commands = world.commands();
+        let reserved = commands.spawn().id();
+        assert!(matches!(
+            world.restore(&blob),
+            Err(SnapshotError::PendingReservations)
+        ));
+
+        world.push_commands(commands);
+        assert!(world.restore(&blob).is_err());
+
+        world.apply_deferred();
+        assert!(world.is_alive(reserved));
+        world.restore(&blob).unwrap();
+        assert_eq!(world.entity_count(), 0);
+    }
+
+    #[test]
+    fn unregistered_component_is_rejected() {
+        let mut world = registered_world();
+        world.spawn().insert(Health(1));
+        let blob = world.snapshot().unwrap();
+
+        let mut other = World::new();
+        assert!(matches!(
+            other.restore(&blob),
+            Err(SnapshotError::UnknownComponent(_))
+        ));
+    }
+}
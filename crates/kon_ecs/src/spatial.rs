@@ -0,0 +1,689 @@
+//! Spatial hash over entities with a 2D position
+//!
+//! `enable_spatial_hash::<P>(cell_size)` stores a `SpatialHash<P>` resource in
+//! the World indexing every entity with the position component `P`. The hash
+//! is refreshed from change detection: components changed since the last
+//! refresh are re-bucketed and removed components are dropped. A refresh
+//! still compares the change ticks of every `P` in one linear pass, but only
+//! entities that moved are hashed and re-bucketed.
+//!
+//! `clear_trackers()` (called each frame by `EcsPlugin`) refreshes every
+//! enabled hash, so queries see positions as of the end of the last frame.
+//! Call `update_spatial_hash::<P>()` first to include movement of the
+//! current frame.
+//!
+//! # Example
+//! ```ignore
+//! impl SpatialPosition for Position {
+//!     fn position(&self) -> (f32, f32) {
+//!         (self.x, self.y)
+//!     }
+//! }
+//!
+//! world.enable_spatial_hash::<Position>(64.0);
+//!
+//! let hash = world.spatial_hash::<Position>().unwrap();
+//! let nearby = hash.query_radius((player.x, player.y), 200.0);
+//! let closest = hash.nearest_k((player.x, player.y), 3);
+//! let hits = hash.raycast((0.0, 0.0), (1.0, 0.0), 500.0, 8.0);
+//! ```
+
+use crate::entity::Entity;
+use crate::storage::SparseSet;
+use crate::{Component, World};
+use std::any::TypeId;
+use std::collections::{HashMap, HashSet};
+use std::marker::PhantomData;
+
+/// Component holding a 2D position that can be indexed by a `SpatialHash`
+pub trait SpatialPosition: Component {
+    fn position(&self) -> (f32, f32);
+}
+
+type Cell = (i32, i32);
+
+/// Distances along a ray where it enters and leaves the occupied cells
+struct RaySpan {
+    enter: f32,
+    exit: f32,
+    /// Upper bound of cells walked between `enter` and `exit`
+    cells: u64,
+}
+
+/// Refreshes the spatial hash of one position component type
+pub(crate) type SpatialUpdater = fn(&mut World);
+
+/// Uniform grid of entity positions
+///
+/// Entities are points bucketed into square cells of `cell_size`. Pick a
+/// cell size close to the typical query radius.
+pub struct SpatialHash<P> {
+    cell_size: f32,
+    cells: HashMap<Cell, Vec<Entity>>,
+    positions: HashMap<Entity, (f32, f32)>,
+    /// World tick of the last refresh, components changed since are re-bucketed
+    last_tick: u32,
+    _marker: PhantomData<fn() -> P>,
+}
+
+impl<P> SpatialHash<P> {
+    /// # Panics
+    /// Panics if `cell_size` is not positive.
+    pub fn new(cell_size: f32) -> Self {
+        assert!(cell_size > 0.0, "Spatial hash cell size must be positive");
+
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+            positions: HashMap::new(),
+            last_tick: 0,
+            _marker: PhantomData,
+        }
+    }
+
+    pub fn cell_size(&self) -> f32 {
+        self.cell_size
+    }
+
+    /// Returns the number of indexed entities
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Returns the indexed position of an entity
+    pub fn position(&self, entity: Entity) -> Option<(f32, f32)> {
+        self.positions.get(&entity).copied()
+    }
+
+    /// Inserts or moves an entity
+    pub fn insert(&mut self, entity: Entity, position: (f32, f32)) {
+        let cell = self.cell(position);
+
+        if let Some(old) = self.positions.insert(entity, position) {
+            let old_cell = self.cell(old);
+            if old_cell == cell {
+                return;
+            }
+            self.remove_from_cell(old_cell, entity);
+        }
+
+        self.cells.entry(cell).or_default().push(entity);
+    }
+
+    /// Removes an entity, returns false if it wasn't indexed
+    pub fn remove(&mut self, entity: Entity) -> bool {
+        match self.positions.remove(&entity) {
+            Some(position) => {
+                self.remove_from_cell(self.cell(position), entity);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.positions.clear();
+    }
+
+    /// Returns entities inside the box spanned by `min` and `max` (inclusive)
+    ///
+    /// Results are in no particular order.
+    pub fn query_aabb(&self, min: (f32, f32), max: (f32, f32)) -> Vec<Entity> {
+        self.collect_in_box(min, max, |position| {
+            (min.0..=max.0).contains(&position.0) && (min.1..=max.1).contains(&position.1)
+        })
+    }
+
+    /// Returns entities within `radius` of `center` (inclusive)
+    ///
+    /// Results are in no particular order.
+    pub fn query_radius(&self, center: (f32, f32), radius: f32) -> Vec<Entity> {
+        let min = (center.0 - radius, center.1 - radius);
+        let max = (center.0 + radius, center.1 + radius);
+
+        self.collect_in_box(min, max, |position| {
+            distance_squared(position, center) <= radius * radius
+        })
+    }
+
+    /// Returns up to `k` entities closest to `point`, nearest first
+    ///
+    /// Searches rings of cells outwards from `point` and stops once no
+    /// unvisited cell can contain a closer entity.
+    pub fn nearest_k(&self, point: (f32, f32), k: usize) -> Vec<Entity> {
+        if k == 0 || self.is_empty() {
+            return Vec::new();
+        }
+
+        let (cx, cy) = self.cell(point);
+        let mut found: Vec<(f32, Entity)> = Vec::new();
+
+        for ring in 0i32.. {
+            // Far away entities: scanning every cell is cheaper than more rings
+            if ring as usize * 8 > self.cells.len() {
+                found = self
+                    .positions
+                    .iter()
+                    .map(|(&entity, &position)| (distance_squared(position, point), entity))
+                    .collect();
+                break;
+            }
+
+            for cell in ring_cells(cx, cy, ring) {
+                for &entity in self.cells.get(&cell).into_iter().flatten() {
+                    found.push((distance_squared(self.positions[&entity], point), entity));
+                }
+            }
+
+            if found.len() == self.len() {
+                break;
+            }
+
+            // Unvisited cells are at least `ring` cells away from `point`
+            if found.len() >= k {
+                found.sort_by(|a, b| a.0.total_cmp(&b.0));
+                let reach = ring as f32 * self.cell_size;
+                if found[k - 1].0 <= reach * reach {
+                    break;
+                }
+            }
+        }
+
+        found.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.id().cmp(&b.1.id())));
+        found.truncate(k);
+        found.into_iter().map(|(_, entity)| entity).collect()
+    }
+
+    /// Returns entities hit by a ray, nearest first
+    ///
+    /// Entities are treated as circles of `radius`. The ray starts at
+    /// `origin`, points along `direction` (any length) and ends after
+    /// `max_distance`, which must be finite. Walks only the cells along the
+    /// ray that lie within the bounds of the occupied cells.
+    pub fn raycast(
+        &self,
+        origin: (f32, f32),
+        direction: (f32, f32),
+        max_distance: f32,
+        radius: f32,
+    ) -> Vec<Entity> {
+        let length = direction.0.hypot(direction.1);
+        if length == 0.0
+            || !length.is_finite()
+            || !(0.0..f32::INFINITY).contains(&max_distance)
+            || !origin.0.is_finite()
+            || !origin.1.is_finite()
+            || self.is_empty()
+        {
+            return Vec::new();
+        }
+        let dir = (direction.0 / length, direction.1 / length);
+
+        // Neighbor cells to check around each visited cell so circles
+        // overlapping the ray from an adjacent cell are found
+        let reach = (radius.max(0.0) / self.cell_size).ceil() as i32;
+
+        // Only walk the part of the ray crossing occupied cells
+        let Some(span) = self.occupied_span(origin, dir, reach) else {
+            return Vec::new();
+        };
+        if span.enter > max_distance {
+            return Vec::new();
+        }
+        let start = (origin.0 + dir.0 * span.enter, origin.1 + dir.1 * span.enter);
+
+        // Distances below are measured from `start` to keep them precise
+        let walk = span.exit.min(max_distance) - span.enter;
+
+        let (mut x, mut y) = self.cell(start);
+        let step = (dir.0.signum() as i32, dir.1.signum() as i32);
+        let boundary = |cell: i32, start: f32, dir: f32| {
+            if dir > 0.0 {
+                ((cell + 1) as f32 * self.cell_size - start) / dir
+            } else if dir < 0.0 {
+                (cell as f32 * self.cell_size - start) / dir
+            } else {
+                f32::INFINITY
+            }
+        };
+        let mut t_max = (boundary(x, start.0, dir.0), boundary(y, start.1, dir.1));
+        let t_delta = (self.cell_size / dir.0.abs(), self.cell_size / dir.1.abs());
+
+        let mut visited = HashSet::new();
+        let mut hits: Vec<(f32, Entity)> = Vec::new();
+
+        for _ in 0..=span.cells {
+            for nx in x - reach..=x + reach {
+                for ny in y - reach..=y + reach {
+                    if !visited.insert((nx, ny)) {
+                        continue;
+                    }
+
+                    for &entity in self.cells.get(&(nx, ny)).into_iter().flatten() {
+                        let center = self.positions[&entity];
+                        if let Some(t) = ray_circle(origin, dir, center, radius, max_distance) {
+                            hits.push((t, entity));
+                        }
+                    }
+                }
+            }
+
+            if t_max.0.min(t_max.1) > walk {
+                break;
+            }
+
+            if t_max.0 < t_max.1 {
+                x += step.0;
+                t_max.0 += t_delta.0;
+            } else {
+                y += step.1;
+                t_max.1 += t_delta.1;
+            }
+        }
+
+        hits.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.id().cmp(&b.1.id())));
+        hits.into_iter().map(|(_, entity)| entity).collect()
+    }
+
+    /// Part of a ray crossing the bounds of the occupied cells, grown by
+    /// `reach` cells
+    fn occupied_span(&self, origin: (f32, f32), dir: (f32, f32), reach: i32) -> Option<RaySpan> {
+        let mut cells = self.cells.keys();
+        let &(x, y) = cells.next()?;
+        let (min, max) = cells.fold(((x, y), (x, y)), |(min, max), &(x, y)| {
+            ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y)))
+        });
+
+        let mut span = (0.0f32, f32::INFINITY);
+        for (origin, dir, min, max) in [
+            (origin.0, dir.0, min.0, max.0),
+            (origin.1, dir.1, min.1, max.1),
+        ] {
+            let low = min.saturating_sub(reach) as f32 * self.cell_size;
+            let high = max.saturating_add(reach).saturating_add(1) as f32 * self.cell_size;
+
+            if dir == 0.0 {
+                if origin < low || origin > high {
+                    return None;
+                }
+                continue;
+            }
+
+            let (a, b) = ((low - origin) / dir, (high - origin) / dir);
+            span.0 = span.0.max(a.min(b));
+            span.1 = span.1.min(a.max(b));
+        }
+
+        // A walk inside the bounds crosses at most every column and row once
+        let cells =
+            (max.0 as i64 - min.0 as i64) + (max.1 as i64 - min.1 as i64) + 4 * reach as i64 + 2;
+
+        (span.0 <= span.1).then_some(RaySpan {
+            enter: span.0,
+            exit: span.1,
+            cells: cells as u64,
+        })
+    }
+
+    fn cell(&self, position: (f32, f32)) -> Cell {
+        (
+            (position.0 / self.cell_size).floor() as i32,
+            (position.1 / self.cell_size).floor() as i32,
+        )
+    }
+
+    fn remove_from_cell(&mut self, cell: Cell, entity: Entity) {
+        if let Some(entities) = self.cells.get_mut(&cell) {
+            if let Some(index) = entities.iter().position(|e| *e == entity) {
+                entities.swap_remove(index);
+            }
+            if entities.is_empty() {
+                self.cells.remove(&cell);
+            }
+        }
+    }
+
+    fn collect_in_box(
+        &self,
+        min: (f32, f32),
+        max: (f32, f32),
+        inside: impl Fn((f32, f32)) -> bool,
+    ) -> Vec<Entity> {
+        let (min_x, min_y) = self.cell(min);
+        let (max_x, max_y) = self.cell(max);
+        let width = max_x as i64 - min_x as i64 + 1;
+        let height = max_y as i64 - min_y as i64 + 1;
+        let box_cells = width.checked_mul(height).unwrap_or(i64::MAX);
+
+        // Boxes covering more cells than are occupied scan the entities instead
+        if box_cells > self.cells.len() as i64 {
+            return self
+                .positions
+                .iter()
+                .filter(|(_, position)| inside(**position))
+                .map(|(entity, _)| *entity)
+                .collect();
+        }
+
+        let mut result = Vec::new();
+        for x in min_x..=max_x {
+            for y in min_y..=max_y {
+                for &entity in self.cells.get(&(x, y)).into_iter().flatten() {
+                    if inside(self.positions[&entity]) {
+                        result.push(entity);
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+fn distance_squared(a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (a.0 - b.0, a.1 - b.1);
+    dx * dx + dy * dy
+}
+
+/// Cells on the border of the square `ring` cells away from the center
+fn ring_cells(cx: i32, cy: i32, ring: i32) -> Vec<Cell> {
+    if ring == 0 {
+        return vec![(cx, cy)];
+    }
+
+    let mut cells = Vec::with_capacity(ring as usize * 8);
+    for dx in -ring..=ring {
+        cells.push((cx + dx, cy - ring));
+        cells.push((cx + dx, cy + ring));
+    }
+    for dy in -ring + 1..ring {
+        cells.push((cx - ring, cy + dy));
+        cells.push((cx + ring, cy + dy));
+    }
+    cells
+}
+
+/// Distance along a normalized ray to where it enters a circle
+fn ray_circle(
+    origin: (f32, f32),
+    dir: (f32, f32),
+    center: (f32, f32),
+    radius: f32,
+    max_distance: f32,
+) -> Option<f32> {
+    let to_center = (center.0 - origin.0, center.1 - origin.1);
+    let along = to_center.0 * dir.0 + to_center.1 * dir.1;
+    let offset = distance_squared(to_center, (0.0, 0.0)) - along * along;
+    if offset > radius * radius {
+        return None;
+    }
+
+    let half_chord = (radius * radius - offset).max(0.0).sqrt();
+    let (enter, exit) = (along - half_chord, along + half_chord);
+    if exit < 0.0 || enter > max_distance {
+        return None;
+    }
+
+    Some(enter.max(0.0))
+}
+
+fn update_typed<P: SpatialPosition>(world: &mut World) {
+    world.update_spatial_hash::<P>();
+}
+
+impl World {
+    /// Starts maintaining a `SpatialHash` of all entities with `P`
+    ///
+    /// Replaces an existing hash for `P` and indexes all current entities.
+    pub fn enable_spatial_hash<P: SpatialPosition>(&mut self, cell_size: f32) {
+        self.insert_resource(SpatialHash::<P>::new(cell_size));
+
+        let type_id = TypeId::of::<P>();
+        if !self.spatial_updaters.iter().any(|(id, _)| *id == type_id) {
+            self.spatial_updaters.push((type_id, update_typed::<P>));
+        }
+
+        self.update_spatial_hash::<P>();
+    }
+
+    /// Stops maintaining the `SpatialHash` of `P` and drops it
+    pub fn disable_spatial_hash<P: SpatialPosition>(&mut self) {
+        let type_id = TypeId::of::<P>();
+        self.spatial_updaters.retain(|(id, _)| *id != type_id);
+        self.remove_resource::<SpatialHash<P>>();
+    }
+
+    /// Returns the spatial hash of `P`, if enabled
+    pub fn spatial_hash<P: SpatialPosition>(&self) -> Option<&SpatialHash<P>> {
+        self.resource()
+    }
+
+    /// Brings the spatial hash of `P` up to date
+    ///
+    /// Re-buckets components changed since the last refresh and drops
+    /// entities that lost `P` this frame. Does nothing if the hash is not
+    /// enabled.
+    pub fn update_spatial_hash<P: SpatialPosition>(&mut self) {
+        if !self.has_resource::<SpatialHash<P>>() {
+            return;
+        }
+
+        // Changes made after this refresh get a later tick
+        let tick = self.advance_change_tick();
+        let type_id = TypeId::of::<P>();
+
+        // Borrow the hash and the component storage side by side
+        let Some(hash) = self.resources.get_mut::<SpatialHash<P>>() else {
+            return;
+        };
+
+        for &entity in self.removed.get(&type_id).into_iter().flatten() {
+            hash.remove(entity);
+        }
+
+        let set = self
+            .components
+            .get(&type_id)
+            .and_then(|s| s.as_any().downcast_ref::<SparseSet<P>>());
+        let generations = &self.generations;
+
+        match set {
+            Some(set) => {
+                for (id, component, ticks) in set.iter_with_ticks() {
+                    if ticks.is_changed(hash.last_tick, tick) {
+                        let entity = Entity::new(id, generations[id as usize]);
+                        hash.insert(entity, component.position());
+                    }
+                }
+
+                // Removals from earlier frames that were never refreshed
+                if hash.len() != set.len() {
+                    let stale: Vec<Entity> = hash
+                        .positions
+                        .keys()
+                        .filter(|e| {
+                            generations.get(e.id() as usize) != Some(&e.generation())
+                                || !set.contains(e.id())
+                        })
+                        .copied()
+                        .collect();
+                    for entity in stale {
+                        hash.remove(entity);
+                    }
+                }
+            }
+            None => hash.clear(),
+        }
+
+        hash.last_tick = tick;
+    }
+
+    /// Refreshes every enabled spatial hash
+    pub(crate) fn update_spatial_hashes(&mut self) {
+        for index in 0..self.spatial_updaters.len() {
+            let (_, update) = self.spatial_updaters[index];
+            update(self);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Position {
+        x: f32,
+        y: f32,
+    }
+
+    impl SpatialPosition for Position {
+        fn position(&self) -> (f32, f32) {
+            (self.x, self.y)
+        }
+    }
+
+    fn spawn_at(world: &mut World, x: f32, y: f32) -> Entity {
+        world.spawn().insert(Position { x, y }).id()
+    }
+
+    fn sorted(mut entities: Vec<Entity>) -> Vec<Entity> {
+        entities.sort_by_key(|e| e.id());
+        entities
+    }
+
+    #[test]
+    fn aabb_and_radius_queries() {
+        let mut world = World::new();
+        let a = spawn_at(&mut world, 0.0, 0.0);
+        let b = spawn_at(&mut world, 15.0, 5.0);
+        let c = spawn_at(&mut world, -30.0, 40.0);
+        world.enable_spatial_hash::<Position>(10.0);
+
+        let hash = world.spatial_hash::<Position>().unwrap();
+        assert_eq!(hash.len(), 3);
+        assert_eq!(
+            sorted(hash.query_aabb((-1.0, -1.0), (15.0, 5.0))),
+            vec![a, b]
+        );
+        assert_eq!(sorted(hash.query_radius((0.0, 0.0), 16.0)), vec![a, b]);
+        assert_eq!(hash.query_radius((-30.0, 40.0), 0.0), vec![c]);
+        assert_eq!(
+            hash.query_aabb((-1000.0, -1000.0), (1000.0, 1000.0)).len(),
+            3
+        );
+    }
+
+    #[test]
+    fn huge_and_unbounded_boxes() {
+        let mut world = World::new();
+        spawn_at(&mut world, 0.0, 0.0);
+        spawn_at(&mut world, -30.0, 40.0);
+        world.enable_spatial_hash::<Position>(1.0);
+
+        let hash = world.spatial_hash::<Position>().unwrap();
+        assert_eq!(hash.query_radius((0.0, 0.0), 1e12).len(), 2);
+        assert_eq!(
+            hash.query_aabb(
+                (f32::NEG_INFINITY, f32::NEG_INFINITY),
+                (f32::INFINITY, f32::INFINITY)
+            )
+            .len(),
+            2
+        );
+    }
+
+    #[test]
+    fn nearest_k_orders_by_distance() {
+        let mut world = World::new();
+        let far = spawn_at(&mut world, 500.0, 0.0);
+        let near = spawn_at(&mut world, 3.0, 4.0);
+        let mid = spawn_at(&mut world, -20.0, 0.0);
+        world.enable_spatial_hash::<Position>(8.0);
+
+        let hash = world.spatial_hash::<Position>().unwrap();
+        assert_eq!(hash.nearest_k((0.0, 0.0), 2), vec![near, mid]);
+        assert_eq!(hash.nearest_k((0.0, 0.0), 10), vec![near, mid, far]);
+        assert_eq!(hash.nearest_k((490.0, 0.0), 1), vec![far]);
+        assert!(hash.nearest_k((0.0, 0.0), 0).is_empty());
+    }
+
+    #[test]
+    fn raycast_hits_in_order() {
+        let mut world = World::new();
+        let second = spawn_at(&mut world, 50.0, 1.0);
+        let first = spawn_at(&mut world, 20.0, -1.0);
+        let behind = spawn_at(&mut world, -10.0, 0.0);
+        let beside = spawn_at(&mut world, 30.0, 20.0);
+        world.enable_spatial_hash::<Position>(10.0);
+
+        let hash = world.spatial_hash::<Position>().unwrap();
+        let hits = hash.raycast((0.0, 0.0), (2.0, 0.0), 100.0, 2.0);
+        assert_eq!(hits, vec![first, second]);
+        assert!(!hits.contains(&behind) && !hits.contains(&beside));
+
+        assert_eq!(hash.raycast((0.0, 0.0), (1.0, 0.0), 30.0, 2.0), vec![first]);
+        assert_eq!(hash.raycast((0.0, 0.0), (1.0, 1.0), 100.0, 2.0), Vec::new());
+
+        // Unbounded rays are rejected, far away origins skip the empty cells
+        assert!(
+            hash.raycast((0.0, 0.0), (1.0, 0.0), f32::INFINITY, 2.0)
+                .is_empty()
+        );
+        assert!(
+            hash.raycast((0.0, 0.0), (1.0, 0.0), f32::NAN, 2.0)
+                .is_empty()
+        );
+        assert_eq!(
+            hash.raycast((-1.0e5, 0.0), (1.0, 0.0), 1.0e12, 2.0),
+            vec![behind, first, second]
+        );
+    }
+
+    #[test]
+    fn follows_changes_removals_and_despawns() {
+        let mut world = World::new();
+        let mover = spawn_at(&mut world, 0.0, 0.0);
+        let doomed = spawn_at(&mut world, 5.0, 5.0);
+        let stripped = spawn_at(&mut world, 6.0, 6.0);
+        world.enable_spatial_hash::<Position>(10.0);
+        world.clear_trackers();
+
+        world.get_mut::<Position>(mover).unwrap().x = 100.0;
+        world.destroy(doomed);
+        world.remove::<Position>(stripped);
+        let added = spawn_at(&mut world, 1.0, 1.0);
+
+        // Not refreshed yet
+        let hash = world.spatial_hash::<Position>().unwrap();
+        assert_eq!(hash.position(mover), Some((0.0, 0.0)));
+
+        world.clear_trackers();
+
+        let hash = world.spatial_hash::<Position>().unwrap();
+        assert_eq!(hash.len(), 2);
+        assert_eq!(hash.position(mover), Some((100.0, 0.0)));
+        assert_eq!(hash.query_radius((0.0, 0.0), 20.0), vec![added]);
+    }
+
+    #[test]
+    fn missed_removals_are_pruned() {
+        let mut world = World::new();
+        let entity = spawn_at(&mut world, 0.0, 0.0);
+        world.enable_spatial_hash::<Position>(10.0);
+
+        // Removal tracker cleared without refreshing the hash
+        world.destroy(entity);
+        world.removed.clear();
+        world.update_spatial_hash::<Position>();
+
+        assert!(world.spatial_hash::<Position>().unwrap().is_empty());
+
+        world.disable_spatial_hash::<Position>();
+        assert!(world.spatial_hash::<Position>().is_none());
+    }
+}
@@ -3,28 +3,77 @@
 //! SparseSet provides O(1) insert, remove, and lookup with dense memory layout
 //! for cache-efficient iteration.
 
-use std::any::Any;
 use crate::Component;
+use crate::change_detection::ComponentTicks;
+use serde::{Deserialize, Serialize};
+use std::any::Any;
+use std::cmp::Ordering;
 
 /// Fast component storage with O(1) operations
 ///
 /// Uses a SparseSet data structure
-/// - `sparse`: Maps entity ID -> dense array index
+/// - `sparse`: Maps entity ID -> dense array index, in pages allocated on demand
 /// - `dense`: Contiguous component data
 /// - `entities`: Entity IDs in dense array order
+/// - `ticks`: Added/changed ticks in dense array order
 ///
 /// Benefits:
 /// - O(1) insert, remove, get
 /// - Cache-friendly iteration (dense array)
 /// - Stable pointers within a frame
 pub struct SparseSet<T> {
-    sparse: Vec<usize>,
+    sparse: Vec<Option<Page>>,
     dense: Vec<T>,
     entities: Vec<u32>,
+    ticks: Vec<ComponentTicks>,
+    /// Tick of the latest `get_mut_tracked` call
+    mutated: u32,
 }
 
+/// Number of entity IDs covered by one sparse page (4 KiB of `u32`)
+const PAGE_BITS: usize = 10;
+const PAGE_SIZE: usize = 1 << PAGE_BITS;
+
+/// Fixed-size block of the sparse index, allocated on first use
+type Page = Box<[u32; PAGE_SIZE]>;
+
 /// Sentinel value indicating "no entry" in sparse array
-const NONE: usize = usize::MAX;
+const NONE: u32 = u32::MAX;
+
+/// Heap memory used by a storage, in bytes
+///
+/// Counts allocated capacity, not only the occupied part.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemoryUsage {
+    /// Component values
+    pub dense: usize,
+    /// Entity IDs and change ticks stored next to the values
+    pub metadata: usize,
+    /// Page table and allocated sparse pages
+    pub sparse: usize,
+    /// Number of allocated sparse pages
+    pub pages: usize,
+}
+
+impl MemoryUsage {
+    /// Returns the total number of bytes
+    pub fn total(&self) -> usize {
+        self.dense + self.metadata + self.sparse
+    }
+}
+
+impl std::ops::Add for MemoryUsage {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        Self {
+            dense: self.dense + other.dense,
+            metadata: self.metadata + other.metadata,
+            sparse: self.sparse + other.sparse,
+            pages: self.pages + other.pages,
+        }
+    }
+}
 
 impl<T> SparseSet<T> {
     pub fn new() -> Self {
@@ -32,6 +81,41 @@ impl<T> SparseSet<T> {
             sparse: Vec::new(),
             dense: Vec::new(),
             entities: Vec::new(),
+            ticks: Vec::new(),
+            mutated: 0,
+        }
+    }
+
+    /// Returns the dense index of an entity
+    #[inline(always)]
+    fn dense_index(&self, entity_id: u32) -> Option<usize> {
+        let id = entity_id as usize;
+        let page = self.sparse.get(id >> PAGE_BITS)?.as_ref()?;
+        let dense_idx = page[id & (PAGE_SIZE - 1)];
+
+        (dense_idx != NONE).then_some(dense_idx as usize)
+    }
+
+    /// Points an entity at a dense index, allocating its page if needed
+    #[inline(always)]
+    fn set_dense_index(&mut self, entity_id: u32, dense_idx: usize) {
+        let id = entity_id as usize;
+        let page_idx = id >> PAGE_BITS;
+
+        if page_idx >= self.sparse.len() {
+            self.sparse.resize_with(page_idx + 1, || None);
+        }
+
+        let page = self.sparse[page_idx].get_or_insert_with(|| Box::new([NONE; PAGE_SIZE]));
+        page[id & (PAGE_SIZE - 1)] = dense_idx as u32;
+    }
+
+    /// Removes an entity from the sparse index
+    #[inline(always)]
+    fn clear_dense_index(&mut self, entity_id: u32) {
+        let id = entity_id as usize;
+        if let Some(Some(page)) = self.sparse.get_mut(id >> PAGE_BITS) {
+            page[id & (PAGE_SIZE - 1)] = NONE;
         }
     }
 
@@ -39,92 +123,145 @@ impl<T> SparseSet<T> {
     ///
     /// If the entity already has this component, it will be replaced.
     pub fn insert(&mut self, entity_id: u32, value: T) {
-        let id = entity_id as usize;
-
-        if id >= self.sparse.len() {
-            self.sparse.resize(id + 1, NONE);
-        }
+        self.insert_tracked(entity_id, value, 0);
+    }
 
-        if self.sparse[id] != NONE {
-            let dense_idx = self.sparse[id];
+    /// Inserts or updates a component, recording the change tick
+    ///
+    /// A new component gets `tick` as both its added and changed tick.
+    /// A replaced component keeps its added tick and only updates the changed tick.
+    pub fn insert_tracked(&mut self, entity_id: u32, value: T, tick: u32) {
+        if let Some(dense_idx) = self.dense_index(entity_id) {
             self.dense[dense_idx] = value;
+            self.ticks[dense_idx].changed = tick;
         } else {
             let dense_idx = self.dense.len();
-            self.sparse[id] = dense_idx;
+            self.set_dense_index(entity_id, dense_idx);
             self.dense.push(value);
             self.entities.push(entity_id);
+            self.ticks.push(ComponentTicks::new(tick));
         }
     }
 
-    #[inline(always)]
-    pub fn get(&self, entity_id: u32) -> Option<&T> {
-        let id = entity_id as usize;
-        if id >= self.sparse.len() {
-            return None;
-        }
-
-        let dense_idx = self.sparse[id];
-        if dense_idx == NONE {
-            return None;
+    /// Reserves capacity for at least `additional` more components
+    ///
+    /// `max_entity_id` pre-sizes the page table so inserts up to that ID
+    /// don't reallocate it. Pages themselves are still allocated on demand.
+    pub fn reserve(&mut self, additional: usize, max_entity_id: u32) {
+        self.dense.reserve(additional);
+        self.entities.reserve(additional);
+        self.ticks.reserve(additional);
+
+        let page_idx = max_entity_id as usize >> PAGE_BITS;
+        if page_idx >= self.sparse.len() {
+            self.sparse.resize_with(page_idx + 1, || None);
         }
+    }
 
-        Some(&self.dense[dense_idx])
+    #[inline(always)]
+    pub fn get(&self, entity_id: u32) -> Option<&T> {
+        self.dense_index(entity_id).map(|i| &self.dense[i])
     }
 
     #[inline(always)]
     pub fn get_mut(&mut self, entity_id: u32) -> Option<&mut T> {
-        let id = entity_id as usize;
-        if id >= self.sparse.len() {
-            return None;
-        }
-
-        let dense_idx = self.sparse[id];
-        if dense_idx == NONE {
-            return None;
-        }
+        self.dense_index(entity_id).map(|i| &mut self.dense[i])
+    }
 
+    /// Gets a mutable reference and marks the component as changed at `tick`
+    #[inline(always)]
+    pub fn get_mut_tracked(&mut self, entity_id: u32, tick: u32) -> Option<&mut T> {
+        let dense_idx = self.dense_index(entity_id)?;
+        self.ticks[dense_idx].changed = tick;
+        self.mutated = tick;
         Some(&mut self.dense[dense_idx])
     }
 
+    /// Returns the tick of the latest mutable access via `get_mut_tracked`
+    #[inline(always)]
+    pub fn last_mutated(&self) -> u32 {
+        self.mutated
+    }
+
+    /// Returns the added/changed ticks of a component
+    #[inline(always)]
+    pub fn ticks(&self, entity_id: u32) -> Option<ComponentTicks> {
+        self.dense_index(entity_id).map(|i| self.ticks[i])
+    }
+
     /// Removes a component and returns it
     ///
     /// Uses swap-remove for O(1) deletion. The last element is moved
     /// to fill the gap, so iteration order is not preserved.
     pub fn remove(&mut self, entity_id: u32) -> Option<T> {
-        let id = entity_id as usize;
-        if id >= self.sparse.len() {
-            return None;
-        }
-
-        let dense_idx = self.sparse[id];
-        if dense_idx == NONE {
-            return None;
-        }
-        self.sparse[id] = NONE;
-
-        if self.dense.is_empty() {
-            return None;
-        }
+        let dense_idx = self.dense_index(entity_id)?;
+        self.clear_dense_index(entity_id);
 
         let last_entity = *self.entities.last()?;
 
         if dense_idx < self.dense.len() - 1 {
             self.entities[dense_idx] = last_entity;
-            self.sparse[last_entity as usize] = dense_idx;
+            self.set_dense_index(last_entity, dense_idx);
         }
 
         self.entities.pop();
+        self.ticks.swap_remove(dense_idx);
         Some(self.dense.swap_remove(dense_idx))
     }
 
     pub fn contains(&self, entity_id: u32) -> bool {
-        let id = entity_id as usize;
-        if id >= self.sparse.len() {
-            return false;
+        self.dense_index(entity_id).is_some()
+    }
+
+    /// Physically reorders the components by `compare`
+    ///
+    /// The sort is stable. Iteration and `entities()` follow the new order
+    /// until the next removal. Ticks move with their components, so sorting
+    /// doesn't count as a change. An already sorted set is left untouched
+    /// without allocating.
+    pub fn sort_by<F>(&mut self, mut compare: F)
+    where
+        F: FnMut(&T, &T) -> Ordering,
+    {
+        if self
+            .dense
+            .is_sorted_by(|a, b| compare(a, b) != Ordering::Greater)
+        {
+            return;
+        }
+
+        let mut order: Vec<usize> = (0..self.dense.len()).collect();
+        order.sort_by(|&a, &b| compare(&self.dense[a], &self.dense[b]));
+
+        // Apply the permutation in place, one cycle at a time:
+        // slot `i` receives the element currently at `order[i]`
+        for start in 0..order.len() {
+            let mut current = start;
+            while order[current] != start {
+                let next = order[current];
+                self.dense.swap(current, next);
+                self.entities.swap(current, next);
+                self.ticks.swap(current, next);
+                order[current] = current;
+                current = next;
+            }
+            order[current] = current;
+        }
+
+        for dense_idx in 0..self.entities.len() {
+            self.set_dense_index(self.entities[dense_idx], dense_idx);
         }
+    }
 
-        let dense_idx = self.sparse[id];
-        dense_idx != NONE
+    /// Physically reorders the components by a key
+    ///
+    /// See `sort_by`.
+    pub fn sort_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        self.sort_by(|a, b| key(a).cmp(&key(b)));
     }
 
     /// Iterates over all (entity_id, component) pairs
@@ -132,6 +269,13 @@ impl<T> SparseSet<T> {
         self.entities.iter().copied().zip(self.dense.iter())
     }
 
+    /// Iterates over all (entity_id, component, ticks) triples
+    pub fn iter_with_ticks(&self) -> impl Iterator<Item = (u32, &T, ComponentTicks)> {
+        self.iter()
+            .zip(self.ticks.iter().copied())
+            .map(|((id, value), ticks)| (id, value, ticks))
+    }
+
     /// Iterates over all (entity_id, component) pairs mutably
     pub fn iter_mut(&mut self) -> impl Iterator<Item = (u32, &mut T)> {
         self.entities.iter().copied().zip(self.dense.iter_mut())
@@ -148,9 +292,53 @@ impl<T> SparseSet<T> {
         self.dense.len()
     }
 
+    /// Returns the number of components that fit without reallocating
+    pub fn capacity(&self) -> usize {
+        self.dense.capacity()
+    }
+
     pub fn is_empty(&self) -> bool {
         self.dense.is_empty()
     }
+
+    /// Releases unused capacity
+    ///
+    /// Shrinks the dense arrays to their length and frees sparse pages that
+    /// no longer hold any entity, e.g. after many high-ID entities were
+    /// destroyed.
+    pub fn shrink_to_fit(&mut self) {
+        self.dense.shrink_to_fit();
+        self.entities.shrink_to_fit();
+        self.ticks.shrink_to_fit();
+
+        for slot in &mut self.sparse {
+            if slot
+                .as_ref()
+                .is_some_and(|page| page.iter().all(|&i| i == NONE))
+            {
+                *slot = None;
+            }
+        }
+
+        while self.sparse.last().is_some_and(Option::is_none) {
+            self.sparse.pop();
+        }
+        self.sparse.shrink_to_fit();
+    }
+
+    /// Returns the heap memory used by this storage
+    pub fn memory_usage(&self) -> MemoryUsage {
+        let pages = self.sparse.iter().filter(|p| p.is_some()).count();
+
+        MemoryUsage {
+            dense: self.dense.capacity() * std::mem::size_of::<T>(),
+            metadata: self.entities.capacity() * std::mem::size_of::<u32>()
+                + self.ticks.capacity() * std::mem::size_of::<ComponentTicks>(),
+            sparse: self.sparse.capacity() * std::mem::size_of::<Option<Page>>()
+                + pages * std::mem::size_of::<[u32; PAGE_SIZE]>(),
+            pages,
+        }
+    }
 }
 
 impl<T> Default for SparseSet<T> {
@@ -169,20 +357,47 @@ pub trait Storage: Any + Send + Sync {
     fn remove(&mut self, entity_id: u32) -> bool;
     fn contains(&self, entity_id: u32) -> bool;
     fn entity_ids(&self) -> &[u32];
+    fn ticks(&self, entity_id: u32) -> Option<ComponentTicks>;
+
+    /// Removes a component and returns it boxed (used to move entities between Worlds)
+    fn take_boxed(&mut self, entity_id: u32) -> Option<Box<dyn Any + Send + Sync>>;
+
+    /// Inserts a boxed component, returning false if its type doesn't match
+    fn insert_boxed(
+        &mut self,
+        entity_id: u32,
+        component: Box<dyn Any + Send + Sync>,
+        tick: u32,
+    ) -> bool;
+
+    /// Creates an empty storage of the same component type
+    fn new_empty(&self) -> Box<dyn Storage>;
+
+    /// Releases unused capacity
+    fn shrink_to_fit(&mut self);
+
+    /// Returns the heap memory used by this storage
+    fn memory_usage(&self) -> MemoryUsage;
 
-    /// Returns type name (debug builds only)
-    #[cfg(debug_assertions)]
+    /// Returns the number of stored components
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    fn len(&self) -> usize;
+
+    /// Returns the number of components that fit without reallocating
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    fn capacity(&self) -> usize;
+
+    /// Returns the component type name
     fn type_name(&self) -> &'static str;
 
-    /// Returns formatted debug string for a component (debug builds only)
-    #[cfg(debug_assertions)]
+    /// Returns formatted debug string for a component
+    /// (debug builds or `inspect` feature)
+    #[cfg(any(debug_assertions, feature = "inspect"))]
     fn debug_entry(&self, entity_id: u32) -> Option<String>;
 
-    /// Prints memory layout of stored components (debug builds only)
-    ///
-    /// Shows physical memory addresses and offsets to verify contiguity.
-    #[cfg(debug_assertions)]
-    fn dump_memory_layout(&self);
+    /// Returns the size of one component value in bytes
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    fn element_size(&self) -> usize;
 }
 
 impl<T: Component> Storage for SparseSet<T> {
@@ -206,51 +421,63 @@ impl<T: Component> Storage for SparseSet<T> {
         self.entities()
     }
 
-    #[cfg(debug_assertions)]
-    fn type_name(&self) -> &'static str {
-        std::any::type_name::<T>()
+    fn ticks(&self, entity_id: u32) -> Option<ComponentTicks> {
+        SparseSet::ticks(self, entity_id)
     }
 
-    #[cfg(debug_assertions)]
-    fn debug_entry(&self, entity_id: u32) -> Option<String> {
-        self.get(entity_id).map(|v| format!("{:?}", v))
+    fn take_boxed(&mut self, entity_id: u32) -> Option<Box<dyn Any + Send + Sync>> {
+        SparseSet::remove(self, entity_id).map(|value| Box::new(value) as _)
     }
 
-    /// Prints a formatted table of memory addresses for stored components
-    ///
-    /// Used for verifying cache-friendly memory layout and debugging
-    /// performance issues. Only available in debug builds.
-    #[cfg(debug_assertions)]
-    fn dump_memory_layout(&self) {
-        let type_name = std::any::type_name::<T>()
-            .split("::")
-            .last()
-            .unwrap_or("Unknown");
-        let size = std::mem::size_of::<T>();
-
-        println!(
-            "\n● STORAGE: {} ({} bytes per element)",
-            type_name.to_uppercase(),
-            size
-        );
-        println!("┌─────────┬───────────┬────────────────────┬───────────────┐");
-        println!("│  Index  │ Entity ID │   Memory Address   │    Offset     │");
-        println!("├─────────┼───────────┼────────────────────┼───────────────┤");
+    fn insert_boxed(
+        &mut self,
+        entity_id: u32,
+        component: Box<dyn Any + Send + Sync>,
+        tick: u32,
+    ) -> bool {
+        match component.downcast::<T>() {
+            Ok(value) => {
+                self.insert_tracked(entity_id, *value, tick);
+                true
+            }
+            Err(_) => false,
+        }
+    }
 
-        let mut prev_addr = 0usize;
+    fn new_empty(&self) -> Box<dyn Storage> {
+        Box::new(SparseSet::<T>::new())
+    }
 
-        for (i, component) in self.dense.iter().enumerate() {
-            let addr = component as *const T as usize;
-            let offset = if i > 0 { addr - prev_addr } else { 0 };
+    fn shrink_to_fit(&mut self) {
+        SparseSet::shrink_to_fit(self)
+    }
 
-            println!(
-                "│ {:^7} │ {:^9} │ 0x{:012x}     │ {:>10} B  │",
-                i, self.entities[i], addr, offset
-            );
+    fn memory_usage(&self) -> MemoryUsage {
+        SparseSet::memory_usage(self)
+    }
 
-            prev_addr = addr;
-        }
-        println!("└─────────┴───────────┴────────────────────┴───────────────┘");
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    fn len(&self) -> usize {
+        SparseSet::len(self)
+    }
+
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    fn capacity(&self) -> usize {
+        SparseSet::capacity(self)
+    }
+
+    fn type_name(&self) -> &'static str {
+        std::any::type_name::<T>()
+    }
+
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    fn debug_entry(&self, entity_id: u32) -> Option<String> {
+        self.get(entity_id).map(|v| format!("{:?}", v))
+    }
+
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    fn element_size(&self) -> usize {
+        std::mem::size_of::<T>()
     }
 }
 
@@ -343,6 +570,109 @@ mod tests {
         assert_eq!(set.get(2), Some(&300));
     }
 
+    #[test]
+    fn insert_tracked_records_ticks() {
+        let mut set = SparseSet::new();
+        set.insert_tracked(1, "a", 3);
+        set.insert_tracked(1, "b", 5);
+
+        let ticks = set.ticks(1).unwrap();
+        assert_eq!(ticks.added, 3);
+        assert_eq!(ticks.changed, 5);
+    }
+
+    #[test]
+    fn get_mut_tracked_marks_changed() {
+        let mut set = SparseSet::new();
+        set.insert_tracked(1, 10, 1);
+        set.insert_tracked(2, 20, 1);
+
+        *set.get_mut_tracked(1, 4).unwrap() += 1;
+
+        assert_eq!(set.ticks(1).unwrap().changed, 4);
+        assert_eq!(set.ticks(2).unwrap().changed, 1);
+    }
+
+    #[test]
+    fn remove_keeps_ticks_aligned() {
+        let mut set = SparseSet::new();
+        set.insert_tracked(1, "a", 1);
+        set.insert_tracked(2, "b", 2);
+        set.insert_tracked(3, "c", 3);
+
+        set.remove(1);
+
+        assert_eq!(set.ticks(3).unwrap().added, 3);
+        assert_eq!(set.ticks(2).unwrap().added, 2);
+        assert_eq!(set.ticks(1), None);
+    }
+
+    #[test]
+    fn reserve_presizes_storage() {
+        let mut set = SparseSet::<i32>::new();
+        set.reserve(100, 99);
+
+        assert!(set.dense.capacity() >= 100);
+        assert_eq!(set.sparse.len(), 1);
+        assert!(set.is_empty());
+
+        set.insert(99, 1);
+        assert_eq!(set.get(99), Some(&1));
+    }
+
+    #[test]
+    fn sort_reorders_dense_storage() {
+        let mut set = SparseSet::new();
+        for (id, value) in [(4, 30), (7, 10), (1, 40), (9, 20)] {
+            set.insert_tracked(id, value, id);
+        }
+
+        set.sort_by_key(|&value| value);
+
+        assert_eq!(set.entities(), &[7, 9, 4, 1]);
+        assert_eq!(
+            set.iter().map(|(_, &v)| v).collect::<Vec<_>>(),
+            vec![10, 20, 30, 40]
+        );
+        assert_eq!(set.get(4), Some(&30));
+        assert_eq!(set.ticks(9).unwrap().added, 9);
+
+        set.remove(7);
+        assert_eq!(set.get(1), Some(&40));
+    }
+
+    #[test]
+    fn high_ids_allocate_single_page() {
+        let mut set = SparseSet::new();
+        set.insert(1_000_000, 7u8);
+
+        let memory = set.memory_usage();
+        assert_eq!(memory.pages, 1);
+        assert!(memory.sparse < 64 * 1024);
+        assert_eq!(set.get(1_000_000), Some(&7));
+        assert_eq!(set.get(999_999), None);
+        assert_eq!(set.get(u32::MAX - 1), None);
+    }
+
+    #[test]
+    fn shrink_frees_empty_pages() {
+        let mut set = SparseSet::new();
+        for id in 0..10 {
+            set.insert(id, id);
+        }
+        set.insert(500_000, 0);
+        assert_eq!(set.memory_usage().pages, 2);
+
+        set.remove(500_000);
+        set.shrink_to_fit();
+
+        let memory = set.memory_usage();
+        assert_eq!(memory.pages, 1);
+        assert_eq!(set.sparse.len(), 1);
+        assert_eq!(memory.dense, 10 * std::mem::size_of::<u32>());
+        assert_eq!(set.get(9), Some(&9));
+    }
+
     #[test]
     fn empty_set() {
         let set = SparseSet::<i32>::new();
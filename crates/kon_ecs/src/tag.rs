@@ -0,0 +1,429 @@
+//! Tag bitsets and interned tag handles
+//!
+//! Every registered tag owns one bit slot. Entity tag sets are stored as
+//! `TagMask`, a growable bitset whose first 128 bits live inline, so worlds
+//! with few tags never allocate. Slots of unregistered tags are recycled.
+//!
+//! `Tag` is an interned handle to a slot. Queries and tag operations accept
+//! either a tag name or a `Tag`; handles skip the name lookup entirely.
+//!
+//! # Example
+//! ```ignore
+//! let enemy = world.register_tag("enemy");
+//!
+//! world.spawn().insert(Health(10)).tag(enemy);
+//!
+//! // No string hashing per query
+//! world.select::<(Health,)>()
+//!     .tagged(enemy)
+//!     .each(|entity, (hp,)| println!("{} {:?}", entity, hp));
+//! ```
+
+use std::collections::HashMap;
+
+/// Number of tag bits stored inline in a `TagMask`
+const INLINE_BITS: usize = 128;
+
+/// Empty mask, returned for entities without tags
+pub(crate) static EMPTY_MASK: TagMask = TagMask {
+    inline: 0,
+    spill: Vec::new(),
+};
+
+/// Growable bitset of tag slots
+///
+/// Bits 0-127 are stored inline, higher bits spill into a heap vector.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TagMask {
+    inline: u128,
+    spill: Vec<u128>,
+}
+
+impl TagMask {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns true if the bit is set
+    #[inline(always)]
+    pub fn contains(&self, bit: usize) -> bool {
+        match bit.checked_sub(INLINE_BITS) {
+            None => self.inline & (1 << bit) != 0,
+            Some(rest) => self
+                .spill
+                .get(rest / INLINE_BITS)
+                .is_some_and(|word| word & (1 << (rest % INLINE_BITS)) != 0),
+        }
+    }
+
+    /// Sets a bit, returning true if it was not set before
+    pub fn insert(&mut self, bit: usize) -> bool {
+        let word = self.word_mut(bit);
+        let mask = 1 << (bit % INLINE_BITS);
+        let added = *word & mask == 0;
+        *word |= mask;
+        added
+    }
+
+    /// Clears a bit, returning true if it was set before
+    pub fn remove(&mut self, bit: usize) -> bool {
+        if !self.contains(bit) {
+            return false;
+        }
+
+        *self.word_mut(bit) &= !(1 << (bit % INLINE_BITS));
+        true
+    }
+
+    /// Clears all bits
+    pub fn clear(&mut self) {
+        self.inline = 0;
+        self.spill.clear();
+    }
+
+    /// Returns true if no bit is set
+    pub fn is_empty(&self) -> bool {
+        self.inline == 0 && self.spill.iter().all(|&word| word == 0)
+    }
+
+    /// Returns true if every bit of `other` is also set in `self`
+    #[inline(always)]
+    pub fn contains_all(&self, other: &TagMask) -> bool {
+        if self.inline & other.inline != other.inline {
+            return false;
+        }
+
+        other.spill.iter().enumerate().all(|(i, &word)| {
+            let own = self.spill.get(i).copied().unwrap_or(0);
+            own & word == word
+        })
+    }
+
+    /// Returns true if `self` and `other` share at least one bit
+    #[inline(always)]
+    pub fn intersects(&self, other: &TagMask) -> bool {
+        self.inline & other.inline != 0
+            || self.spill.iter().zip(&other.spill).any(|(a, b)| a & b != 0)
+    }
+
+    /// Iterates over the indices of all set bits in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = usize> + '_ {
+        std::iter::once(self.inline)
+            .chain(self.spill.iter().copied())
+            .enumerate()
+            .flat_map(|(word_index, word)| {
+                (0..INLINE_BITS)
+                    .filter(move |bit| word & (1 << bit) != 0)
+                    .map(move |bit| word_index * INLINE_BITS + bit)
+            })
+    }
+
+    fn word_mut(&mut self, bit: usize) -> &mut u128 {
+        match bit.checked_sub(INLINE_BITS) {
+            None => &mut self.inline,
+            Some(rest) => {
+                let index = rest / INLINE_BITS;
+                if index >= self.spill.len() {
+                    self.spill.resize(index + 1, 0);
+                }
+                &mut self.spill[index]
+            }
+        }
+    }
+}
+
+/// Interned handle to a registered tag
+///
+/// Obtained via `world.register_tag()` or `world.tag_handle()`. A handle
+/// becomes stale once its tag is unregistered; stale handles match nothing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Tag {
+    slot: u32,
+    generation: u32,
+}
+
+impl Tag {
+    /// Returns the bit slot of the tag
+    #[inline]
+    pub fn slot(&self) -> usize {
+        self.slot as usize
+    }
+}
+
+/// A tag given by name or by interned handle
+///
+/// Accepted by every tag operation, so both `"player"` and a `Tag` work.
+#[derive(Debug, Clone, Copy)]
+pub enum TagRef<'a> {
+    Name(&'a str),
+    Handle(Tag),
+}
+
+impl<'a> From<&'a str> for TagRef<'a> {
+    fn from(name: &'a str) -> Self {
+        TagRef::Name(name)
+    }
+}
+
+impl<'a> From<&'a String> for TagRef<'a> {
+    fn from(name: &'a String) -> Self {
+        TagRef::Name(name)
+    }
+}
+
+impl From<Tag> for TagRef<'_> {
+    fn from(tag: Tag) -> Self {
+        TagRef::Handle(tag)
+    }
+}
+
+impl From<&Tag> for TagRef<'_> {
+    fn from(tag: &Tag) -> Self {
+        TagRef::Handle(*tag)
+    }
+}
+
+/// Tag name or handle stored past the lifetime of a `TagRef`
+///
+/// Used by command buffers and cached query state.
+#[derive(Debug, Clone)]
+pub(crate) enum OwnedTag {
+    Name(Box<str>),
+    Handle(Tag),
+}
+
+impl From<TagRef<'_>> for OwnedTag {
+    fn from(tag: TagRef<'_>) -> Self {
+        match tag {
+            TagRef::Name(name) => OwnedTag::Name(name.into()),
+            TagRef::Handle(tag) => OwnedTag::Handle(tag),
+        }
+    }
+}
+
+impl OwnedTag {
+    pub(crate) fn as_ref(&self) -> TagRef<'_> {
+        match self {
+            OwnedTag::Name(name) => TagRef::Name(name),
+            OwnedTag::Handle(tag) => TagRef::Handle(*tag),
+        }
+    }
+}
+
+/// Registered tag occupying a bit slot
+struct TagSlot {
+    name: Option<String>,
+    generation: u32,
+    /// Number of entities currently holding the tag
+    holders: usize,
+}
+
+/// Name <-> slot mapping with holder counts and slot recycling
+#[derive(Default)]
+pub(crate) struct TagRegistry {
+    by_name: HashMap<String, Tag>,
+    slots: Vec<TagSlot>,
+    free: Vec<u32>,
+    /// Bumped whenever a tag is registered or unregistered
+    version: u64,
+}
+
+impl TagRegistry {
+    /// Returns the handle of a tag, registering it if needed
+    pub(crate) fn intern(&mut self, name: &str) -> Tag {
+        if let Some(&tag) = self.by_name.get(name) {
+            return tag;
+        }
+
+        let tag = match self.free.pop() {
+            Some(slot) => {
+                let entry = &mut self.slots[slot as usize];
+                entry.name = Some(name.to_string());
+                Tag {
+                    slot,
+                    generation: entry.generation,
+                }
+            }
+            None => {
+                self.slots.push(TagSlot {
+                    name: Some(name.to_string()),
+                    generation: 0,
+                    holders: 0,
+                });
+                Tag {
+                    slot: self.slots.len() as u32 - 1,
+                    generation: 0,
+                }
+            }
+        };
+
+        self.by_name.insert(name.to_string(), tag);
+        self.version += 1;
+        tag
+    }
+
+    /// Returns the handle of a registered tag
+    pub(crate) fn lookup(&self, name: &str) -> Option<Tag> {
+        self.by_name.get(name).copied()
+    }
+
+    /// Resolves a tag reference to its bit slot without registering
+    #[inline]
+    pub(crate) fn resolve(&self, tag: TagRef<'_>) -> Option<usize> {
+        match tag {
+            TagRef::Name(name) => self.lookup(name).map(|t| t.slot()),
+            TagRef::Handle(tag) => self.is_valid(tag).then(|| tag.slot()),
+        }
+    }
+
+    /// Resolves a tag reference to its bit slot, registering names as needed
+    ///
+    /// Returns None for stale handles.
+    pub(crate) fn resolve_or_intern(&mut self, tag: TagRef<'_>) -> Option<usize> {
+        match tag {
+            TagRef::Name(name) => Some(self.intern(name).slot()),
+            TagRef::Handle(tag) => self.is_valid(tag).then(|| tag.slot()),
+        }
+    }
+
+    /// Returns true if the handle refers to a currently registered tag
+    pub(crate) fn is_valid(&self, tag: Tag) -> bool {
+        self.slots
+            .get(tag.slot())
+            .is_some_and(|s| s.generation == tag.generation && s.name.is_some())
+    }
+
+    /// Returns the name of the tag in a slot
+    pub(crate) fn name(&self, slot: usize) -> Option<&str> {
+        self.slots.get(slot).and_then(|s| s.name.as_deref())
+    }
+
+    /// Returns the number of entities holding the tag in a slot
+    pub(crate) fn holders(&self, slot: usize) -> usize {
+        self.slots.get(slot).map_or(0, |s| s.holders)
+    }
+
+    pub(crate) fn add_holder(&mut self, slot: usize) {
+        self.slots[slot].holders += 1;
+    }
+
+    pub(crate) fn remove_holder(&mut self, slot: usize) {
+        let entry = &mut self.slots[slot];
+        entry.holders = entry.holders.saturating_sub(1);
+    }
+
+    /// Frees the slot of a tag no entity holds
+    ///
+    /// Returns false if the tag is unknown or still held.
+    pub(crate) fn unregister(&mut self, slot: usize) -> bool {
+        let Some(entry) = self.slots.get_mut(slot) else {
+            return false;
+        };
+
+        if entry.holders > 0 {
+            return false;
+        }
+
+        let Some(name) = entry.name.take() else {
+            return false;
+        };
+
+        entry.generation = entry.generation.wrapping_add(1);
+        self.by_name.remove(&name);
+        self.free.push(slot as u32);
+        self.version += 1;
+        true
+    }
+
+    /// Frees every registered slot without holders, returning how many were freed
+    pub(crate) fn prune(&mut self) -> usize {
+        let unused: Vec<usize> = (0..self.slots.len())
+            .filter(|&slot| self.slots[slot].name.is_some() && self.slots[slot].holders == 0)
+            .collect();
+
+        for &slot in &unused {
+            self.unregister(slot);
+        }
+
+        unused.len()
+    }
+
+    /// Returns the number of registered tags
+    pub(crate) fn len(&self) -> usize {
+        self.by_name.len()
+    }
+
+    /// Returns a counter that changes whenever the set of tags changes
+    pub(crate) fn version(&self) -> u64 {
+        self.version
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mask_spills_past_inline_bits() {
+        let mut mask = TagMask::new();
+        assert!(mask.insert(3));
+        assert!(mask.insert(200));
+        assert!(!mask.insert(200));
+
+        assert!(mask.contains(3));
+        assert!(mask.contains(200));
+        assert!(!mask.contains(201));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![3, 200]);
+
+        assert!(mask.remove(200));
+        assert!(!mask.remove(200));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn mask_contains_all_and_intersects() {
+        let mut entity = TagMask::new();
+        entity.insert(1);
+        entity.insert(300);
+
+        let mut required = TagMask::new();
+        required.insert(300);
+        assert!(entity.contains_all(&required));
+
+        required.insert(400);
+        assert!(!entity.contains_all(&required));
+        assert!(entity.intersects(&required));
+
+        let mut excluded = TagMask::new();
+        excluded.insert(2);
+        assert!(!entity.intersects(&excluded));
+    }
+
+    #[test]
+    fn registry_recycles_slots() {
+        let mut registry = TagRegistry::default();
+        let a = registry.intern("a");
+        let b = registry.intern("b");
+        assert_eq!(registry.intern("a"), a);
+
+        assert!(registry.unregister(a.slot()));
+        assert!(!registry.is_valid(a));
+
+        let c = registry.intern("c");
+        assert_eq!(c.slot(), a.slot());
+        assert_ne!(c, a);
+        assert!(registry.is_valid(b));
+    }
+
+    #[test]
+    fn registry_keeps_held_tags() {
+        let mut registry = TagRegistry::default();
+        let a = registry.intern("a");
+        registry.intern("b");
+        registry.add_holder(a.slot());
+
+        assert!(!registry.unregister(a.slot()));
+        assert_eq!(registry.prune(), 1);
+        assert_eq!(registry.len(), 1);
+    }
+}
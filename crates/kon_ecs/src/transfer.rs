@@ -0,0 +1,437 @@
+//! Entity cloning and moving entities between Worlds
+//!
+//! `clone_entity` duplicates an entity inside its World, copying every
+//! component type registered with `register_cloneable` (or
+//! `register_component`) plus its tags. Other components are skipped with a
+//! warning.
+//! `move_entity_to` and `merge` move entities into another World and don't
+//! need any registration, since components are moved rather than copied.
+//! Entity references are remapped through `MapEntities` for component types
+//! registered with `register_map_entities` (or `register_serializable_mapped`).
+//!
+//! # Example
+//! ```ignore
+//! // Build a level in a staging World, then stream it in at once
+//! let mut staging = World::new();
+//! staging.spawn().insert(Position { x: 0.0, y: 0.0 }).tag("enemy");
+//!
+//! let spawned = world.merge(staging);
+//!
+//! world.register_cloneable::<Position>();
+//! let twin = world.clone_entity(player).unwrap();
+//! ```
+
+use crate::entity::Entity;
+use crate::hierarchy::{Children, Parent};
+use crate::hooks::HookKind;
+use crate::scene::{BoxedComponent, EntityMapper, MapEntities};
+use crate::{Component, World};
+use std::any::TypeId;
+use std::collections::HashMap;
+
+/// Copies a component from one entity to another
+type CloneFn = fn(&mut World, Entity, Entity);
+
+/// Remaps the entity references of a boxed component
+type MapFn = fn(&mut BoxedComponent, &EntityMapper);
+
+/// Component types that can be cloned or remapped
+#[derive(Default)]
+pub(crate) struct TransferRegistry {
+    cloners: HashMap<TypeId, CloneFn>,
+    mappers: HashMap<TypeId, MapFn>,
+}
+
+fn clone_component<C: Component + Clone>(world: &mut World, source: Entity, clone: Entity) {
+    if let Some(component) = world.get::<C>(source).cloned() {
+        world.insert(clone, component);
+    }
+}
+
+fn map_component<C: Component + MapEntities>(
+    component: &mut BoxedComponent,
+    mapper: &EntityMapper,
+) {
+    if let Some(component) = component.downcast_mut::<C>() {
+        component.map_entities(mapper);
+    }
+}
+
+/// `Parent` and `Children` are rebuilt through `set_parent`, never copied
+fn is_hierarchy(type_id: TypeId) -> bool {
+    type_id == TypeId::of::<Parent>() || type_id == TypeId::of::<Children>()
+}
+
+impl World {
+    /// Registers a component type to be copied by `clone_entity`
+    pub fn register_cloneable<C: Component + Clone>(&mut self) {
+        self.transfer_registry
+            .cloners
+            .insert(TypeId::of::<C>(), clone_component::<C>);
+    }
+
+    /// Registers a component type whose `Entity` references are remapped
+    /// when it is moved by `move_entity_to` or `merge`
+    pub fn register_map_entities<C: Component + MapEntities>(&mut self) {
+        self.transfer_registry
+            .mappers
+            .insert(TypeId::of::<C>(), map_component::<C>);
+    }
+
+    /// Spawns a copy of an entity
+    ///
+    /// Copies all cloneable components and tags. The copy is attached to the
+    /// same parent and gets the same outgoing relations; children are not
+    /// cloned. Components that are not registered as cloneable are skipped
+    /// with a warning naming them; `uncloneable_components` lists them.
+    ///
+    /// Returns None if the entity is not alive.
+    pub fn clone_entity(&mut self, entity: Entity) -> Option<Entity> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let skipped = self.uncloneable_components(entity);
+        if !skipped.is_empty() {
+            log::warn!(
+                "clone_entity skipped components of {}: {}. Register them with `register_component`.",
+                self.describe(entity),
+                skipped.join(", ")
+            );
+        }
+
+        let cloners: Vec<CloneFn> = self
+            .transfer_registry
+            .cloners
+            .iter()
+            .filter(|&(type_id, _)| !is_hierarchy(*type_id) && self.has_by_type_id(entity, type_id))
+            .map(|(_, clone)| *clone)
+            .collect();
+
+        let clone = self.spawn().id();
+
+        for name in self.get_entity_tags(entity.id()) {
+            self.tag(clone, &name);
+        }
+
+        for clone_fn in cloners {
+            clone_fn(self, entity, clone);
+        }
+
+        if let Some(parent) = self.parent(entity) {
+            self.set_parent(clone, parent);
+        }
+        self.copy_relations(entity, clone);
+
+        Some(clone)
+    }
+
+    /// Returns the type names of the components of an entity that
+    /// `clone_entity` would skip, sorted
+    ///
+    /// These are the component types not registered with
+    /// `register_component` or `register_cloneable`.
+    pub fn uncloneable_components(&self, entity: Entity) -> Vec<&'static str> {
+        if !self.is_alive(entity) {
+            return Vec::new();
+        }
+
+        let mut names: Vec<&'static str> = self
+            .components()
+            .iter()
+            .filter(|&(type_id, storage)| {
+                !is_hierarchy(*type_id)
+                    && !self.transfer_registry.cloners.contains_key(type_id)
+                    && storage.contains(entity.id())
+            })
+            .map(|(_, storage)| storage.type_name())
+            .collect();
+
+        names.sort_unstable();
+        names
+    }
+
+    /// Moves an entity with all its components and tags into another World
+    ///
+    /// In this World the entity is destroyed: `on_remove` hooks run, it is
+    /// detached from the hierarchy and relation cleanup policies apply.
+    /// Hierarchy and relations are not carried over, and references to other
+    /// entities in `MapEntities` components become dangling.
+    ///
+    /// Returns the entity in `other`, or None if the entity is not alive.
+    pub fn move_entity_to(&mut self, other: &mut World, entity: Entity) -> Option<Entity> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let owned: Vec<TypeId> = self
+            .components()
+            .iter()
+            .filter(|&(type_id, storage)| !is_hierarchy(*type_id) && storage.contains(entity.id()))
+            .map(|(type_id, _)| *type_id)
+            .collect();
+
+        for type_id in owned {
+            self.queue_hook(HookKind::Remove, type_id, entity);
+        }
+        self.flush_hooks();
+
+        // A hook may have destroyed the entity already
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        let tags = self.get_entity_tags(entity.id());
+        let components = self.take_components(entity);
+        self.destroy(entity);
+
+        let moved = other.spawn().id();
+        let map = HashMap::from([(entity, moved)]);
+
+        for name in &tags {
+            other.tag(moved, name);
+        }
+        other.put_components(self, moved, components, &EntityMapper::new(&map));
+        other.flush_hooks();
+
+        Some(moved)
+    }
+
+    /// Moves every entity of another World into this one
+    ///
+    /// Components, tags, hierarchy and relations are carried over with entity
+    /// references remapped to the new handles. Registrations, hooks and
+    /// prefabs of `other` are dropped. Hooks of this World run once all
+    /// entities have been moved.
+    ///
+    /// Returns a map from the handles in `other` to the new handles.
+    pub fn merge(&mut self, mut other: World) -> HashMap<Entity, Entity> {
+        let mut entities: Vec<Entity> = other.entities().collect();
+        entities.sort_by_key(|e| e.id());
+
+        self.reserve_entities(entities.len());
+        let map: HashMap<Entity, Entity> = entities
+            .iter()
+            .map(|&entity| (entity, self.spawn().id()))
+            .collect();
+        let mapper = EntityMapper::new(&map);
+
+        for &entity in &entities {
+            let merged = map[&entity];
+
+            for name in other.get_entity_tags(entity.id()) {
+                self.tag(merged, &name);
+            }
+
+            let components = other.take_components(entity);
+            self.put_components(&other, merged, components, &mapper);
+        }
+
+        for &entity in &entities {
+            for child in other.children(entity) {
+                self.set_parent(map[child], map[&entity]);
+            }
+        }
+
+        self.import_relations(&other, &mapper);
+        self.flush_hooks();
+
+        map
+    }
+
+    /// Removes all components except `Parent` / `Children` and returns them boxed
+    ///
+    /// No hooks run; removals are recorded for `RemovedComponents`.
+    fn take_components(&mut self, entity: Entity) -> Vec<(TypeId, BoxedComponent)> {
+        let mut taken = Vec::new();
+
+        for (type_id, storage) in self.components_mut().iter_mut() {
+            if is_hierarchy(*type_id) {
+                continue;
+            }
+
+            if let Some(component) = storage.take_boxed(entity.id()) {
+                taken.push((*type_id, component));
+            }
+        }
+
+        for (type_id, _) in &taken {
+            self.removed.entry(*type_id).or_default().push(entity);
+        }
+
+        taken
+    }
+
+    /// Inserts components taken from `source` into a freshly spawned entity
+    ///
+    /// Storages missing in this World are created from the source storages.
+    /// `on_add` / `on_insert` hooks are queued, not flushed.
+    fn put_components(
+        &mut self,
+        source: &World,
+        entity: Entity,
+        components: Vec<(TypeId, BoxedComponent)>,
+        mapper: &EntityMapper,
+    ) {
+        let tick = self.change_tick();
+
+        for (type_id, mut component) in components {
+            let map_fn = self
+                .transfer_registry
+                .mappers
+                .get(&type_id)
+                .or_else(|| source.transfer_registry.mappers.get(&type_id));
+            if let Some(map_fn) = map_fn {
+                map_fn(&mut component, mapper);
+            }
+
+            let Some(theirs) = source.components().get(&type_id) else {
+                continue;
+            };
+
+            let inserted = self
+                .components_mut()
+                .entry(type_id)
+                .or_insert_with(|| theirs.new_empty())
+                .insert_boxed(entity.id(), component, tick);
+
+            if inserted {
+                self.queue_hook(HookKind::Add, type_id, entity);
+                self.queue_hook(HookKind::Insert, type_id, entity);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Relation;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    #[derive(Debug, PartialEq)]
+    struct Unique(u32);
+
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Target(Entity);
+
+    impl MapEntities for Target {
+        fn map_entities(&mut self, mapper: &EntityMapper) {
+            self.0 = mapper.map(self.0);
+        }
+    }
+
+    struct Likes;
+    impl Relation for Likes {}
+
+    #[test]
+    fn clone_copies_cloneable_components_and_tags() {
+        let mut world = World::new();
+        world.register_cloneable::<Health>();
+
+        let parent = world.spawn().id();
+        let original = world
+            .spawn()
+            .insert(Health(5))
+            .insert(Unique(1))
+            .tag("enemy")
+            .id();
+        world.set_parent(original, parent);
+
+        let clone = world.clone_entity(original).unwrap();
+
+        assert_ne!(clone, original);
+        assert_eq!(world.get::<Health>(clone), Some(&Health(5)));
+        assert!(!world.has::<Unique>(clone));
+        assert_eq!(
+            world.uncloneable_components(original),
+            vec![std::any::type_name::<Unique>()]
+        );
+        assert!(world.has_tag(clone, "enemy"));
+        assert_eq!(world.parent(clone), Some(parent));
+        assert_eq!(world.children(parent), &[original, clone]);
+    }
+
+    #[test]
+    fn clone_dead_entity_returns_none() {
+        let mut world = World::new();
+        let entity = world.spawn().id();
+        world.destroy(entity);
+
+        assert_eq!(world.clone_entity(entity), None);
+    }
+
+    #[test]
+    fn move_entity_between_worlds() {
+        let mut world = World::new();
+        let mut other = World::new();
+        other.spawn().insert(Health(1));
+
+        let entity = world
+            .spawn()
+            .insert(Health(7))
+            .insert(Unique(3))
+            .tag("boss")
+            .id();
+        let moved = world.move_entity_to(&mut other, entity).unwrap();
+
+        assert!(!world.is_alive(entity));
+        assert_eq!(world.removed::<Health>().collect::<Vec<_>>(), vec![entity]);
+        assert_eq!(other.get::<Health>(moved), Some(&Health(7)));
+        assert_eq!(other.get::<Unique>(moved), Some(&Unique(3)));
+        assert!(other.has_tag(moved, "boss"));
+        assert!(other.is_added::<Unique>(moved));
+    }
+
+    #[test]
+    fn move_runs_hooks_in_both_worlds() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicU32, Ordering};
+
+        let removed = Arc::new(AtomicU32::new(0));
+        let added = Arc::new(AtomicU32::new(0));
+
+        let mut world = World::new();
+        let counter = removed.clone();
+        world.hooks::<Health>().on_remove(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let mut other = World::new();
+        let counter = added.clone();
+        other.hooks::<Health>().on_add(move |_, _| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+
+        let entity = world.spawn().insert(Health(1)).id();
+        world.move_entity_to(&mut other, entity);
+
+        assert_eq!(removed.load(Ordering::Relaxed), 1);
+        assert_eq!(added.load(Ordering::Relaxed), 1);
+    }
+
+    #[test]
+    fn merge_remaps_hierarchy_relations_and_references() {
+        let mut world = World::new();
+        world.spawn().insert(Health(0));
+
+        let mut staging = World::new();
+        staging.register_map_entities::<Target>();
+        let root = staging.spawn().insert(Health(10)).tag("level").id();
+        let child = staging.spawn().insert(Target(root)).id();
+        staging.set_parent(child, root);
+        staging.relate::<Likes>(child, root);
+
+        let map = world.merge(staging);
+        let (root, child) = (map[&root], map[&child]);
+
+        assert_eq!(world.entity_count(), 3);
+        assert_eq!(world.get::<Health>(root), Some(&Health(10)));
+        assert!(world.has_tag(root, "level"));
+        assert_eq!(world.get::<Target>(child), Some(&Target(root)));
+        assert_eq!(world.parent(child), Some(root));
+        assert!(world.has_relation::<Likes>(child, root));
+    }
+}
@@ -3,33 +3,64 @@
 //! The world is the core ECS container that stores:
 //! - Entities with generational indices
 //! - Component storage (SparseSet per type)
-//! - Tag system (128 bitmask-based labels per entity)
+//! - Tag system (growable bitset of labels per entity)
+//! - World-local resources
+//! - Change ticks and removed component trackers
 //! - Deferred operations queue
 
 use crate::Component;
-use crate::entity::{Entity, EntityBuilder};
+use crate::change_detection::{ComponentTicks, RemovedComponents};
+use crate::commands::Commands;
+#[cfg(any(debug_assertions, feature = "inspect"))]
+use crate::diff::DiffRegistry;
+use crate::entity::{Entity, EntityBuilder, EntityId};
+use crate::hooks::{ComponentHook, ComponentHooks, HookKind};
+use crate::name::NameIndex;
+use crate::observer::Observers;
+use crate::prefab::Prefab;
 use crate::query::{Query, QueryMut, QueryTuple, QueryTupleMut};
-use crate::storage::{SparseSet, Storage};
+use crate::relation::RelationStorage;
+use crate::resource::Resources;
+use crate::scene::SceneRegistry;
+use crate::spatial::SpatialUpdater;
+use crate::storage::{MemoryUsage, SparseSet, Storage};
+use crate::tag::{EMPTY_MASK, Tag, TagMask, TagRef, TagRegistry};
+use crate::transfer::TransferRegistry;
 use std::any::TypeId;
 use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
 
-/// Boxed closure for deferred World operations
-///
-/// Used to queue operations that modify the World during queries.
-/// Applied via `world.apply_deferred()` at safe points.
-type DeferredOp = Box<dyn FnOnce(&mut World) + Send + Sync>;
+/// Source of process-unique World identifiers
+static NEXT_WORLD_ID: AtomicU64 = AtomicU64::new(0);
 
 /// ECS World containing all entities, components and tags
 ///
 /// # Tag System
-/// Tags are lightweight string labels stored as bitmasks (u128).
-/// - Up to 128 unique tags globally
-/// - O(1) tag filtering in queries
+/// Tags are lightweight string labels stored as bitsets (`TagMask`).
+/// - Unlimited unique tags, the first 128 stored inline without allocation
+/// - O(1) tag filtering in queries, interned `Tag` handles skip name lookups
+/// - Slots of unregistered tags are recycled
 /// - Tags are not components (no storage overhead per entity)
 ///
+/// # Change Detection
+/// Components record the world tick at which they were added and last changed.
+/// `clear_trackers()` (called each frame by `EcsPlugin`) advances the tick, so
+/// `Added<T>` / `Changed<T>` filters and `removed::<T>()` cover a single frame.
+/// A `QueryState` instead remembers the tick of its previous run.
+///
+/// # Lifecycle Hooks
+/// Per-component-type callbacks registered via `world.hooks::<C>()` run when a
+/// component is added, replaced or removed (including by `destroy`).
+///
+/// # Entity Events
+/// `trigger_on(entity, event)` runs observers registered via `observe` or
+/// `observe_entity`, optionally bubbling up to the entity's ancestors.
+///
 /// # Deferred Operations
-/// Use `world.defer()` to queue operations that modify the World during iteration.
-/// Applied via `apply_deferred()` at frame end.
+/// Use `world.commands()` to record operations that modify the World during
+/// iteration and hand them back with `push_commands()`, or `world.defer()` for
+/// a one-off closure. Applied via `apply_deferred()` at frame end.
 ///
 /// # Example
 /// ```ignore
@@ -59,22 +90,70 @@ type DeferredOp = Box<dyn FnOnce(&mut World) + Send + Sync>;
 ///     });
 /// ```
 pub struct World {
-    next_id: u32,
-    generations: Vec<u32>,
-    alive: HashSet<u32>,
-    free_ids: Vec<u32>,
-    components: HashMap<TypeId, Box<dyn Storage>>,
+    /// Process-unique World identifier (used by cached query state)
+    id: u64,
+
+    /// Next never-used entity ID, shared with command buffers that reserve entities
+    pub(crate) next_id: Arc<AtomicU32>,
+    pub(crate) generations: Vec<u32>,
+    pub(crate) alive: HashSet<u32>,
+    pub(crate) free_ids: Vec<u32>,
+    pub(crate) components: HashMap<TypeId, Box<dyn Storage>>,
+
+    /// Tag names, bit slots and holder counts
+    tag_registry: TagRegistry,
+
+    /// Bitset for each entity storing active tags. Indexed by Entity ID.
+    entity_tags: Vec<TagMask>,
+
+    /// Current change tick, advanced by `clear_trackers()` and `QueryState` runs
+    change_tick: AtomicU32,
+
+    /// Change tick at the last `clear_trackers()`
+    last_change_tick: u32,
+
+    /// Entities that lost a component since the last `clear_trackers()`, per component type
+    pub(crate) removed: HashMap<TypeId, Vec<Entity>>,
+
+    /// Relation links per relation type
+    pub(crate) relations: HashMap<TypeId, RelationStorage>,
 
-    /// Mapping of tag names to their respective bit indices (0-127).
-    tag_registry: HashMap<String, usize>,
+    /// Entities per `Name`, maintained by the `Name` hooks
+    pub(crate) names: NameIndex,
 
-    /// List of tag names indexed by their bit position. Used for debugging and inspection.
-    tag_names: Vec<String>,
+    /// Singleton values stored alongside the entities
+    pub(crate) resources: Resources,
 
-    /// Bitmask for each entity storing active tags. Indexed by Entity ID.
-    entity_tags: Vec<u128>,
+    /// Component, relation and resource types registered for scenes
+    pub(crate) scene_registry: SceneRegistry,
 
-    deferred: Vec<DeferredOp>,
+    /// Prefabs registered by name
+    pub(crate) prefabs: HashMap<String, Prefab>,
+
+    /// Component types that can be cloned or remapped between Worlds
+    pub(crate) transfer_registry: TransferRegistry,
+
+    /// Component types compared through `PartialEq` by `diff`
+    #[cfg(any(debug_assertions, feature = "inspect"))]
+    pub(crate) diff_registry: DiffRegistry,
+
+    /// Refresh functions of enabled spatial hashes, run by `clear_trackers()`
+    pub(crate) spatial_updaters: Vec<(TypeId, SpatialUpdater)>,
+
+    /// Lifecycle hooks per component type
+    hooks: HashMap<TypeId, ComponentHooks>,
+
+    /// Entity event observers per event type
+    pub(crate) observers: Observers,
+
+    /// Hooks queued by the current operation, flushed by `flush_hooks()`
+    pending_hooks: Vec<(ComponentHook, TypeId, Entity)>,
+
+    /// Component type and entity of every hook currently running
+    running_hooks: HashSet<(TypeId, Entity)>,
+
+    /// Commands applied by `apply_deferred()`, in submission order
+    pub(crate) deferred: Commands,
 }
 
 impl Default for World {
@@ -86,17 +165,39 @@ impl Default for World {
 impl World {
     /// Creates a new empty World
     pub fn new() -> Self {
-        Self {
-            next_id: 0,
+        let next_id = Arc::new(AtomicU32::new(0));
+
+        let mut world = Self {
+            id: NEXT_WORLD_ID.fetch_add(1, Ordering::Relaxed),
+            next_id: next_id.clone(),
             generations: Vec::new(),
             alive: HashSet::new(),
             free_ids: Vec::new(),
             components: HashMap::new(),
-            tag_registry: HashMap::new(),
-            tag_names: Vec::new(),
+            tag_registry: TagRegistry::default(),
             entity_tags: Vec::new(),
-            deferred: Vec::new(),
-        }
+            change_tick: AtomicU32::new(1),
+            last_change_tick: 0,
+            removed: HashMap::new(),
+            relations: HashMap::new(),
+            names: NameIndex::default(),
+            resources: Resources::default(),
+            scene_registry: SceneRegistry::default(),
+            prefabs: HashMap::new(),
+            transfer_registry: TransferRegistry::default(),
+            #[cfg(any(debug_assertions, feature = "inspect"))]
+            diff_registry: DiffRegistry::default(),
+            spatial_updaters: Vec::new(),
+            hooks: HashMap::new(),
+            pending_hooks: Vec::new(),
+            running_hooks: HashSet::new(),
+            observers: Observers::default(),
+            deferred: Commands::new(next_id),
+        };
+
+        world.register_names();
+        world.register_hierarchy();
+        world
     }
 
     /// Starts building a new entity
@@ -104,11 +205,10 @@ impl World {
     /// Returns an EntityBuilder for fluent API chaining.
     /// Entity IDs are reused from a free list after destruction.
     pub fn spawn(&mut self) -> EntityBuilder<'_> {
-        let id = self.free_ids.pop().unwrap_or_else(|| {
-            let id = self.next_id;
-            self.next_id += 1;
-            id
-        });
+        let id = self
+            .free_ids
+            .pop()
+            .unwrap_or_else(|| self.next_id.fetch_add(1, Ordering::Relaxed));
 
         if id as usize >= self.generations.len() {
             self.generations.resize(id as usize + 1, 0);
@@ -121,6 +221,32 @@ impl World {
         EntityBuilder::new(self, entity)
     }
 
+    /// Makes an entity reserved by a command buffer alive
+    pub(crate) fn spawn_reserved(&mut self, entity: Entity) {
+        let id = entity.id();
+        if id as usize >= self.generations.len() {
+            self.generations.resize(id as usize + 1, 0);
+        }
+
+        if self.generations[id as usize] == entity.generation() {
+            self.alive.insert(id);
+        }
+    }
+
+    /// Checks if a command buffer's entity allocator belongs to this World
+    pub(crate) fn owns_allocator(&self, next_id: &Arc<AtomicU32>) -> bool {
+        Arc::ptr_eq(&self.next_id, next_id)
+    }
+
+    /// Reserves capacity for at least `additional` more entities
+    ///
+    /// Avoids repeated reallocation when spawning many entities at once.
+    pub fn reserve_entities(&mut self, additional: usize) {
+        let fresh = additional.saturating_sub(self.free_ids.len());
+        self.generations.reserve(fresh);
+        self.alive.reserve(additional);
+    }
+
     /// Checks if an entity is alive and matches the given generation
     ///
     /// Returns false if:
@@ -131,6 +257,64 @@ impl World {
             && self.generations.get(entity.id() as usize) == Some(&entity.generation())
     }
 
+    /// Turns bits from `Entity::to_bits` back into a handle
+    ///
+    /// Returns None if the entity is not alive in this World.
+    pub fn get_entity(&self, bits: u64) -> Option<Entity> {
+        let entity = Entity::from_bits(bits);
+        self.is_alive(entity).then_some(entity)
+    }
+
+    /// Returns the stable `EntityId` of an entity, if it has one
+    pub fn entity_id(&self, entity: Entity) -> Option<EntityId> {
+        self.get::<EntityId>(entity).copied()
+    }
+
+    /// Returns the stable `EntityId` of an entity, generating one if needed
+    ///
+    /// Returns None if the entity is not alive.
+    pub fn assign_entity_id(&mut self, entity: Entity) -> Option<EntityId> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        if let Some(id) = self.entity_id(entity) {
+            return Some(id);
+        }
+
+        let id = EntityId::new();
+        self.insert(entity, id);
+        Some(id)
+    }
+
+    /// Finds the entity carrying a stable `EntityId`
+    ///
+    /// Scans the `EntityId` storage, O(n) in the number of ids.
+    pub fn find_entity(&self, id: EntityId) -> Option<Entity> {
+        let set = self
+            .components
+            .get(&TypeId::of::<EntityId>())?
+            .as_any()
+            .downcast_ref::<SparseSet<EntityId>>()?;
+
+        set.iter()
+            .find(|(_, stored)| **stored == id)
+            .map(|(entity_id, _)| Entity::new(entity_id, self.generation(entity_id)))
+    }
+
+    /// Returns the process-unique identifier of this World
+    pub(crate) fn id(&self) -> u64 {
+        self.id
+    }
+
+    /// Returns a value that changes whenever a component storage is created
+    /// or a tag is registered or unregistered (used by cached query state)
+    ///
+    /// Storages are never dropped, so their count only grows.
+    pub(crate) fn layout_version(&self) -> (usize, u64) {
+        (self.components.len(), self.tag_registry.version())
+    }
+
     /// Returns internal component storage map (used by query system)
     pub(crate) fn components(&self) -> &HashMap<TypeId, Box<dyn Storage>> {
         &self.components
@@ -152,6 +336,21 @@ impl World {
             .is_some_and(|s| s.contains(entity.id()))
     }
 
+    /// Returns the change ticks of a component by TypeId (used by query filters)
+    pub(crate) fn ticks_by_type_id(
+        &self,
+        entity: Entity,
+        type_id: &TypeId,
+    ) -> Option<ComponentTicks> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+
+        self.components
+            .get(type_id)
+            .and_then(|s| s.ticks(entity.id()))
+    }
+
     /// Returns the current generation for an entity ID (used by query system)
     pub(crate) fn generation(&self, id: u32) -> u32 {
         self.generations.get(id as usize).copied().unwrap_or(0)
@@ -161,6 +360,12 @@ impl World {
     ///
     /// The entity ID is added to a free list for reuse.
     /// The generation counter is increased to invalidate old references.
+    /// `on_remove` hooks run for every component before it is dropped,
+    /// including components inserted by those hooks; a component type whose
+    /// hook already ran is dropped without running it again.
+    /// The entity is removed from its parent's `Children` and its children are orphaned.
+    /// Relations involving the entity are removed according to their `CleanupPolicy`.
+    /// Observers registered for the entity are dropped.
     ///
     /// Returns true if the entity was alive, false otherwise.
     pub fn destroy(&mut self, entity: Entity) -> bool {
@@ -170,52 +375,202 @@ impl World {
 
         let id = entity.id();
 
-        for storage in self.components.values_mut() {
-            storage.remove(id);
+        // Repeat until the hooks stop adding components to the entity
+        let mut notified = HashSet::new();
+        while !self.hooks.is_empty() {
+            let owned: Vec<TypeId> = self
+                .components
+                .iter()
+                .filter(|(type_id, storage)| storage.contains(id) && !notified.contains(*type_id))
+                .map(|(type_id, _)| *type_id)
+                .collect();
+
+            if owned.is_empty() {
+                break;
+            }
+
+            for type_id in owned {
+                notified.insert(type_id);
+                self.queue_hook(HookKind::Remove, type_id, entity);
+            }
+            self.flush_hooks();
+
+            // A hook may have destroyed the entity already
+            if !self.is_alive(entity) {
+                return true;
+            }
+        }
+
+        self.detach_from_hierarchy(entity);
+        self.observers.remove_entity(entity);
+        let doomed = self.detach_relations(entity);
+
+        for (type_id, storage) in self.components.iter_mut() {
+            if storage.remove(id) {
+                self.removed.entry(*type_id).or_default().push(entity);
+            }
         }
 
-        if (id as usize) < self.entity_tags.len() {
-            self.entity_tags[id as usize] = 0;
+        if let Some(mask) = self.entity_tags.get_mut(id as usize) {
+            for slot in mask.iter() {
+                self.tag_registry.remove_holder(slot);
+            }
+            mask.clear();
         }
 
         self.alive.remove(&id);
         self.generations[id as usize] = self.generations[id as usize].wrapping_add(1);
         self.free_ids.push(id);
 
+        for source in doomed {
+            self.destroy(source);
+        }
+
         true
     }
 
+    /// Destroys every alive entity
+    ///
+    /// Hooks and relation cleanup run as for `destroy`.
+    /// Registrations, hooks, resources and tag names are kept.
+    pub fn clear(&mut self) {
+        let mut entities: Vec<Entity> = self.entities().collect();
+        entities.sort_by_key(|e| e.id());
+
+        for entity in entities {
+            self.destroy(entity);
+        }
+    }
+
     /// Inserts a component into an entity
     ///
     /// If the entity already has this component type, it will be replaced.
     /// Does nothing if the entity is not alive.
+    ///
+    /// New components are marked as added, replaced ones as changed.
+    /// Runs `on_replace` before overwriting, then `on_add` / `on_insert`.
     pub fn insert<C: Component>(&mut self, entity: Entity, component: C) {
         if !self.is_alive(entity) {
             return;
         }
 
+        let type_id = TypeId::of::<C>();
+        let hooked = self.hooks.contains_key(&type_id);
+        let replacing = hooked && self.has::<C>(entity);
+
+        if replacing {
+            self.queue_hook(HookKind::Replace, type_id, entity);
+            self.flush_hooks();
+
+            if !self.is_alive(entity) {
+                return;
+            }
+        }
+
+        let tick = self.change_tick();
         let storage = self
             .components
-            .entry(TypeId::of::<C>())
+            .entry(type_id)
             .or_insert_with(|| Box::new(SparseSet::<C>::new()));
 
         if let Some(set) = storage.as_any_mut().downcast_mut::<SparseSet<C>>() {
-            set.insert(entity.id(), component);
+            set.insert_tracked(entity.id(), component, tick);
+        }
+
+        if hooked {
+            if !replacing {
+                self.queue_hook(HookKind::Add, type_id, entity);
+            }
+            self.queue_hook(HookKind::Insert, type_id, entity);
+            self.flush_hooks();
+        }
+    }
+
+    /// Inserts components into many entities at once
+    ///
+    /// The component storage is looked up once and its capacity reserved up
+    /// front, which is considerably faster than calling `insert` in a loop.
+    /// Dead entities are skipped. Components with lifecycle hooks fall back
+    /// to `insert` so hooks still run per entity.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let velocities = particles.iter().map(|&e| (e, Velocity { x: 0.0, y: -1.0 }));
+    /// world.insert_batch(velocities);
+    /// ```
+    pub fn insert_batch<C, I>(&mut self, batch: I)
+    where
+        C: Component,
+        I: IntoIterator<Item = (Entity, C)>,
+    {
+        let type_id = TypeId::of::<C>();
+
+        if self.hooks.contains_key(&type_id) {
+            for (entity, component) in batch {
+                self.insert(entity, component);
+            }
+            return;
+        }
+
+        let batch = batch.into_iter();
+        let tick = self.change_tick();
+        let alive = &self.alive;
+        let generations = &self.generations;
+        let storage = self
+            .components
+            .entry(type_id)
+            .or_insert_with(|| Box::new(SparseSet::<C>::new()));
+
+        let Some(set) = storage.as_any_mut().downcast_mut::<SparseSet<C>>() else {
+            return;
+        };
+
+        set.reserve(
+            batch.size_hint().0,
+            self.next_id.load(Ordering::Relaxed).saturating_sub(1),
+        );
+
+        for (entity, component) in batch {
+            let id = entity.id();
+            if alive.contains(&id) && generations.get(id as usize) == Some(&entity.generation()) {
+                set.insert_tracked(id, component, tick);
+            }
         }
     }
 
     /// Removes a component from an entity
     ///
     /// Returns true if the component was removed, false if not found.
+    /// Removed components are reported by `removed::<C>()` until the next `clear_trackers()`.
+    /// Runs `on_remove` while the component is still readable.
     pub fn remove<C: Component>(&mut self, entity: Entity) -> bool {
         if !self.is_alive(entity) {
             return false;
         }
 
-        self.components
+        if self.hooks.contains_key(&TypeId::of::<C>()) && self.has::<C>(entity) {
+            self.queue_hook(HookKind::Remove, TypeId::of::<C>(), entity);
+            self.flush_hooks();
+
+            if !self.is_alive(entity) {
+                return true;
+            }
+        }
+
+        let removed = self
+            .components
             .get_mut(&TypeId::of::<C>())
             .and_then(|s| s.as_any_mut().downcast_mut::<SparseSet<C>>())
-            .is_some_and(|s| s.remove(entity.id()).is_some())
+            .is_some_and(|s| s.remove(entity.id()).is_some());
+
+        if removed {
+            self.removed
+                .entry(TypeId::of::<C>())
+                .or_default()
+                .push(entity);
+        }
+
+        removed
     }
 
     /// Gets an immutable reference to a component
@@ -236,6 +591,8 @@ impl World {
 
     /// Gets a mutable reference to a component
     ///
+    /// The component is marked as changed this frame.
+    ///
     /// Returns None if:
     /// - Entity is not alive
     /// - Entity doesn't have this component type
@@ -244,10 +601,11 @@ impl World {
             return None;
         }
 
+        let tick = self.change_tick();
         self.components
             .get_mut(&TypeId::of::<C>())
             .and_then(|s| s.as_any_mut().downcast_mut::<SparseSet<C>>())
-            .and_then(|s| s.get_mut(entity.id()))
+            .and_then(|s| s.get_mut_tracked(entity.id(), tick))
     }
 
     /// Checks if an entity has a component of the given type
@@ -261,72 +619,206 @@ impl World {
             .is_some_and(|s| s.contains(entity.id()))
     }
 
-    /// Maps a tag name to its bit index (0-127) in the bitmask
+    /// Registers what `#[component]` derives for a component type
     ///
-    /// Creates a new index if the tag hasn't been registered yet.
+    /// Makes `C` copyable by `clone_entity` and compared with `PartialEq` by
+    /// `diff` / `diff_since`. `#[component]` can't do this itself, since the
+    /// World only learns about a type once it is used, so call this for each
+    /// component type during setup.
+    pub fn register_component<C: Component + Clone + PartialEq>(&mut self) {
+        self.register_cloneable::<C>();
+        self.register_diffable::<C>();
+    }
+
+    /// Returns the lifecycle hooks of a component type for registration
     ///
-    /// # Panics
-    /// Panics if more than 128 unique tags are registered globally.
-    #[track_caller]
-    fn get_or_create_tag_id(&mut self, tag: &str) -> usize {
-        if let Some(&id) = self.tag_registry.get(tag) {
-            id
-        } else {
-            let id = self.tag_registry.len();
-            assert!(id < 128, "ECS only supports up to 128 unique tags");
-            self.tag_registry.insert(tag.to_string(), id);
-            self.tag_names.push(tag.to_string());
-            id
+    /// # Example
+    /// ```ignore
+    /// world.hooks::<Name>()
+    ///     .on_insert(|world, entity| index_name(world, entity))
+    ///     .on_remove(|world, entity| unindex_name(world, entity));
+    /// ```
+    pub fn hooks<C: Component>(&mut self) -> &mut ComponentHooks {
+        self.hooks.entry(TypeId::of::<C>()).or_default()
+    }
+
+    /// Queues the hook of the given kind for a component type, if registered
+    ///
+    /// Nothing is queued while a hook of the same component type runs for
+    /// the entity, so a hook touching its own component doesn't recurse.
+    pub(crate) fn queue_hook(&mut self, kind: HookKind, type_id: TypeId, entity: Entity) {
+        if self.running_hooks.contains(&(type_id, entity)) {
+            return;
+        }
+
+        if let Some(hook) = self.hooks.get(&type_id).and_then(|h| h.get(kind)) {
+            self.pending_hooks.push((hook.clone(), type_id, entity));
+        }
+    }
+
+    /// Runs all queued hooks
+    ///
+    /// Hooks triggered by other hooks are flushed by the nested operation
+    /// that queued them, so each hook sees the World right after its own change.
+    pub(crate) fn flush_hooks(&mut self) {
+        let pending = std::mem::take(&mut self.pending_hooks);
+        for (hook, type_id, entity) in pending {
+            self.running_hooks.insert((type_id, entity));
+            hook(self, entity);
+            self.running_hooks.remove(&(type_id, entity));
         }
     }
 
-    /// Returns the bit index of a tag if it exists (used by query system)
-    pub(crate) fn get_tag_id(&self, tag: &str) -> Option<usize> {
-        self.tag_registry.get(tag).copied()
+    /// Returns true if the component was added to the entity this frame
+    pub fn is_added<C: Component>(&self, entity: Entity) -> bool {
+        self.ticks_by_type_id(entity, &TypeId::of::<C>())
+            .is_some_and(|t| t.is_added(self.last_change_tick, self.change_tick()))
     }
 
-    /// Checks if an entity has a specific tag
+    /// Returns true if the component was added or changed this frame
+    pub fn is_changed<C: Component>(&self, entity: Entity) -> bool {
+        self.ticks_by_type_id(entity, &TypeId::of::<C>())
+            .is_some_and(|t| t.is_changed(self.last_change_tick, self.change_tick()))
+    }
+
+    /// Returns the entities that lost a component of type `C` this frame
     ///
-    /// This is O(1) after the tag name lookup.
+    /// # Example
+    /// ```ignore
+    /// for entity in world.removed::<Health>() {
+    ///     println!("{} lost Health", entity);
+    /// }
+    /// ```
+    pub fn removed<C: Component>(&self) -> RemovedComponents<'_, C> {
+        let entities = self
+            .removed
+            .get(&TypeId::of::<C>())
+            .map(|v| v.as_slice())
+            .unwrap_or(&[]);
+
+        RemovedComponents::new(entities)
+    }
+
+    /// Returns the current change tick
+    #[inline(always)]
+    pub fn change_tick(&self) -> u32 {
+        self.change_tick.load(Ordering::Relaxed)
+    }
+
+    /// Returns the change tick at the last `clear_trackers()`
     #[inline(always)]
-    pub fn has_tag(&self, entity: Entity, tag: &str) -> bool {
-        if let Some(&tag_id) = self.tag_registry.get(tag) {
-            let mask = self.get_tag_mask(entity.id());
-            (mask & (1 << tag_id)) != 0
-        } else {
-            false
+    pub fn last_change_tick(&self) -> u32 {
+        self.last_change_tick
+    }
+
+    /// Advances the change tick and returns the tick before the increment
+    ///
+    /// Used by `QueryState` so changes made after one of its runs get a
+    /// later tick than the run itself.
+    pub(crate) fn advance_change_tick(&self) -> u32 {
+        self.change_tick.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Advances the change tick and clears removed component trackers
+    ///
+    /// Called automatically each frame by `EcsPlugin`. Components added or
+    /// changed before this call no longer match `Added<T>` / `Changed<T>`.
+    /// Enabled spatial hashes are refreshed first.
+    pub fn clear_trackers(&mut self) {
+        self.update_spatial_hashes();
+        self.sync_names();
+        self.last_change_tick = self.advance_change_tick();
+        self.removed.clear();
+    }
+
+    /// Registers a tag and returns its interned handle
+    ///
+    /// Returns the existing handle if the tag is already registered.
+    /// Handles avoid hashing the tag name in `tag`, `has_tag` and queries.
+    pub fn register_tag(&mut self, tag: &str) -> Tag {
+        self.tag_registry.intern(tag)
+    }
+
+    /// Returns the handle of a registered tag
+    pub fn tag_handle(&self, tag: &str) -> Option<Tag> {
+        self.tag_registry.lookup(tag)
+    }
+
+    /// Unregisters a tag so its bit slot can be reused
+    ///
+    /// Existing handles to the tag become stale.
+    /// Returns false if the tag is unknown or still held by an entity.
+    pub fn unregister_tag<'a>(&mut self, tag: impl Into<TagRef<'a>>) -> bool {
+        match self.tag_registry.resolve(tag.into()) {
+            Some(slot) => self.tag_registry.unregister(slot),
+            None => false,
         }
     }
 
-    /// Attaches a tag to an entity
+    /// Unregisters every tag no entity holds and returns how many were removed
+    pub fn prune_tags(&mut self) -> usize {
+        self.tag_registry.prune()
+    }
+
+    /// Returns the number of registered tags
+    pub fn tag_count(&self) -> usize {
+        self.tag_registry.len()
+    }
+
+    /// Returns the number of entities holding a tag
+    pub fn tag_holders<'a>(&self, tag: impl Into<TagRef<'a>>) -> usize {
+        self.tag_registry
+            .resolve(tag.into())
+            .map_or(0, |slot| self.tag_registry.holders(slot))
+    }
+
+    /// Returns the bit slot of a tag if it exists (used by query system)
+    pub(crate) fn get_tag_id(&self, tag: TagRef<'_>) -> Option<usize> {
+        self.tag_registry.resolve(tag)
+    }
+
+    /// Checks if an entity has a specific tag
     ///
-    /// Tags are stored as bitmasks for fast filtering in queries.
-    /// Does nothing if the entity is not alive.
+    /// Accepts a tag name or a `Tag` handle. O(1) for handles,
+    /// one name lookup otherwise.
+    #[inline(always)]
+    pub fn has_tag<'a>(&self, entity: Entity, tag: impl Into<TagRef<'a>>) -> bool {
+        self.tag_registry
+            .resolve(tag.into())
+            .is_some_and(|slot| self.get_tag_mask(entity.id()).contains(slot))
+    }
+
+    /// Attaches a tag to an entity
     ///
-    /// # Panics
-    /// Panics if more than 128 unique tags are created globally.
-    #[track_caller]
-    pub fn tag(&mut self, entity: Entity, tag: &str) {
+    /// Tag names are registered on first use.
+    /// Does nothing if the entity is not alive or the handle is stale.
+    pub fn tag<'a>(&mut self, entity: Entity, tag: impl Into<TagRef<'a>>) {
         if !self.is_alive(entity) {
             return;
         }
 
-        let tag_id = self.get_or_create_tag_id(tag);
+        let Some(slot) = self.tag_registry.resolve_or_intern(tag.into()) else {
+            return;
+        };
         let id = entity.id() as usize;
 
         if id >= self.entity_tags.len() {
-            self.entity_tags.resize(id + 1, 0);
+            self.entity_tags.resize(id + 1, TagMask::new());
         }
 
-        self.entity_tags[id] |= 1 << tag_id;
+        if self.entity_tags[id].insert(slot) {
+            self.tag_registry.add_holder(slot);
+        }
     }
 
-    /// Removes a tag from an entity by clearing its bit in the bitmask
-    pub fn untag(&mut self, entity: Entity, tag: &str) {
-        if let Some(&tag_id) = self.tag_registry.get(tag) {
+    /// Removes a tag from an entity by clearing its bit
+    pub fn untag<'a>(&mut self, entity: Entity, tag: impl Into<TagRef<'a>>) {
+        if let Some(slot) = self.tag_registry.resolve(tag.into()) {
             let id = entity.id() as usize;
-            if id < self.entity_tags.len() {
-                self.entity_tags[id] &= !(1 << tag_id);
+            if let Some(mask) = self.entity_tags.get_mut(id)
+                && mask.remove(slot)
+            {
+                self.tag_registry.remove_holder(slot);
             }
         }
     }
@@ -335,28 +827,21 @@ impl World {
     ///
     /// Primarily used for debugging and inspection.
     pub fn get_entity_tags(&self, entity_id: u32) -> Vec<String> {
-        let mut names = Vec::new();
-
-        if let Some(&mask) = self.entity_tags.get(entity_id as usize) {
-            for i in 0..self.tag_names.len() {
-                if (mask & (1 << i)) != 0 {
-                    names.push(self.tag_names[i].clone());
-                }
-            }
-        }
-
-        names
+        self.get_tag_mask(entity_id)
+            .iter()
+            .filter_map(|slot| self.tag_registry.name(slot))
+            .map(str::to_string)
+            .collect()
     }
 
-    /// Returns the bitmask of all tags for an entity (used by query system)
+    /// Returns the tag bitset of an entity (used by query system)
     ///
-    /// Each bit represents one tag. Used for O(1) tag filtering.
+    /// Each bit represents one tag slot. Used for O(1) tag filtering.
     #[inline(always)]
-    pub(crate) fn get_tag_mask(&self, entity_id: u32) -> u128 {
+    pub(crate) fn get_tag_mask(&self, entity_id: u32) -> &TagMask {
         self.entity_tags
             .get(entity_id as usize)
-            .copied()
-            .unwrap_or(0)
+            .unwrap_or(&EMPTY_MASK)
     }
 
     /// Starts an immutable query
@@ -389,33 +874,100 @@ impl World {
         QueryMut::new(self)
     }
 
-    /// Queues an operation to be executed later via `apply_deferred()`
+    /// Physically sorts the storage of component `C`
     ///
-    /// Useful for spawning/destroying entities during query iteration.
+    /// Queries (`select`, `select_mut` and `QueryState`) whose first
+    /// component is `C` then iterate in this order,
+    /// without per-frame allocation, until a `C` is removed. Sorting is not
+    /// a change for `Changed<C>`.
     ///
     /// # Example
     /// ```ignore
+    /// world.sort_components_by::<Sprite>(|a, b| a.layer.cmp(&b.layer));
+    ///
+    /// world.select::<(Sprite, Position)>().each(|_, (sprite, pos)| {
+    ///     draw(sprite, pos); // back to front
+    /// });
+    /// ```
+    pub fn sort_components_by<C: Component>(
+        &mut self,
+        compare: impl FnMut(&C, &C) -> std::cmp::Ordering,
+    ) {
+        if let Some(set) = self
+            .components
+            .get_mut(&TypeId::of::<C>())
+            .and_then(|s| s.as_any_mut().downcast_mut::<SparseSet<C>>())
+        {
+            set.sort_by(compare);
+        }
+    }
+
+    /// Physically sorts the storage of component `C` by a key
+    ///
+    /// See `sort_components_by`.
+    pub fn sort_components_by_key<C: Component, K: Ord>(&mut self, mut key: impl FnMut(&C) -> K) {
+        self.sort_components_by::<C>(|a, b| key(a).cmp(&key(b)));
+    }
+
+    /// Creates an empty command buffer for this World
+    ///
+    /// The buffer doesn't borrow the World, so it can be filled during query
+    /// iteration and submitted afterwards with `push_commands()`.
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut commands = world.commands();
     /// world.select::<(Health,)>().each(|entity, (health,)| {
     ///     if health.0 <= 0 {
-    ///         world.defer(move |w| {
-    ///             w.destroy(entity);
-    ///         });
+    ///         commands.destroy(entity);
     ///     }
     /// });
-    /// world.apply_deferred();
+    /// world.push_commands(commands);
+    /// ```
+    pub fn commands(&self) -> Commands {
+        Commands::new(self.next_id.clone())
+    }
+
+    /// Queues a command buffer to be applied by `apply_deferred()`
+    ///
+    /// Buffers are applied in the order they are pushed.
+    ///
+    /// # Panics
+    /// Panics if the buffer was created by a different World.
+    pub fn push_commands(&mut self, mut commands: Commands) {
+        self.deferred.append(&mut commands);
+    }
+
+    /// Queues an operation to be executed later via `apply_deferred()`
+    ///
+    /// Prefer `commands()` for spawn/insert/remove/destroy, which doesn't
+    /// allocate a closure per operation.
+    ///
+    /// # Example
+    /// ```ignore
+    /// world.defer(|w| {
+    ///     w.clear_trackers();
+    /// });
     /// ```
     pub fn defer<F: FnOnce(&mut World) + Send + Sync + 'static>(&mut self, f: F) {
-        self.deferred.push(Box::new(f));
+        self.deferred.add(f);
     }
 
-    /// Executes all queued deferred operations
+    /// Executes all queued commands and deferred operations in submission order
     ///
     /// Called automatically each frame by `apply_deferred_system`.
+    /// Commands queued while applying run on the next call.
     pub fn apply_deferred(&mut self) {
-        let deferred = std::mem::take(&mut self.deferred);
-        for f in deferred {
-            f(self);
-        }
+        let fresh = self.commands();
+        let deferred = std::mem::replace(&mut self.deferred, fresh);
+        deferred.apply(self);
+    }
+
+    /// Iterates over all alive entities in no particular order
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        self.alive
+            .iter()
+            .map(|&id| Entity::new(id, self.generations[id as usize]))
     }
 
     /// Returns the number of alive entities
@@ -423,162 +975,61 @@ impl World {
         self.alive.len()
     }
 
+    /// Returns the heap memory used by all component storages
+    pub fn memory_usage(&self) -> MemoryUsage {
+        self.components
+            .values()
+            .map(|storage| storage.memory_usage())
+            .fold(MemoryUsage::default(), |total, usage| total + usage)
+    }
+
+    /// Releases unused capacity of all component storages
+    ///
+    /// Frees sparse pages left empty by destroyed entities. Useful after
+    /// unloading a level.
+    pub fn shrink_to_fit(&mut self) {
+        for storage in self.components.values_mut() {
+            storage.shrink_to_fit();
+        }
+    }
+
     /// No-op in release builds (prints warning)
     ///
-    /// Use debug builds to access memory dump functionality.
-    #[cfg(not(debug_assertions))]
+    /// Use debug builds or enable the `inspect` feature to access memory
+    /// dump functionality.
+    #[cfg(not(any(debug_assertions, feature = "inspect")))]
     pub fn dump_all_memory(&self) {
-        log::warn!("Memory dump is disabled in release mode.");
+        log::warn!("Memory dump is disabled in release mode. Enable the 'inspect' feature.");
     }
 
-    /// Prints physical memory layout of all component storages (debug only)
+    /// Prints the memory layout of all component storages (debug only)
     ///
-    /// Shows memory addresses and byte offsets to verify:
-    /// - Components are stored contiguously
-    /// - Cache-friendly memory access patterns
+    /// Shows the order entities are stored in and their byte offsets, to
+    /// verify that components stay packed after removals. Rendered from
+    /// `inspection()`, see `WorldInspection::memory_table`.
     ///
-    /// Only enabled in debug builds to avoid I/O overhead.
-    #[cfg(debug_assertions)]
+    /// Only enabled in debug builds or with the `inspect` feature to avoid
+    /// I/O overhead.
+    #[cfg(any(debug_assertions, feature = "inspect"))]
     pub fn dump_all_memory(&self) {
-        println!("╔══════════════════════════════════════════════════════════╗");
-        println!("║                   WORLD MEMORY DUMP                      ║");
-        println!("╠══════════════════════════════════════════════════════════╣");
-        println!(
-            "║ Entities: {:<5}          Component Types: {:<5}          ║",
-            self.entity_count(),
-            self.components.len()
-        );
-        println!("╚══════════════════════════════════════════════════════════╝");
-
-        if self.components.is_empty() {
-            println!("(No components registered)");
-        } else {
-            for storage in self.components.values() {
-                storage.dump_memory_layout();
-            }
-        }
+        print!("{}", self.inspection().memory_table());
     }
 
     /// No-op in release builds (prints warning)
     ///
-    /// Use debug builds to access inspection functionality.
-    #[cfg(not(debug_assertions))]
+    /// Use debug builds or enable the `inspect` feature to access inspection
+    /// functionality.
+    #[cfg(not(any(debug_assertions, feature = "inspect")))]
     pub fn inspect(&self) {
-        log::warn!("Inspect is disabled in release mode.");
+        log::warn!("Inspect is disabled in release mode. Enable the 'inspect' feature.");
     }
 
-    /// Prints a formatted table of all entities and their components (debug only)
+    /// No-op in release builds
     ///
-    /// Shows:
-    /// - Entity IDs and generations
-    /// - Attached tags
-    /// - Component values
-    ///
-    /// Useful for debugging game state and verifying entity configurations.
-    /// Only enabled in debug builds to avoid string formatting overhead.
-    #[cfg(debug_assertions)]
-    pub fn inspect(&self) {
-        println!("\n╔══════════════════════════════════════════════════════════════════════════╗");
-        println!("║                            WORLD INSPECTOR                               ║");
-        println!("╠══════════════════════════════════════════════════════════════════════════╣");
-        println!(
-            "║  Entities: {:<5}  Component Types: {:<5}                                 ║",
-            self.alive.len(),
-            self.components.len()
-        );
-        println!("╚══════════════════════════════════════════════════════════════════════════╝\n");
-
-        if self.alive.is_empty() {
-            println!("  (no entities)");
-            return;
-        }
-
-        // Collect data first to calculate widths
-        let mut sorted_ids: Vec<_> = self.alive.iter().copied().collect();
-        sorted_ids.sort();
-
-        let type_names: Vec<String> = self
-            .components
-            .values()
-            .map(|s| {
-                let full = s.type_name();
-                full.rsplit("::").next().unwrap_or(full).to_string()
-            })
-            .collect();
-
-        // Calculate column widths
-        let entity_width = 14;
-        let tags_width = 20;
-
-        let mut col_widths: Vec<usize> = type_names.iter().map(|n| n.len()).collect();
-
-        for id in &sorted_ids {
-            for (i, storage) in self.components.values().enumerate() {
-                let value = storage.debug_entry(*id).unwrap_or("-".to_string());
-                if value.len() > col_widths[i] {
-                    col_widths[i] = value.len();
-                }
-            }
-        }
-
-        // Add padding
-        for w in &mut col_widths {
-            *w = (*w).max(10) + 2;
-        }
-
-        // Header
-        print!("┌{:─<entity_width$}┬{:─<tags_width$}", "", "");
-        for w in &col_widths {
-            print!("┬{:─<w$}", "");
-        }
-        println!("┐");
-
-        print!("│{:<entity_width$}│{:<tags_width$}", " Entity", " Tags");
-        for (name, w) in type_names.iter().zip(&col_widths) {
-            print!("│ {:<width$}", name, width = w - 1);
-        }
-        println!("│");
-
-        print!("├{:─<entity_width$}┼{:─<tags_width$}", "", "");
-        for w in &col_widths {
-            print!("┼{:─<w$}", "");
-        }
-        println!("┤");
-
-        // Rows
-        for id in &sorted_ids {
-            let generation = self.generations[*id as usize];
-            let entity = Entity::new(*id, generation);
-
-            let tags = self.get_entity_tags(*id);
-            let tags_str = if tags.is_empty() {
-                "-".to_string()
-            } else {
-                tags.join(", ")
-            };
-
-            print!(
-                "│ {:<width$}│ {:<tags_w$}",
-                format!("{}", entity),
-                tags_str,
-                width = entity_width - 1,
-                tags_w = tags_width - 1
-            );
-
-            for (storage, w) in self.components.values().zip(&col_widths) {
-                let value = storage.debug_entry(*id).unwrap_or("-".to_string());
-                print!("│ {:<width$}", value, width = w - 1);
-            }
-            println!("│");
-        }
-
-        // Footer
-        print!("└{:─<entity_width$}┴{:─<tags_width$}", "", "");
-        for w in &col_widths {
-            print!("┴{:─<w$}", "");
-        }
-        println!("┘");
-    }
+    /// Diffing is only available in debug builds or with the `inspect`
+    /// feature, so registrations can stay in shared setup code.
+    #[cfg(not(any(debug_assertions, feature = "inspect")))]
+    pub fn register_diffable<C: Component + PartialEq>(&mut self) {}
 }
 
 #[cfg(test)]
@@ -772,7 +1223,7 @@ mod tests {
         world.tag(entity, "friendly");
         world.tag(entity, "tradeable");
         world.destroy(entity);
-        assert_eq!(world.get_tag_mask(entity.id()), 0);
+        assert!(world.get_tag_mask(entity.id()).is_empty());
     }
 
     #[test]
@@ -862,14 +1313,168 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
-    fn world_tag_limit_panic() {
+    fn insert_marks_added_and_changed() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(100)).id();
+
+        assert!(world.is_added::<Health>(entity));
+        assert!(world.is_changed::<Health>(entity));
+
+        world.clear_trackers();
+
+        assert!(!world.is_added::<Health>(entity));
+        assert!(!world.is_changed::<Health>(entity));
+    }
+
+    #[test]
+    fn get_mut_marks_changed() {
+        let mut world = World::new();
+        let entity = world.spawn().insert(Health(100)).id();
+        world.clear_trackers();
+
+        world.get_mut::<Health>(entity).unwrap().0 -= 10;
+
+        assert!(world.is_changed::<Health>(entity));
+        assert!(!world.is_added::<Health>(entity));
+    }
+
+    #[test]
+    fn insert_batch_skips_dead_entities() {
+        let mut world = World::new();
+        world.reserve_entities(3);
+        let e1 = world.spawn().id();
+        let e2 = world.spawn().id();
+        let e3 = world.spawn().id();
+        world.destroy(e2);
+
+        world.insert_batch([(e1, Health(1)), (e2, Health(2)), (e3, Health(3))]);
+
+        assert_eq!(world.get::<Health>(e1), Some(&Health(1)));
+        assert!(!world.has::<Health>(e2));
+        assert_eq!(world.get::<Health>(e3), Some(&Health(3)));
+        assert!(world.is_added::<Health>(e3));
+    }
+
+    #[test]
+    fn removed_tracks_remove_and_destroy() {
+        let mut world = World::new();
+        let e1 = world.spawn().insert(Health(100)).id();
+        let e2 = world.spawn().insert(Health(50)).id();
+
+        world.remove::<Health>(e1);
+        world.destroy(e2);
+
+        let removed: Vec<_> = world.removed::<Health>().collect();
+        assert_eq!(removed, vec![e1, e2]);
+        assert_eq!(world.removed::<Position>().len(), 0);
+
+        world.clear_trackers();
+        assert!(world.removed::<Health>().is_empty());
+    }
+
+    #[test]
+    fn world_supports_more_than_128_tags() {
         let mut world = World::new();
         let entity = world.spawn().id();
 
-        for i in 0..129 {
+        for i in 0..300 {
             let tag_name = format!("tag_{}", i);
             world.tag(entity, &tag_name);
         }
+
+        assert_eq!(world.tag_count(), 300);
+        assert!(world.has_tag(entity, "tag_0"));
+        assert!(world.has_tag(entity, "tag_299"));
+        assert_eq!(world.get_entity_tags(entity.id()).len(), 300);
+    }
+
+    #[test]
+    fn tag_handles_and_recycling() {
+        let mut world = World::new();
+        let e1 = world.spawn().id();
+        let e2 = world.spawn().id();
+
+        let enemy = world.register_tag("enemy");
+        world.tag(e1, enemy);
+        world.tag(e2, "enemy");
+        assert_eq!(world.tag_holders(enemy), 2);
+        assert!(!world.unregister_tag("enemy"));
+
+        world.untag(e1, enemy);
+        world.destroy(e2);
+        assert_eq!(world.tag_holders("enemy"), 0);
+        assert!(world.unregister_tag(enemy));
+
+        // Stale handle no longer matches and the slot is reused
+        assert!(!world.has_tag(e1, enemy));
+        let boss = world.register_tag("boss");
+        assert_eq!(boss.slot(), enemy.slot());
+        world.tag(e1, enemy);
+        assert!(!world.has_tag(e1, boss));
+    }
+
+    #[test]
+    fn prune_tags_keeps_held_tags() {
+        let mut world = World::new();
+        let entity = world.spawn().tag("kept").id();
+        world.register_tag("unused");
+
+        assert_eq!(world.prune_tags(), 1);
+        assert!(world.has_tag(entity, "kept"));
+        assert_eq!(world.tag_count(), 1);
+    }
+
+    #[test]
+    fn shrink_to_fit_releases_pages() {
+        let mut world = World::new();
+        world.reserve_entities(3000);
+        let entities: Vec<Entity> = (0..3000).map(|_| world.spawn().id()).collect();
+        for &entity in &entities {
+            world.insert(entity, Health(1));
+        }
+        let before = world.memory_usage();
+        assert_eq!(before.pages, 3);
+
+        for &entity in &entities[1..] {
+            world.destroy(entity);
+        }
+        world.shrink_to_fit();
+
+        let after = world.memory_usage();
+        assert_eq!(after.pages, 1);
+        assert!(after.total() < before.total());
+        assert_eq!(world.get::<Health>(entities[0]), Some(&Health(1)));
+    }
+
+    #[test]
+    fn entity_bits_round_trip() {
+        let mut world = World::new();
+        let first = world.spawn().id();
+        world.destroy(first);
+        let entity = world.spawn().id();
+        assert_eq!(entity.generation(), 1);
+
+        let bits = entity.to_bits();
+        assert_eq!(Entity::from_bits(bits), entity);
+        assert_eq!(world.get_entity(bits), Some(entity));
+        assert_eq!(world.get_entity(first.to_bits()), None);
+        assert_eq!(world.get_entity(Entity::PLACEHOLDER.to_bits()), None);
+    }
+
+    #[test]
+    fn stable_entity_ids() {
+        let mut world = World::new();
+        let entity = world.spawn().id();
+        let other = world.spawn().id();
+
+        let id = world.assign_entity_id(entity).unwrap();
+        assert_eq!(world.assign_entity_id(entity), Some(id));
+        assert_ne!(world.assign_entity_id(other), Some(id));
+
+        assert_eq!(world.find_entity(id), Some(entity));
+        assert_eq!(EntityId::parse(&id.to_string()), Some(id));
+
+        world.destroy(entity);
+        assert_eq!(world.find_entity(id), None);
     }
 }
@@ -0,0 +1,286 @@
+//! Named Worlds besides the main World
+//!
+//! `EcsPlugin` registers the main `World` plus a `Worlds` resource holding
+//! any number of named Worlds, e.g. for UI, level streaming or an editor
+//! preview. Deferred operations of every named World are applied in the sync
+//! stage together with the main World.
+//!
+//! Systems wrapped with `in_world` see a named World through `ctx.world()`,
+//! so the same system can run against different Worlds. `in_world_parallel`
+//! does the same for parallel systems.
+//!
+//! # Example
+//! ```ignore
+//! fn setup(ctx: &mut Context) {
+//!     ctx.worlds().insert("ui", World::new());
+//!     ctx.world_named("ui").spawn().insert(Button::new("Play"));
+//! }
+//!
+//! Kon::new()
+//!     .add_plugin(DefaultPlugins)
+//!     .add_startup_system(setup)
+//!     .add_system(layout)                   // main World
+//!     .add_system(in_world("ui", layout))   // "ui" World
+//!     .run();
+//! ```
+//!
+//! # Borrowing
+//! All named Worlds share one lock: while a guard from `world_named()` or
+//! `worlds()` is alive, no other named World can be borrowed. Use
+//! `Worlds::get_pair_mut` to work on two at once. For the same reason a
+//! parallel system using a named World declares `write::<Worlds>()` (or
+//! `read::<Worlds>()` if it only calls `world_ref()`), not `World`.
+
+use crate::World;
+use kon_core::{Context, SystemAccess};
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+/// Resource holding named Worlds
+///
+/// Registered by `EcsPlugin`; the main World is not part of it.
+#[derive(Default)]
+pub struct Worlds {
+    worlds: HashMap<String, World>,
+}
+
+impl Worlds {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a World under a name, returning the World it replaced
+    pub fn insert(&mut self, name: &str, world: World) -> Option<World> {
+        self.worlds.insert(name.to_string(), world)
+    }
+
+    /// Removes a World, e.g. to `merge` a streamed level into the main World
+    pub fn remove(&mut self, name: &str) -> Option<World> {
+        self.worlds.remove(name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&World> {
+        self.worlds.get(name)
+    }
+
+    pub fn get_mut(&mut self, name: &str) -> Option<&mut World> {
+        self.worlds.get_mut(name)
+    }
+
+    /// Returns two different Worlds mutably
+    ///
+    /// Returns None if either is missing or both names are equal.
+    pub fn get_pair_mut(&mut self, a: &str, b: &str) -> Option<(&mut World, &mut World)> {
+        if a == b {
+            return None;
+        }
+
+        let [a, b] = self.worlds.get_disjoint_mut([a, b]);
+        Some((a?, b?))
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.worlds.contains_key(name)
+    }
+
+    /// Iterates over all names
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.worlds.keys().map(String::as_str)
+    }
+
+    /// Iterates over all Worlds mutably
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&str, &mut World)> {
+        self.worlds
+            .iter_mut()
+            .map(|(name, world)| (name.as_str(), world))
+    }
+
+    pub fn len(&self) -> usize {
+        self.worlds.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.worlds.is_empty()
+    }
+}
+
+thread_local! {
+    /// Name of the World `ctx.world()` resolves to on this thread, None for
+    /// the main World
+    ///
+    /// Set while a system wrapped with `in_world` runs. Each system runs on a
+    /// single thread, so systems of the same parallel batch don't see each
+    /// other's World.
+    static ACTIVE_WORLD: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// Returns the World name selected by `in_world` for the running system
+pub(crate) fn active_world() -> Option<String> {
+    ACTIVE_WORLD.with(|active| active.borrow().clone())
+}
+
+/// Restores the previously active World, also when the system panics
+struct ActiveWorldGuard(Option<String>);
+
+impl ActiveWorldGuard {
+    fn enter(name: &str) -> Self {
+        Self(ACTIVE_WORLD.with(|active| active.borrow_mut().replace(name.to_string())))
+    }
+}
+
+impl Drop for ActiveWorldGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        ACTIVE_WORLD.with(|active| *active.borrow_mut() = previous);
+    }
+}
+
+/// Runs a system against a named World
+///
+/// Inside the system, `ctx.world()` and `ctx.world_ref()` return the named
+/// World instead of the main one. The selection is per thread, so threads
+/// spawned by the system itself see the main World.
+///
+/// # Panics
+/// The wrapped system panics on `ctx.world()` if the World doesn't exist.
+pub fn in_world<F>(name: &str, mut system: F) -> impl FnMut(&mut Context) + 'static
+where
+    F: FnMut(&mut Context) + 'static,
+{
+    let name = name.to_string();
+
+    move |ctx| {
+        let _active = ActiveWorldGuard::enter(&name);
+        system(ctx);
+    }
+}
+
+/// Runs a parallel system against a named World
+///
+/// Like `in_world`, and adds `write::<Worlds>()` to `access` since the named
+/// World is borrowed through the `Worlds` resource. Returns the access and
+/// the system to pass to `add_parallel_system`.
+///
+/// # Example
+/// ```ignore
+/// let (access, system) = in_world_parallel("ui", SystemAccess::new().write::<Button>(), layout);
+/// app.add_parallel_system(access, system);
+/// ```
+pub fn in_world_parallel<F>(
+    name: &str,
+    access: SystemAccess,
+    mut system: F,
+) -> (SystemAccess, impl FnMut(&Context) + Send + 'static)
+where
+    F: FnMut(&Context) + Send + 'static,
+{
+    let name = name.to_string();
+
+    let system = move |ctx: &Context| {
+        let _active = ActiveWorldGuard::enter(&name);
+        system(ctx);
+    };
+    (access.write::<Worlds>(), system)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ContextEcsExt, EcsPlugin};
+    use kon_core::App;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Health(i32);
+
+    fn app_with_ui_world() -> App {
+        let mut app = App::new();
+        app.add_plugin(EcsPlugin);
+        app.context().worlds().insert("ui", World::new());
+        app
+    }
+
+    #[test]
+    fn named_world_is_separate() {
+        let app = app_with_ui_world();
+        let ctx = app.context();
+
+        ctx.world_named("ui").spawn().insert(Health(1));
+
+        assert_eq!(ctx.world_named_ref("ui").entity_count(), 1);
+        assert_eq!(ctx.world_ref().entity_count(), 0);
+    }
+
+    #[test]
+    fn in_world_redirects_world_access() {
+        let mut app = app_with_ui_world();
+
+        let mut spawn = in_world("ui", |ctx: &mut Context| {
+            ctx.world().spawn().insert(Health(1));
+        });
+        spawn(app.context_mut());
+
+        let ctx = app.context();
+        assert_eq!(ctx.world_named_ref("ui").entity_count(), 1);
+        assert_eq!(ctx.world_ref().entity_count(), 0);
+    }
+
+    #[test]
+    fn parallel_named_world_systems_declare_worlds() {
+        let mut app = app_with_ui_world();
+
+        for _ in 0..2 {
+            let (access, system) = in_world_parallel("ui", SystemAccess::new(), |ctx| {
+                ctx.world().spawn().insert(Health(1));
+            });
+            app.add_parallel_system(access, system);
+        }
+        app.tick();
+
+        let ctx = app.context();
+        assert_eq!(ctx.world_named_ref("ui").entity_count(), 2);
+        assert_eq!(ctx.world_ref().entity_count(), 0);
+    }
+
+    #[test]
+    fn panicking_system_restores_main_world() {
+        let mut app = app_with_ui_world();
+
+        let mut fail = in_world("ui", |_: &mut Context| panic!("system failed"));
+        let ctx = app.context_mut();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| fail(ctx)));
+        assert!(result.is_err());
+
+        app.context().world().spawn().insert(Health(1));
+        assert_eq!(app.context().world_named_ref("ui").entity_count(), 0);
+    }
+
+    #[test]
+    fn sync_stage_applies_named_worlds() {
+        let mut app = app_with_ui_world();
+
+        {
+            let ctx = app.context();
+            let mut ui = ctx.world_named("ui");
+            let mut commands = ui.commands();
+            commands.spawn().insert(Health(1));
+            ui.push_commands(commands);
+        }
+
+        app.tick();
+        assert_eq!(app.context().world_named_ref("ui").entity_count(), 1);
+    }
+
+    #[test]
+    fn pair_access() {
+        let mut worlds = Worlds::new();
+        worlds.insert("a", World::new());
+        worlds.insert("b", World::new());
+
+        let (a, b) = worlds.get_pair_mut("a", "b").unwrap();
+        let entity = a.spawn().insert(Health(5)).id();
+        a.move_entity_to(b, entity);
+
+        assert_eq!(worlds.get("b").unwrap().entity_count(), 1);
+        assert!(worlds.get_pair_mut("a", "a").is_none());
+    }
+}
@@ -3,8 +3,7 @@
 //! Provides convenient `input()` method on Context
 //! instead of manually calling `ctx.global::<Input>()`.
 
-use std::cell::RefMut;
-use kon_core::Context;
+use kon_core::{Context, GlobalMut};
 use crate::Input;
 
 /// Extension trait for convenient Input access from Context
@@ -22,7 +21,7 @@ use crate::Input;
 /// # Panics
 /// Panics if Input is not registered. Ensure `InputPlugin` or `DefaultPlugins` is added.
 pub trait ContextInputExt {
-    fn input(&self) -> RefMut<'_, Input>;
+    fn input(&self) -> GlobalMut<'_, Input>;
 }
 
 impl ContextInputExt for Context {
@@ -31,7 +30,7 @@ impl ContextInputExt for Context {
     /// # Panics
     /// Panics with a helpful message if InputPlugin is not registered
     #[track_caller]
-    fn input(&self) -> RefMut<'_, Input> {
+    fn input(&self) -> GlobalMut<'_, Input> {
         self.global::<Input>()
             .expect("Failed to access Input. Ensure 'DefaultPlugins' or 'InputPlugin' is added")
     }
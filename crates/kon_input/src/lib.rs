@@ -1,3 +1,11 @@
+//! # Kon Input
+//!
+//! Keyboard and mouse input handling for Kon Engine.
+//!
+//! # Example
+//! ```ignore
+//! use kon::prelude::*;
+//!
 //! #[system]
 //! fn player_control(ctx: &mut Context) {
 //!     let input = ctx.input();
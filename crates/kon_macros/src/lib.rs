@@ -9,10 +9,16 @@
 //! ## `#[component]`
 //! Automatically derives Debug, Clone, and PartialEq for component types.
 //! Components must be simple data structures.
+//!
+//! ## `#[derive(Bundle)]`
+//! Implements `kon_ecs::Bundle` for a struct whose fields are all components.
+//! Tags can be attached with `#[bundle(tags = ["player", ...])]`. Works in
+//! crates depending on `kon` as well as on `kon_ecs` directly.
 
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{FnArg, ItemFn, PatType, Type, parse_macro_input};
+use proc_macro_crate::{FoundCrate, crate_name};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, FnArg, ItemFn, LitStr, PatType, Type, parse_macro_input};
 
 /// Marks a function as a system
 ///
@@ -103,6 +109,9 @@ pub fn system(_attr: TokenStream, item: TokenStream) -> TokenStream {
 /// }
 /// ```
 ///
+/// Call `world.register_component::<Position>()` during setup so
+/// `clone_entity` and World diffs use the derived `Clone` and `PartialEq`.
+///
 /// # Requirements
 /// Component types must be:
 /// - Simple structs (no enums or unions)
@@ -119,3 +128,148 @@ pub fn component(_attr: TokenStream, item: TokenStream) -> TokenStream {
 
     output.into()
 }
+
+/// Path to `kon_ecs` from the crate the derive is used in
+///
+/// Engine crates and `kon_ecs` itself depend on `kon_ecs`, games on `kon`.
+fn ecs_path() -> proc_macro2::TokenStream {
+    let found = |found| match found {
+        FoundCrate::Itself => quote! { crate },
+        FoundCrate::Name(name) => {
+            let name = format_ident!("{}", name);
+            quote! { ::#name }
+        }
+    };
+
+    match (crate_name("kon_ecs"), crate_name("kon")) {
+        (Ok(ecs), _) => found(ecs),
+        (Err(_), Ok(kon)) => {
+            let kon = found(kon);
+            quote! { #kon::kon_ecs }
+        }
+        (Err(_), Err(_)) => quote! { ::kon::kon_ecs },
+    }
+}
+
+/// Derives `Bundle` for a struct of components
+///
+/// Every field must be a component. Tags listed in `#[bundle(tags = [...])]`
+/// are attached on insert and detached by `remove_bundle`.
+///
+/// # Example
+/// ```ignore
+/// #[derive(Bundle)]
+/// #[bundle(tags = ["player"])]
+/// struct PlayerBundle {
+///     position: Position,
+///     velocity: Velocity,
+///     health: Health,
+/// }
+///
+/// let player = ctx.world().spawn_bundle(PlayerBundle { ... });
+/// ```
+///
+/// # Errors
+/// Compile error if:
+/// - Used on an enum, union or unit struct
+/// - The `bundle` attribute is malformed
+#[proc_macro_derive(Bundle, attributes(bundle))]
+pub fn derive_bundle(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => fields.named.iter().collect::<Vec<_>>(),
+            Fields::Unnamed(fields) => fields.unnamed.iter().collect::<Vec<_>>(),
+            Fields::Unit => Vec::new(),
+        },
+        _ => {
+            return syn::Error::new_spanned(&input.ident, "Bundle can only be derived for structs")
+                .to_compile_error()
+                .into();
+        }
+    };
+
+    if fields.is_empty() {
+        return syn::Error::new_spanned(&input.ident, "Bundle must have at least one component")
+            .to_compile_error()
+            .into();
+    }
+
+    // Parse #[bundle(tags = ["a", "b"])]
+    let mut tags: Vec<LitStr> = Vec::new();
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("bundle")) {
+        let result = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("tags") {
+                let value = meta.value()?;
+                let content;
+                syn::bracketed!(content in value);
+                let list = content.parse_terminated(|p| p.parse::<LitStr>(), syn::Token![,])?;
+                tags.extend(list);
+                Ok(())
+            } else {
+                Err(meta.error("expected `tags = [...]`"))
+            }
+        });
+
+        if let Err(err) = result {
+            return err.to_compile_error().into();
+        }
+    }
+
+    let types: Vec<_> = fields.iter().map(|f| &f.ty).collect();
+    let accessors: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .map(|(i, f)| match &f.ident {
+            Some(ident) => quote! { #ident },
+            None => {
+                let index = syn::Index::from(i);
+                quote! { #index }
+            }
+        })
+        .collect();
+
+    let columns: Vec<_> = (0..fields.len())
+        .map(|i| format_ident!("__column_{}", i))
+        .collect();
+
+    let ecs = ecs_path();
+    let output = quote! {
+        impl #impl_generics #ecs::Bundle for #name #ty_generics #where_clause {
+            fn insert_into(self, world: &mut #ecs::World, entity: #ecs::Entity) {
+                #(world.insert(entity, self.#accessors);)*
+            }
+
+            fn remove_from(world: &mut #ecs::World, entity: #ecs::Entity) {
+                #(world.remove::<#types>(entity);)*
+            }
+
+            fn type_ids() -> ::std::vec::Vec<::std::any::TypeId> {
+                ::std::vec![#(::std::any::TypeId::of::<#types>()),*]
+            }
+
+            fn tags() -> &'static [&'static str] {
+                &[#(#tags),*]
+            }
+
+            fn insert_batch_into(
+                world: &mut #ecs::World,
+                batch: ::std::vec::Vec<(#ecs::Entity, Self)>,
+            ) {
+                #(let mut #columns: ::std::vec::Vec<(#ecs::Entity, #types)> =
+                    ::std::vec::Vec::with_capacity(batch.len());)*
+
+                for (entity, bundle) in batch {
+                    #(#columns.push((entity, bundle.#accessors));)*
+                }
+
+                #(world.insert_batch(#columns);)*
+            }
+        }
+    };
+
+    output.into()
+}
@@ -1,14 +1,13 @@
-use std::cell::RefMut;
-use kon_core::Context;
+use kon_core::{Context, GlobalMut};
 use crate::KonWindow;
 
 pub trait ContextWindowExt {
-    fn window(&self) -> RefMut<'_, KonWindow>;
+    fn window(&self) -> GlobalMut<'_, KonWindow>;
 }
 
 impl ContextWindowExt for Context {
     #[track_caller]
-    fn window(&self) -> RefMut<'_, KonWindow> {
+    fn window(&self) -> GlobalMut<'_, KonWindow> {
         self.global::<KonWindow>().expect(
             "Failed to access KonWindow. Ensure 'DefaultPlugins' or 'WindowPlugin' is added",
         )
@@ -1,10 +1,11 @@
 use kon_core::{App, Plugin};
-use crate::WindowDriver;
+use crate::{KonWindow, WindowDriver};
 
 pub struct WindowPlugin;
 
 impl Plugin for WindowPlugin {
     fn build(&self, app: &mut App) {
         app.set_driver(WindowDriver);
+        app.pin_to_main_thread::<KonWindow>();
     }
 }
@@ -1,6 +1,6 @@
 //! Lifecycle Demo
 //!
-//! Demonstrates entity spawning, destruction, and deferred operations.
+//! Demonstrates entity spawning (plain and from bundles), destruction, and deferred operations.
 //! Shows how entities can have lifetimes and be destroyed automatically.
 
 use kon::prelude::*;
@@ -11,6 +11,13 @@ struct Health(i32);
 #[component]
 struct Lifetime(u32);
 
+#[derive(Bundle)]
+#[bundle(tags = ["temporary"])]
+struct TemporaryBundle {
+    health: Health,
+    lifetime: Lifetime,
+}
+
 #[system]
 fn setup(ctx: &mut Context) {
     println!("=== Lifecycle Demo ===\n");
@@ -22,12 +29,10 @@ fn setup(ctx: &mut Context) {
         .id();
 
     for i in 1..=3 {
-        ctx.world()
-            .spawn()
-            .insert(Health(10 * i))
-            .insert(Lifetime(i as u32))
-            .tag("temporary")
-            .id();
+        ctx.world().spawn_bundle(TemporaryBundle {
+            health: Health(10 * i),
+            lifetime: Lifetime(i as u32),
+        });
     }
 
     println!("[SETUP] Created 1 permanent + 3 temporary entities\n");
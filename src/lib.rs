@@ -40,7 +40,7 @@
 
 pub use kon_core;
 pub use kon_ecs;
-pub use kon_macros::{component, system};
+pub use kon_macros::{Bundle, component, system};
 pub use kon_window;
 pub use kon_input;
 pub use log;
@@ -50,9 +50,9 @@ use kon_core::Plugin;
 pub mod prelude {
     //! Common imports for Kon Engine
     pub use crate::DefaultPlugins;
-    pub use crate::{component, system};
-    pub use kon_core::{App, Context, Event, Events, Globals, Kon, Plugin, Time, Driver, events::*};
-    pub use kon_ecs::{ContextEcsExt, EcsPlugin, Entity, EntityBuilder, Query, World};
+    pub use crate::{Bundle, component, system};
+    pub use kon_core::{App, Context, Event, Events, Globals, Kon, Plugin, SystemAccess, Time, Driver, events::*};
+    pub use kon_ecs::{Added, Changed, Children, Commands, ContextEcsExt, EcsPlugin, Entity, EntityBuilder, EntityEvent, Name, Parent, Query, SpatialPosition, Trigger, World, Worlds, in_world, in_world_parallel};
     pub use kon_window::{KonWindow, WindowConfig, WindowPlugin, ContextWindowExt, types::*};
     pub use kon_input::{InputPlugin, ContextInputExt, InputSource, Input};
 }